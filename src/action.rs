@@ -0,0 +1,581 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    NextPage,
+    PrevPage,
+    FirstPage,
+    LastPage,
+    GotoPercent,
+    NextChapter,
+    PrevChapter,
+    NextSection,
+    PrevSection,
+    FileInfo,
+    CursorDown,
+    CursorUp,
+    ScrollDown,
+    ScrollUp,
+    HalfPageDown,
+    HalfPageUp,
+    ToggleContinuous,
+    ZoomIn,
+    ZoomOut,
+    ZoomReset,
+    ScrollLeft,
+    ScrollRight,
+    JumpBack,
+    ToggleRecent,
+    NextFile,
+    PrevFile,
+    NextLink,
+    PrevLink,
+    ActivateLink,
+    CopyUrl,
+    ToggleCursorLine,
+    ToggleRuler,
+    CycleNumbers,
+    VisualMode,
+    #[cfg(feature = "tts")]
+    ToggleSpeech,
+    ToggleDiff,
+    ToggleMinimap,
+    ToggleRaw,
+    SwitchPane,
+    MinimapDown,
+    MinimapUp,
+    MinimapJump,
+    ShowImages,
+    ShowLinkList,
+    ShowOutline,
+    ShowPalette,
+    ExternalCommand,
+    PipePage,
+    OpenEditor,
+    AddNote,
+    ShowNotes,
+    SetMark(u8),
+    JumpToMark(u8),
+    AddHighlight,
+    CycleHighlight,
+    RemoveHighlight,
+    SearchForward,
+    SearchBackward,
+    NextMatch,
+    PrevMatch,
+    Command,
+    RepeatLast,
+    Screenshot,
+    Refresh,
+    Help,
+    Quit,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Keymap {
+    #[default]
+    VimIsh,
+    Less,
+}
+
+pub const KEYMAPS: &[&str] = &["vim-ish", "less"];
+
+impl Keymap {
+    pub fn named(name: &str) -> Option<Keymap> {
+        match name {
+            "vim-ish" => Some(Keymap::VimIsh),
+            "less" => Some(Keymap::Less),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Keymap::VimIsh => "vim-ish",
+            Keymap::Less => "less",
+        }
+    }
+
+    fn overrides(self) -> &'static [Binding] {
+        match self {
+            Keymap::VimIsh => &[],
+            Keymap::Less => LESS,
+        }
+    }
+
+    pub fn bindings(self) -> Vec<&'static Binding> {
+        let overrides = self.overrides();
+        overrides.iter()
+            .chain(BINDINGS.iter().filter(|binding| {
+                !overrides.iter().any(|other| other.code == binding.code && other.ctrl == binding.ctrl && other.shift == binding.shift && other.context == binding.context)
+            }))
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Context {
+    Always,
+    CursorLine,
+    Continuous,
+    Minimap,
+}
+
+pub struct Binding {
+    pub code: KeyCode,
+    pub ctrl: bool,
+    pub shift: bool,
+    pub context: Context,
+    pub action: Action,
+}
+
+const fn key(code: KeyCode, action: Action) -> Binding {
+    Binding { code, ctrl: false, shift: false, context: Context::Always, action }
+}
+
+const fn ctrl(c: char, action: Action) -> Binding {
+    Binding { code: KeyCode::Char(c), ctrl: true, shift: false, context: Context::Always, action }
+}
+
+const fn shift(code: KeyCode, action: Action) -> Binding {
+    Binding { code, ctrl: false, shift: true, context: Context::Always, action }
+}
+
+const fn when(context: Context, code: KeyCode, action: Action) -> Binding {
+    Binding { code, ctrl: false, shift: false, context, action }
+}
+
+pub const BINDINGS: &[Binding] = &[
+    ctrl('o', Action::JumpBack),
+    ctrl('^', Action::ToggleRecent),
+    ctrl('6', Action::ToggleRecent),
+    ctrl('r', Action::ToggleRaw),
+    ctrl('g', Action::FileInfo),
+    ctrl('w', Action::SwitchPane),
+    ctrl('s', Action::Screenshot),
+    ctrl('n', Action::NextFile),
+    ctrl('b', Action::PrevFile),
+    ctrl('p', Action::ShowPalette),
+    key(KeyCode::Tab, Action::NextLink),
+    key(KeyCode::BackTab, Action::PrevLink),
+    key(KeyCode::Char('m'), Action::ToggleMinimap),
+    when(Context::Minimap, KeyCode::Down, Action::MinimapDown),
+    when(Context::Minimap, KeyCode::Up, Action::MinimapUp),
+    when(Context::Minimap, KeyCode::Enter, Action::MinimapJump),
+    key(KeyCode::Enter, Action::ActivateLink),
+    key(KeyCode::Char('Y'), Action::CopyUrl),
+    key(KeyCode::Char('c'), Action::ToggleCursorLine),
+    key(KeyCode::Char('R'), Action::ToggleRuler),
+    key(KeyCode::Char('#'), Action::CycleNumbers),
+    key(KeyCode::Char('s'), Action::ToggleContinuous),
+    key(KeyCode::Char('+'), Action::ZoomIn),
+    key(KeyCode::Char('='), Action::ZoomIn),
+    key(KeyCode::Char('-'), Action::ZoomOut),
+    key(KeyCode::Char('0'), Action::ZoomReset),
+    key(KeyCode::Char('v'), Action::VisualMode),
+    when(Context::Continuous, KeyCode::Char('j'), Action::ScrollDown),
+    when(Context::Continuous, KeyCode::Down, Action::ScrollDown),
+    when(Context::Continuous, KeyCode::Char('k'), Action::ScrollUp),
+    when(Context::Continuous, KeyCode::Up, Action::ScrollUp),
+    when(Context::CursorLine, KeyCode::Char('j'), Action::CursorDown),
+    when(Context::CursorLine, KeyCode::Down, Action::CursorDown),
+    when(Context::CursorLine, KeyCode::Char('k'), Action::CursorUp),
+    when(Context::CursorLine, KeyCode::Up, Action::CursorUp),
+    #[cfg(feature = "tts")]
+    key(KeyCode::Char('p'), Action::ToggleSpeech),
+    key(KeyCode::Char('d'), Action::ToggleDiff),
+    key(KeyCode::Char('i'), Action::ShowImages),
+    key(KeyCode::Char('F'), Action::ShowLinkList),
+    key(KeyCode::Char('t'), Action::ShowOutline),
+    key(KeyCode::Char('!'), Action::ExternalCommand),
+    key(KeyCode::Char('|'), Action::PipePage),
+    key(KeyCode::Char('E'), Action::OpenEditor),
+    key(KeyCode::Char('N'), Action::AddNote),
+    key(KeyCode::Char('A'), Action::ShowNotes),
+    key(KeyCode::Char('H'), Action::AddHighlight),
+    key(KeyCode::Char('C'), Action::CycleHighlight),
+    key(KeyCode::Char('U'), Action::RemoveHighlight),
+    shift(KeyCode::Left, Action::ScrollLeft),
+    shift(KeyCode::Right, Action::ScrollRight),
+    key(KeyCode::Left, Action::PrevPage),
+    key(KeyCode::Char('h'), Action::PrevPage),
+    key(KeyCode::Right, Action::NextPage),
+    key(KeyCode::Char('l'), Action::NextPage),
+    key(KeyCode::PageDown, Action::NextPage),
+    key(KeyCode::PageUp, Action::PrevPage),
+    key(KeyCode::Home, Action::FirstPage),
+    key(KeyCode::Char('g'), Action::FirstPage),
+    key(KeyCode::End, Action::LastPage),
+    key(KeyCode::Char('G'), Action::LastPage),
+    key(KeyCode::Char('%'), Action::GotoPercent),
+    key(KeyCode::Char('}'), Action::NextChapter),
+    key(KeyCode::Char('{'), Action::PrevChapter),
+    key(KeyCode::Char(']'), Action::NextSection),
+    key(KeyCode::Char('['), Action::PrevSection),
+    key(KeyCode::Char('q'), Action::Quit),
+    key(KeyCode::Esc, Action::Quit),
+    key(KeyCode::Char('r'), Action::Refresh),
+    key(KeyCode::Char('?'), Action::Help),
+    key(KeyCode::Char(':'), Action::Command),
+    key(KeyCode::Char('.'), Action::RepeatLast),
+    key(KeyCode::Char('/'), Action::SearchForward),
+    key(KeyCode::Char('\\'), Action::SearchBackward),
+    key(KeyCode::Char('n'), Action::NextMatch),
+    key(KeyCode::Char(','), Action::PrevMatch),
+];
+
+const LESS: &[Binding] = &[
+    key(KeyCode::Char(' '), Action::NextPage),
+    key(KeyCode::Char('f'), Action::NextPage),
+    key(KeyCode::Char('b'), Action::PrevPage),
+    key(KeyCode::Char('d'), Action::HalfPageDown),
+    key(KeyCode::Char('u'), Action::HalfPageUp),
+    key(KeyCode::Char('='), Action::FileInfo),
+    key(KeyCode::Char('N'), Action::PrevMatch),
+    key(KeyCode::Char('D'), Action::ToggleDiff),
+    key(KeyCode::Char('a'), Action::AddNote),
+    key(KeyCode::Char('v'), Action::OpenEditor),
+    key(KeyCode::Char('V'), Action::VisualMode),
+];
+
+pub const CATEGORIES: &[&str] = &["Navegación", "Búsqueda", "Marcas y notas", "Enlaces", "Pantalla", "General"];
+
+pub const ALL: &[Action] = &[
+    Action::PrevPage,
+    Action::NextPage,
+    Action::FirstPage,
+    Action::LastPage,
+    Action::GotoPercent,
+    Action::NextChapter,
+    Action::PrevChapter,
+    Action::NextSection,
+    Action::PrevSection,
+    Action::JumpBack,
+    Action::ToggleRecent,
+    Action::NextFile,
+    Action::PrevFile,
+    Action::ShowOutline,
+    Action::ToggleCursorLine,
+    Action::ToggleRuler,
+    Action::CycleNumbers,
+    Action::CursorDown,
+    Action::CursorUp,
+    Action::ToggleContinuous,
+    Action::ScrollDown,
+    Action::ScrollUp,
+    Action::HalfPageDown,
+    Action::HalfPageUp,
+    Action::ScrollLeft,
+    Action::ScrollRight,
+    Action::ToggleMinimap,
+    Action::MinimapDown,
+    Action::MinimapUp,
+    Action::MinimapJump,
+    Action::SearchForward,
+    Action::SearchBackward,
+    Action::NextMatch,
+    Action::PrevMatch,
+    Action::SetMark(1),
+    Action::JumpToMark(1),
+    Action::AddNote,
+    Action::ShowNotes,
+    Action::AddHighlight,
+    Action::CycleHighlight,
+    Action::RemoveHighlight,
+    Action::VisualMode,
+    Action::NextLink,
+    Action::PrevLink,
+    Action::ActivateLink,
+    Action::CopyUrl,
+    Action::ShowLinkList,
+    Action::ShowImages,
+    Action::ToggleDiff,
+    Action::ToggleRaw,
+    Action::SwitchPane,
+    Action::ZoomIn,
+    Action::ZoomOut,
+    Action::ZoomReset,
+    #[cfg(feature = "tts")]
+    Action::ToggleSpeech,
+    Action::FileInfo,
+    Action::Command,
+    Action::ShowPalette,
+    Action::ExternalCommand,
+    Action::PipePage,
+    Action::OpenEditor,
+    Action::RepeatLast,
+    Action::Screenshot,
+    Action::Refresh,
+    Action::Help,
+    Action::Quit,
+];
+
+pub struct KeyState {
+    pub cursor_line: bool,
+    pub continuous: bool,
+    pub minimap: bool,
+    pub keymap: Keymap,
+    pub help_key: Option<KeyCode>,
+}
+
+impl Action {
+    pub fn from_key(key: KeyEvent, state: &KeyState) -> Option<Action> {
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        if !ctrl && state.help_key == Some(key.code) {
+            return Some(Action::Help);
+        }
+        let shift = key.modifiers.contains(KeyModifiers::SHIFT) && !matches!(key.code, KeyCode::Char(_));
+        state.keymap.bindings()
+            .into_iter()
+            .filter(|binding| binding.code == key.code && binding.ctrl == ctrl && binding.shift == shift)
+            .find(|binding| match binding.context {
+                Context::Always => true,
+                Context::CursorLine => state.cursor_line,
+                Context::Continuous => state.continuous,
+                Context::Minimap => state.minimap,
+            })
+            .map(|binding| binding.action)
+    }
+
+    pub fn from_prefixed(prefix: char, key: KeyEvent) -> Option<Action> {
+        let KeyCode::Char(digit @ '1'..='9') = key.code else {
+            return None;
+        };
+        let slot = digit as u8 - b'0';
+        match prefix {
+            'M' => Some(Action::SetMark(slot)),
+            '\'' => Some(Action::JumpToMark(slot)),
+            _ => None,
+        }
+    }
+
+    pub fn keys(self, keymap: Keymap) -> Vec<String> {
+        match self {
+            Action::SetMark(_) => return vec!["M1..M9".to_string()],
+            Action::JumpToMark(_) => return vec!["'1..'9".to_string()],
+            _ => {}
+        }
+        keymap.bindings()
+            .into_iter()
+            .filter(|binding| binding.action == self)
+            .map(binding_name)
+            .collect()
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Action::NextPage => "next_page",
+            Action::PrevPage => "prev_page",
+            Action::FirstPage => "first_page",
+            Action::LastPage => "last_page",
+            Action::GotoPercent => "goto_percent",
+            Action::NextChapter => "next_chapter",
+            Action::PrevChapter => "prev_chapter",
+            Action::NextSection => "next_section",
+            Action::PrevSection => "prev_section",
+            Action::FileInfo => "file_info",
+            Action::CursorDown => "cursor_down",
+            Action::CursorUp => "cursor_up",
+            Action::ScrollDown => "scroll_down",
+            Action::ScrollUp => "scroll_up",
+            Action::HalfPageDown => "half_page_down",
+            Action::HalfPageUp => "half_page_up",
+            Action::ToggleContinuous => "toggle_continuous",
+            Action::ZoomIn => "zoom_in",
+            Action::ZoomOut => "zoom_out",
+            Action::ZoomReset => "zoom_reset",
+            Action::ScrollLeft => "scroll_left",
+            Action::ScrollRight => "scroll_right",
+            Action::JumpBack => "jump_back",
+            Action::ToggleRecent => "toggle_recent",
+            Action::NextFile => "next_file",
+            Action::PrevFile => "prev_file",
+            Action::NextLink => "next_link",
+            Action::PrevLink => "prev_link",
+            Action::ActivateLink => "activate_link",
+            Action::CopyUrl => "copy_url",
+            Action::ToggleCursorLine => "toggle_cursor_line",
+            Action::ToggleRuler => "toggle_ruler",
+            Action::CycleNumbers => "cycle_numbers",
+            Action::VisualMode => "visual_mode",
+            #[cfg(feature = "tts")]
+            Action::ToggleSpeech => "toggle_speech",
+            Action::ToggleDiff => "toggle_diff",
+            Action::ToggleMinimap => "toggle_minimap",
+            Action::ToggleRaw => "toggle_raw",
+            Action::SwitchPane => "switch_pane",
+            Action::MinimapDown => "minimap_down",
+            Action::MinimapUp => "minimap_up",
+            Action::MinimapJump => "minimap_jump",
+            Action::ShowImages => "show_images",
+            Action::ShowLinkList => "show_link_list",
+            Action::ShowOutline => "show_outline",
+            Action::ShowPalette => "show_palette",
+            Action::ExternalCommand => "external_command",
+            Action::PipePage => "pipe_page",
+            Action::OpenEditor => "open_editor",
+            Action::AddNote => "add_note",
+            Action::ShowNotes => "show_notes",
+            Action::SetMark(_) => "set_mark",
+            Action::JumpToMark(_) => "jump_to_mark",
+            Action::AddHighlight => "add_highlight",
+            Action::CycleHighlight => "cycle_highlight",
+            Action::RemoveHighlight => "remove_highlight",
+            Action::SearchForward => "search_forward",
+            Action::SearchBackward => "search_backward",
+            Action::NextMatch => "next_match",
+            Action::PrevMatch => "prev_match",
+            Action::Command => "command",
+            Action::RepeatLast => "repeat_last",
+            Action::Screenshot => "screenshot",
+            Action::Refresh => "refresh",
+            Action::Help => "help",
+            Action::Quit => "quit",
+        }
+    }
+
+    pub fn describe(self) -> (&'static str, &'static str) {
+        match self {
+            Action::NextPage => ("Navegación", "Página siguiente"),
+            Action::PrevPage => ("Navegación", "Página anterior"),
+            Action::FirstPage => ("Navegación", "Primera página (5g: página 5)"),
+            Action::LastPage => ("Navegación", "Última página (5G: página 5)"),
+            Action::GotoPercent => ("Navegación", "Ir a un punto del documento (50%: la mitad; también :50%)"),
+            Action::NextChapter => ("Navegación", "Capítulo siguiente según el índice (sin índice, página siguiente del PDF)"),
+            Action::PrevChapter => ("Navegación", "Principio del capítulo o capítulo anterior"),
+            Action::NextSection => ("Navegación", "Siguiente título del índice (al comparar, siguiente cambio)"),
+            Action::PrevSection => ("Navegación", "Título anterior del índice (al comparar, cambio anterior)"),
+            Action::JumpBack => ("Navegación", "Volver atrás"),
+            Action::ToggleRecent => ("Navegación", "Alternar con la última página leída (más de 1 s en ella)"),
+            Action::NextFile => ("Navegación", "Abrir el PDF siguiente de la carpeta, donde lo dejaste"),
+            Action::PrevFile => ("Navegación", "Abrir el PDF anterior de la carpeta"),
+            Action::ShowOutline => ("Navegación", "Índice: escribe para filtrar (o clic en la cabecera)"),
+            Action::ToggleCursorLine => ("Navegación", "Línea de cursor"),
+            Action::ToggleRuler => ("Navegación", "Regla de lectura: resalta la línea (j/k) y atenúa el resto de la página"),
+            Action::CycleNumbers => ("Pantalla", "Números de línea: no, absolutos o relativos a la línea de cursor"),
+            Action::CursorDown => ("Navegación", "Bajar la línea de cursor"),
+            Action::CursorUp => ("Navegación", "Subir la línea de cursor"),
+            Action::ToggleContinuous => ("Navegación", "Lectura continua: el documento como un solo rollo, sin páginas"),
+            Action::ScrollDown => ("Navegación", "Lectura continua: bajar una línea"),
+            Action::ScrollUp => ("Navegación", "Lectura continua: subir una línea"),
+            Action::HalfPageDown => ("Navegación", "Bajar media pantalla (en vista paginada avanza una página entera)"),
+            Action::HalfPageUp => ("Navegación", "Subir media pantalla (en vista paginada retrocede una página entera)"),
+            Action::ScrollLeft => ("Navegación", "Desplazar a la izquierda las líneas anchas (<)"),
+            Action::ScrollRight => ("Navegación", "Desplazar a la derecha las líneas anchas (>)"),
+            Action::ToggleMinimap => ("Navegación", "Mapa del documento"),
+            Action::MinimapDown => ("Navegación", "Mapa: bajar"),
+            Action::MinimapUp => ("Navegación", "Mapa: subir"),
+            Action::MinimapJump => ("Navegación", "Mapa: ir a la página elegida"),
+            Action::SearchForward => ("Búsqueda", "Buscar hacia adelante (↑/↓ historial)"),
+            Action::SearchBackward => ("Búsqueda", "Buscar hacia atrás"),
+            Action::NextMatch => ("Búsqueda", "Siguiente coincidencia"),
+            Action::PrevMatch => ("Búsqueda", "Coincidencia anterior"),
+            Action::SetMark(_) => ("Marcas y notas", "Guardar marca rápida"),
+            Action::JumpToMark(_) => ("Marcas y notas", "Ir a la marca rápida"),
+            Action::AddNote => ("Marcas y notas", "Añadir nota a la página"),
+            Action::ShowNotes => ("Marcas y notas", "Lista de notas"),
+            Action::AddHighlight => ("Marcas y notas", "Resaltar la línea del cursor (o la selección en modo visual)"),
+            Action::CycleHighlight => ("Marcas y notas", "Cambiar el color del resaltado"),
+            Action::RemoveHighlight => ("Marcas y notas", "Quitar el resaltado"),
+            Action::VisualMode => ("Marcas y notas", "Seleccionar texto (h/j/k/l, w/b, 0/$; y copia, H resalta, | la pasa por una orden, ESC sale)"),
+            Action::NextLink => ("Enlaces", "Seleccionar enlace siguiente"),
+            Action::PrevLink => ("Enlaces", "Seleccionar enlace anterior"),
+            Action::ActivateLink => ("Enlaces", "Seguir enlace / abrir URL"),
+            Action::CopyUrl => ("Enlaces", "Copiar URL seleccionada"),
+            Action::ShowLinkList => ("Enlaces", "Lista de enlaces"),
+            Action::ShowImages => ("Pantalla", "Imágenes de la página"),
+            Action::ToggleDiff => ("Pantalla", "Comparar con el segundo PDF"),
+            Action::ToggleRaw => ("Pantalla", "Texto extraído sin procesar (RAW)"),
+            Action::ZoomIn => ("Pantalla", "Texto más ancho"),
+            Action::ZoomOut => ("Pantalla", "Texto más estrecho, centrado en la terminal"),
+            Action::ZoomReset => ("Pantalla", "Ancho del texto según la terminal"),
+            Action::SwitchPane => ("Pantalla", "Cambiar de panel en la vista dividida (:split, :vsplit, :only)"),
+            #[cfg(feature = "tts")]
+            Action::ToggleSpeech => ("Pantalla", "Leer en voz alta / detener"),
+            Action::FileInfo => ("General", "Archivo, página y porcentaje leído"),
+            Action::Command => ("General", "Órdenes (:set sin argumentos lista las opciones)"),
+            Action::ShowPalette => ("General", "Paleta: busca cualquier acción por su nombre y ejecútala"),
+            Action::ExternalCommand => ("General", "Pasar la página a la orden externa (external_command)"),
+            Action::PipePage => ("General", "Pasar la página (o la selección en modo visual) por una orden: | wc -w"),
+            Action::OpenEditor => ("General", "Abrir el texto extraído en $EDITOR, en la página actual"),
+            Action::RepeatLast => ("General", "Repetir la última orden"),
+            Action::Screenshot => ("General", "Guardar la pantalla en un archivo de texto (screenshot_ansi conserva los colores)"),
+            Action::Refresh => ("General", "Refrescar"),
+            Action::Help => ("General", "Mostrar ayuda"),
+            Action::Quit => ("General", "Salir"),
+        }
+    }
+
+    pub fn repeatable(self) -> bool {
+        !matches!(
+            self,
+            Action::ActivateLink
+                | Action::FileInfo
+                | Action::VisualMode
+                | Action::CopyUrl
+                | Action::ShowImages
+                | Action::ShowLinkList
+                | Action::ShowOutline
+                | Action::ShowPalette
+                | Action::ExternalCommand
+                | Action::OpenEditor
+                | Action::PipePage
+                | Action::AddNote
+                | Action::ShowNotes
+                | Action::SetMark(_)
+                | Action::JumpToMark(_)
+                | Action::SearchForward
+                | Action::SearchBackward
+                | Action::Command
+                | Action::RepeatLast
+                | Action::Screenshot
+                | Action::Refresh
+                | Action::Help
+                | Action::Quit
+        )
+    }
+}
+
+fn binding_name(binding: &Binding) -> String {
+    let name = match binding.code {
+        KeyCode::Char(' ') => "Espacio".to_string(),
+        KeyCode::Char(c) => c.to_string(),
+        KeyCode::Left => "←".to_string(),
+        KeyCode::Right => "→".to_string(),
+        KeyCode::Up => "↑".to_string(),
+        KeyCode::Down => "↓".to_string(),
+        KeyCode::BackTab => "Shift+Tab".to_string(),
+        KeyCode::Esc => "ESC".to_string(),
+        other => format!("{:?}", other),
+    };
+    if binding.shift {
+        return format!("Shift+{}", name);
+    }
+    if binding.ctrl {
+        format!("Ctrl+{}", name.to_uppercase())
+    } else {
+        name
+    }
+}
+
+pub fn parse_key(name: &str) -> Option<KeyCode> {
+    let mut chars = name.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => Some(KeyCode::Char(c)),
+        _ => name.strip_prefix('F')?.parse().ok().filter(|n| (1..=12).contains(n)).map(KeyCode::F),
+    }
+}
+
+pub fn dump_keys(keymap: Keymap) {
+    for binding in keymap.bindings() {
+        let context = match binding.context {
+            Context::Always => "",
+            Context::CursorLine => "cursor_line",
+            Context::Continuous => "continuous",
+            Context::Minimap => "minimap",
+        };
+        println!("{}\t{}\t{}", binding_name(binding), binding.action.name(), context);
+    }
+    println!("M1..M9\tset_mark\t");
+    println!("'1..'9\tjump_to_mark\t");
+}
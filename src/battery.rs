@@ -0,0 +1,8 @@
+use battery::units::ratio::percent;
+use battery::{Manager, State};
+
+pub fn status() -> Option<String> {
+    let battery = Manager::new().ok()?.batteries().ok()?.flatten().next()?;
+    let charging = battery.state() == State::Charging;
+    Some(format!("{}{:.0}%", if charging { "⚡" } else { "🔋" }, battery.state_of_charge().get::<percent>()))
+}
@@ -0,0 +1,64 @@
+use textwrap::core::Word;
+use textwrap::{Options, WordSeparator, WordSplitter};
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Break {
+    #[default]
+    Anywhere,
+    Never,
+    UrlAware,
+}
+
+pub const NAMES: &[&str] = &["anywhere", "never", "url-aware"];
+
+pub fn policy(name: &str) -> Break {
+    match name {
+        "never" => Break::Never,
+        "url-aware" => Break::UrlAware,
+        _ => Break::Anywhere,
+    }
+}
+
+pub fn apply(options: Options<'_>, policy: Break) -> Options<'_> {
+    match policy {
+        Break::Anywhere => options,
+        Break::Never => options.break_words(false).word_separator(WordSeparator::AsciiSpace).word_splitter(WordSplitter::NoHyphenation),
+        Break::UrlAware => options.word_separator(WordSeparator::Custom(url_words)),
+    }
+}
+
+fn url_words(line: &str) -> Box<dyn Iterator<Item = Word<'_>> + '_> {
+    let mut words = Vec::new();
+    let mut start = 0;
+    let mut chars = line.char_indices().peekable();
+    while let Some((index, c)) = chars.next() {
+        let next = chars.peek().map(|(_, next)| *next);
+        let ends_word = match next {
+            None => true,
+            Some(next) if c == ' ' => next != ' ',
+            Some(next) => next != ' ' && matches!(c, '/' | '?' | '&' | '-'),
+        };
+        if ends_word {
+            let end = index + c.len_utf8();
+            words.push(Word::from(&line[start..end]));
+            start = end;
+        }
+    }
+    Box::new(words.into_iter())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_policy_wraps_like_plain_textwrap() {
+        let text = "Véase https://example.com/ruta/muy/larga?consulta=uno&otra=dos para más detalles.";
+        let default = |width| textwrap::wrap(text, apply(Options::new(width), policy("anywhere")));
+        for width in [12, 20, 33] {
+            assert_eq!(default(width), textwrap::wrap(text, Options::new(width)));
+        }
+        assert_eq!(policy("desconocido"), Break::default());
+        assert_ne!(textwrap::wrap(text, apply(Options::new(20), policy("url-aware"))), default(20));
+    }
+}
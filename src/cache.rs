@@ -0,0 +1,44 @@
+use std::collections::VecDeque;
+use std::rc::Rc;
+
+pub struct Lru<K, V> {
+    capacity: usize,
+    entries: VecDeque<(K, Rc<V>)>,
+    pub hits: usize,
+    pub misses: usize,
+}
+
+impl<K: PartialEq, V> Lru<K, V> {
+    pub fn new(capacity: usize) -> Self {
+        Lru { capacity, entries: VecDeque::new(), hits: 0, misses: 0 }
+    }
+
+    pub fn get_or_insert_with(&mut self, key: K, make: impl FnOnce() -> V) -> Rc<V> {
+        let position = self.entries.iter().position(|(k, _)| *k == key);
+        if let Some(entry) = position.and_then(|position| self.entries.remove(position)) {
+            self.hits += 1;
+            let value = Rc::clone(&entry.1);
+            self.entries.push_front(entry);
+            return value;
+        }
+        self.misses += 1;
+        let value = Rc::new(make());
+        if self.capacity > 0 {
+            self.entries.truncate(self.capacity - 1);
+            self.entries.push_front((key, Rc::clone(&value)));
+        }
+        value
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+}
@@ -0,0 +1,382 @@
+use clap::error::ErrorKind;
+use clap::{CommandFactory, Parser, ValueHint};
+
+use crate::exit::ExitCode;
+
+pub const OPTIONS_VAR: &str = "PDFVIEW_OPTS";
+
+pub fn parse() -> Cli {
+    let mut args = goto_shorthand(std::env::args().collect());
+    if args.get(1).is_some_and(|arg| arg == "completions") {
+        match args.get(2).and_then(|shell| crate::completions::generate(shell, Cli::command())) {
+            Some(script) => {
+                print!("{}", script);
+                std::process::exit(0);
+            }
+            None => {
+                eprintln!("❌ Uso: {} completions {}", Cli::command().get_name(), crate::completions::SHELLS.join("|"));
+                ExitCode::Usage.exit();
+            }
+        }
+    }
+    let defaults = std::env::var(OPTIONS_VAR).ok().filter(|_| !gives_option(&args[1..], "ignore_env"));
+    if let Some(defaults) = defaults.as_deref().map(split_options).filter(|options| !options.is_empty()) {
+        let program = args.first().cloned().unwrap_or_default();
+        let error = match Cli::try_parse_from(std::iter::once(program).chain(defaults.iter().cloned())) {
+            Ok(cli) if cli.file.is_some() => Some("solo admite opciones, no archivos".to_string()),
+            Ok(_) => None,
+            Err(e) if matches!(e.kind(), ErrorKind::DisplayHelp | ErrorKind::DisplayVersion) => e.exit(),
+            Err(e) => Some(e.render().to_string().lines().next().unwrap_or_default().trim_start_matches("error: ").to_string()),
+        };
+        if let Some(error) = error {
+            eprintln!("❌ Error en {}=\"{}\": {}", OPTIONS_VAR, defaults.join(" "), error);
+            ExitCode::Usage.exit();
+        }
+        let defaults = without_overridden(defaults, &args[1..]);
+        args.splice(1..1, defaults);
+    }
+    let mut cli = Cli::parse_from(args);
+    let home = std::env::var("HOME").ok();
+    cli.file = cli.file.map(|path| expand_path(&path, home.as_deref()));
+    cli.compare = cli.compare.map(|path| expand_path(&path, home.as_deref()));
+    cli.config = cli.config.map(|path| expand_path(&path, home.as_deref()));
+    cli
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Role {
+    Option(clap::Id),
+    Value,
+    Positional,
+    Rest,
+    Other,
+}
+
+fn roles(args: &[String]) -> Vec<Role> {
+    let command = Cli::command();
+    let mut roles: Vec<Role> = Vec::with_capacity(args.len());
+    let mut value_next = false;
+    for arg in args {
+        let role = if std::mem::take(&mut value_next) {
+            Role::Value
+        } else if arg == "--" || roles.last() == Some(&Role::Rest) {
+            Role::Rest
+        } else if let Some(option) = arg.strip_prefix("--") {
+            let (name, inline) = option.split_once('=').map_or((option, false), |(name, _)| (name, true));
+            match command.get_arguments().find(|found| found.get_long() == Some(name)) {
+                Some(found) => {
+                    value_next = !inline && found.get_action().takes_values();
+                    Role::Option(found.get_id().clone())
+                }
+                None => Role::Other,
+            }
+        } else if arg.starts_with('-') && arg != "-" {
+            Role::Other
+        } else {
+            Role::Positional
+        };
+        roles.push(role);
+    }
+    roles
+}
+
+fn goto_shorthand(mut args: Vec<String>) -> Vec<String> {
+    let roles = roles(args.get(1..).unwrap_or_default());
+    for (arg, role) in args.iter_mut().skip(1).zip(roles) {
+        match arg.strip_prefix('+') {
+            Some(page) if role == Role::Positional && !page.is_empty() && page.bytes().all(|b| b.is_ascii_digit()) => {
+                *arg = format!("--goto={}", page);
+            }
+            _ => {}
+        }
+    }
+    args
+}
+
+fn gives_option(args: &[String], id: &str) -> bool {
+    roles(args).iter().any(|role| matches!(role, Role::Option(given) if given.as_str() == id))
+}
+
+fn without_overridden(defaults: Vec<String>, args: &[String]) -> Vec<String> {
+    let command = Cli::command();
+    let given: Vec<clap::Id> = roles(args).into_iter()
+        .filter_map(|role| match role {
+            Role::Option(id) => Some(id),
+            _ => None,
+        })
+        .collect();
+    let conflicts = |a: &clap::Id, b: &clap::Id| {
+        let one_way = |from: &clap::Id, to: &clap::Id| command.get_arguments()
+            .find(|arg| arg.get_id() == from)
+            .is_some_and(|arg| command.get_arg_conflicts_with(arg).iter().any(|other| other.get_id() == to));
+        a == b || one_way(a, b) || one_way(b, a)
+    };
+    let mut keep = true;
+    let roles = roles(&defaults);
+    defaults.into_iter()
+        .zip(roles)
+        .filter(|(_, role)| {
+            match role {
+                Role::Option(id) => keep = !given.iter().any(|other| conflicts(id, other)),
+                Role::Value => {}
+                _ => keep = true,
+            }
+            keep
+        })
+        .map(|(option, _)| option)
+        .collect()
+}
+
+fn split_options(value: &str) -> Vec<String> {
+    let mut options = Vec::new();
+    let mut current: Option<String> = None;
+    let mut quote = None;
+    for c in value.chars() {
+        match (quote, c) {
+            (Some(open), c) if c == open => quote = None,
+            (None, '"' | '\'') => {
+                quote = Some(c);
+                current.get_or_insert_with(String::new);
+            }
+            (None, c) if c.is_whitespace() => options.extend(current.take()),
+            (_, c) => current.get_or_insert_with(String::new).push(c),
+        }
+    }
+    options.extend(current);
+    options
+}
+
+fn expand_path(path: &str, home: Option<&str>) -> String {
+    if let Some(url) = path.strip_prefix("file://") {
+        let url = url.strip_prefix("localhost").unwrap_or(url);
+        return percent_decode(url);
+    }
+    match (path.strip_prefix('~'), home) {
+        (Some(rest), Some(home)) if rest.is_empty() || rest.starts_with('/') => format!("{}{}", home.trim_end_matches('/'), rest),
+        _ => path.to_string(),
+    }
+}
+
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        let hex = bytes.get(index + 1..index + 3)
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match (bytes[index], hex) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                index += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                index += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+#[derive(Debug, Parser)]
+#[command(version, about = "Visor de PDF para la terminal", after_help = crate::exit::HELP, args_override_self = true)]
+pub struct Cli {
+    /// Archivo PDF a abrir
+    #[arg(value_hint = ValueHint::FilePath)]
+    pub file: Option<String>,
+
+    /// Segundo PDF con el que comparar (tecla d)
+    #[arg(value_hint = ValueHint::FilePath)]
+    pub compare: Option<String>,
+
+    /// Abre directamente la comparación de los dos PDFs, uno al lado del otro
+    /// (] y [ saltan entre cambios)
+    #[arg(long)]
+    pub diff: bool,
+
+    /// Imprime las páginas paginadas como JSON y termina
+    /// (index desde 0, pdf_page desde 1)
+    #[arg(long)]
+    pub dump_pages: bool,
+
+    /// Imprime título, autor, páginas, caracteres y palabras del PDF
+    /// y si parece contener solo imágenes, y termina
+    #[arg(long)]
+    pub info: bool,
+
+    /// Con --info, imprime la información como JSON
+    #[arg(long, requires = "info")]
+    pub json: bool,
+
+    /// Imprime el documento entero, paginado y limpio, para leerlo con less o bat
+    /// (--page-sep elige cómo se separan las páginas). Es lo que se hace por defecto
+    /// cuando la salida no es una terminal
+    #[arg(long, conflicts_with = "tui")]
+    pub pager: bool,
+
+    /// Separador entre páginas con --pager: rule (línea con la página y la del PDF),
+    /// ff (salto de página, para herramientas que cortan por \f),
+    /// page (--- Página N (PDF M) ---) o none (texto continuo)
+    #[arg(long, value_name = "ESTILO", default_value = "rule", value_parser = ["rule", "ff", "page", "none"])]
+    pub page_sep: String,
+
+    /// Abre el visor aunque la salida no sea una terminal
+    #[arg(long)]
+    pub tui: bool,
+
+    /// Imprime los pasajes resaltados con su página y termina
+    #[arg(long)]
+    pub export_highlights: bool,
+
+    /// Imprime las teclas asignadas a cada acción y termina
+    #[arg(long)]
+    pub dump_keys: bool,
+
+    /// Al salir, deja la última página impresa en la terminal.
+    /// Sin esta opción el visor usa la pantalla alternativa y, al salir,
+    /// la terminal vuelve a mostrar lo que había antes
+    #[arg(long)]
+    pub keep_output: bool,
+
+    /// Motor de extracción de texto
+    #[arg(long, value_name = "MOTOR", default_value = "pdf-extract", value_parser = ["pdf-extract", "pdftotext"])]
+    pub extractor: String,
+
+    /// Codificación del texto extraído; latin1 repara acentos mal decodificados ("Ã©" en lugar de "é")
+    #[arg(long, value_name = "CODIFICACIÓN", default_value = "utf8", value_parser = ["utf8", "latin1"])]
+    pub encoding: String,
+
+    /// Columnas del PDF: 2 lee la columna izquierda entera antes que la derecha,
+    /// auto lo hace solo en las páginas que parecen maquetadas a dos columnas
+    #[arg(long, value_name = "MODO", default_value = "1", value_parser = ["auto", "1", "2"])]
+    pub columns: String,
+
+    /// Muestra N páginas consecutivas una al lado de otra (también :set cols=N)
+    #[arg(long, value_name = "N", default_value_t = 1, value_parser = clap::value_parser!(u8).range(1..=4))]
+    pub cols: u8,
+
+    /// Abre el documento en la página N del PDF (también +N, como en los
+    /// editores; si se dan las dos, manda la última). Manda sobre la
+    /// posición guardada en una sesión (--session)
+    #[arg(long, value_name = "N")]
+    pub goto: Option<usize>,
+
+    /// Abre el documento en la primera coincidencia de TÉRMINO
+    /// (con --goto, la primera a partir de esa página)
+    #[arg(long, value_name = "TÉRMINO")]
+    pub search: Option<String>,
+
+    /// Perfil de teclas: vim-ish (el de siempre) o less
+    /// (por defecto, keymap en config.toml)
+    #[arg(long, value_name = "PERFIL", value_parser = clap::builder::PossibleValuesParser::new(crate::action::KEYMAPS))]
+    pub keymap: Option<String>,
+
+    /// Hace sonar la campana de la terminal en cada cambio de página
+    /// (también :set bell=turn; :set quiet las silencia todas)
+    #[arg(long)]
+    pub bell_on_turn: bool,
+
+    /// Atenúa la pantalla tras SEGUNDOS sin pulsar ninguna tecla;
+    /// cualquier tecla la devuelve a su brillo normal
+    #[arg(long, value_name = "SEGUNDOS")]
+    pub idle_dim_secs: Option<u64>,
+
+    /// Restaura una sesión guardada con :mksession NOMBRE
+    /// (documento, posición, búsqueda y opciones)
+    #[arg(long, value_name = "NOMBRE")]
+    pub session: Option<String>,
+
+    /// Guarda la primera pantalla en RUTA como texto y termina
+    /// (admite --goto y --search para elegir la página)
+    #[arg(long, value_name = "RUTA", value_hint = ValueHint::FilePath)]
+    pub screenshot: Option<String>,
+
+    /// Conserva los colores ANSI en las capturas
+    #[arg(long)]
+    pub screenshot_ansi: bool,
+
+    /// Usa RUTA como config.toml en lugar del de siempre; las opciones
+    /// de la línea de órdenes siguen mandando sobre el archivo y nunca se
+    /// escriben en él. c, +, -, R y # solo cambian su propia clave y
+    /// respetan los comentarios y el resto del archivo
+    #[arg(long, value_name = "RUTA", value_hint = ValueHint::FilePath, conflicts_with = "no_config")]
+    pub config: Option<String>,
+
+    /// Arranca con las opciones por defecto, sin leer ni guardar config.toml
+    #[arg(long)]
+    pub no_config: bool,
+
+    /// No lee las opciones por defecto de la variable PDFVIEW_OPTS
+    /// (por ejemplo PDFVIEW_OPTS="--keymap less --cols 2"; las de la
+    /// línea de órdenes mandan sobre ellas)
+    #[arg(long)]
+    pub ignore_env: bool,
+
+    /// Muestra en stderr cuánto tarda cada paso de la carga
+    #[arg(long)]
+    pub verbose: bool,
+
+    /// Ancho de terminal usado para paginar (por defecto, el de la terminal actual)
+    #[arg(long, value_name = "COLUMNAS")]
+    pub width: Option<u16>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn file_urls_become_paths() {
+        assert_eq!(expand_path("file:///home/ana/doc.pdf", None), "/home/ana/doc.pdf");
+        assert_eq!(expand_path("file://localhost/tmp/doc.pdf", None), "/tmp/doc.pdf");
+    }
+
+    #[test]
+    fn percent_encoded_spaces_are_decoded() {
+        assert_eq!(expand_path("file:///home/ana/Mis%20libros/caf%C3%A9.pdf", None), "/home/ana/Mis libros/café.pdf");
+        assert_eq!(expand_path("informe%20final.pdf", None), "informe%20final.pdf");
+    }
+
+    #[test]
+    fn environment_options_split_like_a_shell() {
+        assert_eq!(split_options("  --keymap less --search 'dos palabras' --config=\"a b.toml\" "), ["--keymap", "less", "--search", "dos palabras", "--config=a b.toml"]);
+        assert_eq!(split_options("--search ''"), ["--search", ""]);
+        assert!(split_options("   ").is_empty());
+    }
+
+    #[test]
+    fn later_options_override_earlier_ones() {
+        let cli = Cli::try_parse_from(["viewerpdf", "--keymap", "less", "--cols", "2", "--keymap", "vim-ish", "doc.pdf"]).unwrap();
+        assert_eq!((cli.keymap.as_deref(), cli.cols, cli.file.as_deref()), (Some("vim-ish"), 2, Some("doc.pdf")));
+    }
+
+    #[test]
+    fn only_positional_plus_numbers_become_goto() {
+        let args = |list: &[&str]| goto_shorthand(list.iter().map(|arg| arg.to_string()).collect());
+        assert_eq!(args(&["viewerpdf", "+12", "doc.pdf"]), ["viewerpdf", "--goto=12", "doc.pdf"]);
+        assert_eq!(args(&["viewerpdf", "--search", "+1", "doc.pdf"]), ["viewerpdf", "--search", "+1", "doc.pdf"]);
+        assert_eq!(args(&["viewerpdf", "--search=+1", "doc.pdf", "+3"]), ["viewerpdf", "--search=+1", "doc.pdf", "--goto=3"]);
+        assert_eq!(args(&["viewerpdf", "doc.pdf", "--", "+3"]), ["viewerpdf", "doc.pdf", "--", "+3"]);
+    }
+
+    #[test]
+    fn command_line_options_replace_conflicting_defaults() {
+        let words = |list: &[&str]| list.iter().map(|arg| arg.to_string()).collect::<Vec<_>>();
+        let defaults = words(&["--pager", "--config", "perfil.toml", "--keymap", "less", "--cols=2"]);
+        assert_eq!(without_overridden(defaults.clone(), &words(&["--tui", "doc.pdf"])), words(&["--config", "perfil.toml", "--keymap", "less", "--cols=2"]));
+        assert_eq!(without_overridden(defaults.clone(), &words(&["--no-config", "--cols", "3"])), words(&["--pager", "--keymap", "less"]));
+        assert_eq!(without_overridden(defaults.clone(), &words(&["--search", "--tui"])), defaults);
+
+        assert!(gives_option(&words(&["--ignore-env", "doc.pdf"]), "ignore_env"));
+        assert!(!gives_option(&words(&["--search", "--ignore-env", "doc.pdf"]), "ignore_env"));
+        assert!(!gives_option(&words(&["doc.pdf", "--", "--ignore-env"]), "ignore_env"));
+    }
+
+    #[test]
+    fn tilde_expands_to_home() {
+        assert_eq!(expand_path("~/doc.pdf", Some("/home/ana")), "/home/ana/doc.pdf");
+        assert_eq!(expand_path("~otro/doc.pdf", Some("/home/ana")), "~otro/doc.pdf");
+        assert_eq!(expand_path("~/doc.pdf", None), "~/doc.pdf");
+    }
+}
@@ -0,0 +1,44 @@
+use std::io::{stdout, Write};
+
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+pub fn copy(text: &str) -> std::io::Result<()> {
+    let mut out = stdout();
+    write!(out, "\x1b]52;c;{}\x07", base64(text.as_bytes()))?;
+    out.flush()
+}
+
+pub fn paste() -> Option<String> {
+    const COMMANDS: &[&[&str]] = &[
+        &["wl-paste", "--no-newline"],
+        &["xclip", "-o", "-selection", "clipboard"],
+        &["xsel", "--clipboard", "--output"],
+        &["pbpaste"],
+        &["powershell.exe", "-NoProfile", "-Command", "Get-Clipboard"],
+    ];
+    COMMANDS.iter().find_map(|command| {
+        let output = std::process::Command::new(command[0])
+            .args(&command[1..])
+            .stderr(std::process::Stdio::null())
+            .output()
+            .ok()?;
+        output.status.success().then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+    })
+}
+
+pub fn base64(bytes: &[u8]) -> String {
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let n = (chunk[0] as u32) << 16
+            | (*chunk.get(1).unwrap_or(&0) as u32) << 8
+            | *chunk.get(2).unwrap_or(&0) as u32;
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(BASE64[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
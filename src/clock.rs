@@ -0,0 +1,31 @@
+use std::time::Duration;
+
+use chrono::{Local, Utc};
+
+pub fn timestamp() -> String {
+    Utc::now().format("%Y%m%d-%H%M%S").to_string()
+}
+
+pub fn local_time() -> String {
+    Local::now().format("%H:%M").to_string()
+}
+
+pub fn duration(elapsed: Duration) -> String {
+    let minutes = elapsed.as_secs() / 60;
+    if minutes < 60 {
+        format!("{}m", minutes)
+    } else {
+        format!("{}h {:02}m", minutes / 60, minutes % 60)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reading_time_is_shown_in_minutes_then_hours() {
+        assert_eq!(duration(Duration::from_secs(18 * 60 + 59)), "18m");
+        assert_eq!(duration(Duration::from_secs(65 * 60)), "1h 05m");
+    }
+}
@@ -0,0 +1,180 @@
+use std::ops::Range;
+use std::sync::OnceLock;
+
+use syntect::easy::ScopeRangeIterator;
+use syntect::parsing::{ParseState, ScopeStack, SyntaxSet};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Language {
+    Rust,
+    Python,
+    CLike,
+    Shell,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Token {
+    Keyword,
+    String,
+    Comment,
+    Number,
+}
+
+pub fn blocks(lines: &[&str]) -> Vec<Option<Language>> {
+    let mut rows = vec![None; lines.len()];
+    let mut start = 0;
+    while start < lines.len() {
+        let captioned = is_caption(lines[start]);
+        let first = if captioned { start + 1 } else { start };
+        let opens = captioned || (indented(lines[start]) && looks_like_code(lines[start]));
+        if !opens {
+            start += 1;
+            continue;
+        }
+        let mut end = first;
+        while end < lines.len() {
+            let line = lines[end];
+            let next = lines[end + 1..].iter().find(|line| !line.trim().is_empty());
+            let belongs = if line.trim().is_empty() {
+                next.is_some_and(|next| indented(next) && looks_like_code(next))
+            } else {
+                indented(line) || (captioned && looks_like_code(line))
+            };
+            if !belongs {
+                break;
+            }
+            end += 1;
+        }
+        let block = &lines[first..end];
+        let code_lines = block.iter().filter(|line| looks_like_code(line)).count();
+        if block.len() >= 3 && code_lines * 2 >= block.len() {
+            let language = guess(block);
+            rows[first..end].fill(Some(language));
+        }
+        start = end.max(start + 1);
+    }
+    rows
+}
+
+fn is_caption(line: &str) -> bool {
+    let line = line.trim();
+    ["Listing ", "Listado "].iter().any(|caption| {
+        let Some(mut rest) = line.strip_prefix(caption) else {
+            return false;
+        };
+        while let Some(number) = rest.strip_prefix(|c: char| c.is_ascii_digit()) {
+            let number = number.trim_start_matches(|c: char| c.is_ascii_digit());
+            match number.strip_prefix('.') {
+                Some(next) if next.starts_with(|c: char| c.is_ascii_digit()) => rest = next,
+                _ => return number.starts_with([':', '.']),
+            }
+        }
+        false
+    })
+}
+
+fn indented(line: &str) -> bool {
+    line.starts_with("    ") || line.starts_with('\t')
+}
+
+fn looks_like_code(line: &str) -> bool {
+    let line = line.trim();
+    !line.is_empty()
+        && crate::list_indent(line).is_none()
+        && (line.ends_with([';', '{', '}', ':', ')', ','])
+            || line.starts_with(['#', '$', '}'])
+            || line.starts_with("//")
+            || line.contains(" = ")
+            || line.contains("->")
+            || line.contains("::"))
+}
+
+fn guess(lines: &[&str]) -> Language {
+    let text = lines.join("\n");
+    let score = |signs: &[&str]| signs.iter().filter(|sign| text.contains(*sign)).count();
+    let scores = [
+        (Language::Rust, score(&["fn ", "let mut ", "->", "::", "impl ", "pub ", "&self", "println!"])),
+        (Language::Python, score(&["def ", "import ", "self.", "elif ", "print(", "):\n", "None"])),
+        (Language::CLike, score(&["#include", "int ", "void ", "};", "public ", "function ", "const ", "return "])),
+        (Language::Shell, score(&["$ ", "echo ", "fi\n", "done", "export ", "sudo ", " | "])),
+    ];
+    scores.iter().max_by_key(|(_, score)| *score).map_or(Language::CLike, |(language, _)| *language)
+}
+
+fn syntaxes() -> &'static SyntaxSet {
+    static SYNTAXES: OnceLock<SyntaxSet> = OnceLock::new();
+    SYNTAXES.get_or_init(SyntaxSet::load_defaults_nonewlines)
+}
+
+pub fn tokens(line: &str, language: Language) -> Vec<(Range<usize>, Token)> {
+    let syntaxes = syntaxes();
+    let extension = match language {
+        Language::Rust => "rs",
+        Language::Python => "py",
+        Language::CLike => "c",
+        Language::Shell => "sh",
+    };
+    let Some(syntax) = syntaxes.find_syntax_by_extension(extension) else {
+        return Vec::new();
+    };
+    // Cada renglón se analiza por separado: el texto del PDF no garantiza que el bloque siga completo en la página.
+    let Ok(ops) = ParseState::new(syntax).parse_line(line, syntaxes) else {
+        return Vec::new();
+    };
+    let mut stack = ScopeStack::new();
+    let mut tokens: Vec<(Range<usize>, Token)> = Vec::new();
+    for (range, op) in ScopeRangeIterator::new(&ops, line) {
+        if stack.apply(op).is_err() {
+            break;
+        }
+        let Some(kind) = stack.as_slice().iter().rev().find_map(|scope| kind(&scope.build_string())) else {
+            continue;
+        };
+        if range.is_empty() {
+            continue;
+        }
+        match tokens.last_mut() {
+            Some((last, last_kind)) if last.end == range.start && *last_kind == kind => last.end = range.end,
+            _ => tokens.push((range, kind)),
+        }
+    }
+    tokens
+}
+
+fn kind(scope: &str) -> Option<Token> {
+    if scope.starts_with("comment") {
+        Some(Token::Comment)
+    } else if scope.starts_with("string") {
+        Some(Token::String)
+    } else if scope.starts_with("constant.numeric") {
+        Some(Token::Number)
+    } else if (scope.starts_with("keyword") && !scope.starts_with("keyword.operator")) || scope.starts_with("storage") || scope.starts_with("constant.language") {
+        Some(Token::Keyword)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_real_captions_open_a_listing() {
+        assert!(is_caption("Listing 3: arranque"));
+        assert!(is_caption("Listado 2.1. Configuración"));
+        assert!(!is_caption("Listing 3 muestra la función principal del programa."));
+        assert!(!is_caption("Listing 3.2 muestra otra cosa"));
+        assert!(!is_caption("Listing tres: arranque"));
+    }
+
+    #[test]
+    fn syntect_marks_keywords_strings_and_comments() {
+        let line = "let nombre = \"pdf\"; // comentario";
+        let tokens = tokens(line, Language::Rust);
+        let text = |kind| tokens.iter().filter(|(_, token)| *token == kind).map(|(range, _)| &line[range.clone()]).collect::<Vec<_>>();
+        assert_eq!(text(Token::Keyword), ["let"]);
+        assert_eq!(text(Token::String), ["\"pdf\""]);
+        assert_eq!(text(Token::Comment), ["// comentario"]);
+    }
+}
@@ -0,0 +1,62 @@
+use clap::{Arg, Command};
+use clap_complete::Shell;
+
+pub const SHELLS: &[&str] = &["bash", "zsh", "fish", "powershell"];
+
+pub fn generate(shell: &str, command: Command) -> Option<String> {
+    let shell = match shell {
+        "bash" => Shell::Bash,
+        "zsh" => Shell::Zsh,
+        "fish" => Shell::Fish,
+        "powershell" => Shell::PowerShell,
+        _ => return None,
+    };
+    let mut command = command.disable_help_subcommand(true).subcommand(
+        Command::new("completions")
+            .about("Imprime el script de autocompletado para la shell")
+            .arg(Arg::new("shell").required(true).value_parser(clap::builder::PossibleValuesParser::new(SHELLS))),
+    );
+    let name = command.get_name().to_string();
+    let mut script = Vec::new();
+    clap_complete::generate(shell, &mut command, name, &mut script);
+    String::from_utf8(script).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::CommandFactory;
+
+    fn script(shell: &str) -> String {
+        generate(shell, crate::cli::Cli::command()).unwrap()
+    }
+
+    #[test]
+    fn every_shell_gets_a_script_from_the_cli_definition() {
+        for shell in SHELLS {
+            let script = script(shell);
+            assert!(script.contains("viewerpdf") && script.contains("keymap") && script.contains("completions"), "{}", shell);
+        }
+        assert!(generate("tcsh", crate::cli::Cli::command()).is_none());
+    }
+
+    #[test]
+    fn bash_completes_flag_values() {
+        let complete = |words: &str| -> Option<String> {
+            let program = format!(
+                "{}\nCOMP_WORDS=({}); COMP_CWORD=$((${{#COMP_WORDS[@]}} - 1)); _viewerpdf viewerpdf \"${{COMP_WORDS[COMP_CWORD]}}\" \"${{COMP_WORDS[COMP_CWORD-1]}}\"; echo \"${{COMPREPLY[*]}}\"",
+                script("bash"),
+                words
+            );
+            let output = std::process::Command::new("bash").arg("-c").arg(program).output().ok()?;
+            assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+            Some(String::from_utf8_lossy(&output.stdout).trim().to_string())
+        };
+        let Some(keymaps) = complete("viewerpdf --keymap ''") else {
+            return;
+        };
+        assert_eq!(keymaps, "vim-ish less");
+        assert_eq!(complete("viewerpdf --encoding l").as_deref(), Some("latin1"));
+        assert_eq!(complete("viewerpdf completions f").as_deref(), Some("fish"));
+    }
+}
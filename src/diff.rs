@@ -0,0 +1,172 @@
+use std::ops::Range;
+
+use similar::{ChangeTag, DiffTag, TextDiff};
+use textwrap::{wrap, Options};
+
+use crate::breaks::{self, Break};
+use crate::Page;
+
+pub const SEPARATOR: &str = " ┃ ";
+
+pub const LAYOUTS: &[&str] = &["side-by-side", "unified"];
+
+pub struct Diff {
+    pub pages: Vec<Page>,
+    pub hunks: Vec<(usize, usize)>,
+    pub added: usize,
+    pub removed: usize,
+}
+
+pub fn diff_pages(old: &str, new: &str, width: u16, height: u16, policy: Break, side_by_side: bool) -> Diff {
+    let content_width = (width as usize).saturating_sub(6).max(4);
+    let content_height = (height as usize).saturating_sub(8).max(1);
+    let mut diff = Diff { pages: Vec::new(), hunks: Vec::new(), added: 0, removed: 0 };
+    if old.trim().is_empty() && new.trim().is_empty() {
+        diff.pages.push(Page::new("  Los documentos no tienen texto que comparar.".to_string(), 0, 0));
+        return diff;
+    }
+
+    let (before, after) = (as_lines(old), as_lines(new));
+    let line_starts: Vec<usize> = std::iter::once(0)
+        .chain(before.match_indices('\n').map(|(index, _)| index + 1))
+        .collect();
+    let old_offset = |line: usize| line_starts.get(line).copied().unwrap_or(old.len()).min(old.len());
+    let page_breaks: Vec<usize> = old.match_indices('\x0C').map(|(index, _)| index).collect();
+
+    let text_diff = TextDiff::from_lines(&before, &after);
+    // (línea, empieza un cambio, offset en el texto viejo)
+    let mut lines: Vec<(String, bool, usize)> = Vec::new();
+    for op in text_diff.ops() {
+        let (tag, old_range, new_range) = op.as_tag_tuple();
+        if tag != DiffTag::Equal {
+            diff.removed += old_range.len();
+            diff.added += new_range.len();
+        }
+        let first = lines.len();
+        if side_by_side {
+            let old_lines = &text_diff.old_slices()[old_range.clone()];
+            let new_lines = &text_diff.new_slices()[new_range];
+            let half = content_width.saturating_sub(SEPARATOR.chars().count()) / 2;
+            for row in 0..old_lines.len().max(new_lines.len()) {
+                let offset = old_offset(old_range.start + row.min(old_lines.len()));
+                let (left, right) = if tag == DiffTag::Equal { ("  ", "  ") } else { ("- ", "+ ") };
+                let left = old_lines.get(row).map_or(Vec::new(), |line| wrap_side(left, line, half, policy));
+                let right = new_lines.get(row).map_or(Vec::new(), |line| wrap_side(right, line, half, policy));
+                for index in 0..left.len().max(right.len()) {
+                    let left = left.get(index).map_or("", String::as_str);
+                    let padding = half.saturating_sub(left.chars().count());
+                    let right = right.get(index).map_or("", String::as_str);
+                    lines.push((format!("{}{}{}{}", left, " ".repeat(padding), SEPARATOR, right).trim_end().to_string(), false, offset));
+                }
+            }
+        } else {
+            for change in text_diff.iter_changes(op) {
+                let prefix = match change.tag() {
+                    ChangeTag::Insert => "+ ",
+                    ChangeTag::Delete => "- ",
+                    ChangeTag::Equal => "  ",
+                };
+                let offset = old_offset(change.old_index().unwrap_or(old_range.start));
+                lines.extend(wrap_side(prefix, change.value(), content_width, policy).into_iter().map(|line| (line, false, offset)));
+            }
+        }
+        if tag != DiffTag::Equal {
+            if let Some((_, starts_hunk, _)) = lines.get_mut(first) {
+                *starts_hunk = true;
+            }
+        }
+    }
+
+    for chunk in lines.chunks(content_height) {
+        let page = diff.pages.len();
+        diff.hunks.extend(chunk.iter().enumerate().filter(|(_, (_, hunk, _))| *hunk).map(|(row, _)| (page, row)));
+        let text = chunk.iter().map(|(line, _, _)| line.as_str()).collect::<Vec<_>>().join("\n");
+        let offset = chunk.first().map_or(0, |(_, _, offset)| *offset);
+        diff.pages.push(Page::new(text, page_breaks.partition_point(|&index| index < offset), offset));
+    }
+    diff
+}
+
+fn as_lines(text: &str) -> String {
+    let mut lines = text.replace('\x0C', "\n");
+    if !lines.ends_with('\n') {
+        lines.push('\n');
+    }
+    lines
+}
+
+fn wrap_side(prefix: &str, line: &str, width: usize, policy: Break) -> Vec<String> {
+    let text = format!("{}{}", prefix, line.trim_end());
+    let options = breaks::apply(Options::new(width.max(4)).subsequent_indent(prefix), policy);
+    wrap(&text, options).into_iter().map(|line| line.into_owned()).collect()
+}
+
+pub fn marks(line: &str) -> Vec<(Range<usize>, ChangeTag)> {
+    let tag = |cell: &str| match cell.get(..2) {
+        Some("+ ") => Some(ChangeTag::Insert),
+        Some("- ") => Some(ChangeTag::Delete),
+        _ => None,
+    };
+    let (left, right) = match line.find(SEPARATOR) {
+        Some(split) => (0..split, Some(split + SEPARATOR.len()..line.len())),
+        None => (0..line.len(), None),
+    };
+    std::iter::once(left)
+        .chain(right)
+        .filter_map(|cell| tag(&line[cell.clone()]).map(|tag| (cell, tag)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn changed_lines_line_up_side_by_side() {
+        let old = "Cláusula 1: el plazo es de 30 días.\nCláusula 2: sin cambios.\nCláusula 3: se elimina.";
+        let new = "Cláusula 1: el plazo es de 60 días.\nCláusula 2: sin cambios.\nCláusula 4: nueva.";
+        let diff = diff_pages(old, new, 106, 30, Break::default(), true);
+        let rows: Vec<&str> = diff.pages[0].text.split('\n').collect();
+        assert_eq!(rows.len(), 3);
+        assert!(rows[0].starts_with("- Cláusula 1: el plazo es de 30 días."));
+        assert!(rows[0].ends_with(" ┃ + Cláusula 1: el plazo es de 60 días."));
+        assert!(rows[1].starts_with("  Cláusula 2") && rows[1].ends_with("┃   Cláusula 2: sin cambios."));
+        assert_eq!(diff.hunks, [(0, 0), (0, 2)]);
+        assert_eq!((diff.added, diff.removed), (2, 2));
+
+        let tags: Vec<ChangeTag> = marks(rows[0]).into_iter().map(|(_, tag)| tag).collect();
+        assert_eq!(tags, [ChangeTag::Delete, ChangeTag::Insert]);
+        assert!(marks(rows[1]).is_empty());
+    }
+
+    #[test]
+    fn an_inserted_page_does_not_misalign_the_rest() {
+        let pages: Vec<String> = (1..=4).map(|n| format!("Página {} sin cambios.", n)).collect();
+        let old = pages.join("\x0C");
+        let new = [&pages[..1], &["Página nueva.".to_string()], &pages[1..]].concat().join("\x0C");
+        let diff = diff_pages(&old, &new, 106, 30, Break::default(), false);
+        assert_eq!((diff.added, diff.removed), (1, 0));
+        assert_eq!(diff.hunks, [(0, 1)]);
+        let rows: Vec<&str> = diff.pages[0].text.split('\n').collect();
+        assert_eq!(rows, ["  Página 1 sin cambios.", "+ Página nueva.", "  Página 2 sin cambios.", "  Página 3 sin cambios.", "  Página 4 sin cambios."]);
+    }
+
+    #[test]
+    fn side_by_side_keeps_identical_pages_on_the_same_row() {
+        let pages: Vec<String> = (1..=6).map(|n| format!("Página {}.", n)).collect();
+        let old = pages.join("\x0C");
+        let new = [&pages[..2], &pages[3..]].concat().join("\x0C");
+        let diff = diff_pages(&old, &new, 60, 11, Break::default(), true);
+        assert_eq!(diff.pages.len(), 2);
+        assert_eq!(diff.hunks, [(0, 2)]);
+        let rows: Vec<&str> = diff.pages.iter().flat_map(|page| page.text.split('\n')).collect();
+        assert!(rows[2].starts_with("- Página 3.") && rows[2].ends_with('┃'));
+        for row in rows.iter().filter(|row| !row.starts_with('-')) {
+            let (left, right) = row.split_once(SEPARATOR).unwrap();
+            assert_eq!(left.trim(), right.trim());
+        }
+        let last = &diff.pages[1];
+        assert_eq!(last.pdf_page, 3);
+        assert!(old[last.offset..].starts_with("Página 4.\x0C"));
+    }
+}
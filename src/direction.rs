@@ -0,0 +1,51 @@
+pub const MAX_BYTES: usize = 8 * 1024 * 1024;
+
+fn is_rtl_char(c: char) -> bool {
+    matches!(c, '\u{0590}'..='\u{08FF}' | '\u{FB1D}'..='\u{FDFF}' | '\u{FE70}'..='\u{FEFF}')
+}
+
+pub fn is_rtl(text: &str) -> bool {
+    let (rtl, ltr) = text.chars()
+        .filter(|c| c.is_alphabetic())
+        .fold((0, 0), |(rtl, ltr), c| if is_rtl_char(c) { (rtl + 1, ltr) } else { (rtl, ltr + 1) });
+    rtl > ltr
+}
+
+pub fn visual(line: &str) -> String {
+    let chars: Vec<char> = line.chars().collect();
+    let strong: Vec<Option<bool>> = chars.iter()
+        .map(|c| c.is_alphanumeric().then(|| !is_rtl_char(*c)))
+        .collect();
+    let mut runs: Vec<(bool, Vec<char>)> = Vec::new();
+    for (index, c) in chars.iter().enumerate() {
+        let ltr = strong[index].unwrap_or_else(|| {
+            let before = strong[..index].iter().rev().find_map(|s| *s);
+            let after = strong[index + 1..].iter().find_map(|s| *s);
+            before == Some(true) && after == Some(true)
+        });
+        match runs.last_mut() {
+            Some((run_ltr, run)) if *run_ltr == ltr => run.push(*c),
+            _ => runs.push((ltr, vec![*c])),
+        }
+    }
+    runs.iter()
+        .rev()
+        .flat_map(|(ltr, run)| -> Vec<char> { if *ltr { run.clone() } else { run.iter().rev().map(|c| mirror(*c)).collect() } })
+        .collect()
+}
+
+fn mirror(c: char) -> char {
+    match c {
+        '(' => ')',
+        ')' => '(',
+        '[' => ']',
+        ']' => '[',
+        '{' => '}',
+        '}' => '{',
+        '<' => '>',
+        '>' => '<',
+        '«' => '»',
+        '»' => '«',
+        other => other,
+    }
+}
@@ -0,0 +1,49 @@
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExitCode {
+    Usage = 2,
+    NotFound = 3,
+    Extraction = 4,
+    Encrypted = 5,
+    NoText = 6,
+    Write = 7,
+}
+
+pub const HELP: &str = "\
+Códigos de salida:
+  0  Todo bien
+  2  Uso incorrecto (argumentos u opciones no válidos)
+  3  No se encontró el archivo
+  4  No se pudo extraer el texto
+  5  El PDF está cifrado y necesita contraseña
+  6  El PDF no tiene texto (solo con --dump-pages y --export-highlights)
+  7  No se pudo escribir la captura (--screenshot)";
+
+impl ExitCode {
+    pub fn exit(self) -> ! {
+        std::process::exit(self as i32)
+    }
+}
+
+#[derive(Debug)]
+pub struct Failure {
+    pub code: ExitCode,
+    pub message: String,
+}
+
+impl Failure {
+    pub fn new(code: ExitCode, message: impl Into<String>) -> Failure {
+        Failure { code, message: message.into() }
+    }
+
+    pub fn code_of(error: &(dyn std::error::Error + 'static)) -> ExitCode {
+        error.downcast_ref::<Failure>().map_or(ExitCode::Extraction, |failure| failure.code)
+    }
+}
+
+impl std::fmt::Display for Failure {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for Failure {}
@@ -0,0 +1,133 @@
+use std::fs::{File, OpenOptions};
+use std::hash::{BuildHasher, RandomState};
+use std::io::{ErrorKind, Write};
+use std::path::PathBuf;
+use std::process::{Command, ExitStatus, Stdio};
+
+pub struct Output {
+    pub status: ExitStatus,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+pub fn run(template: &str, text: &str, capture: bool) -> Result<Output, String> {
+    let temp = if template.contains("{}") { Some(temp_file("texto", text)?) } else { None };
+    let command_line = match &temp {
+        Some(path) => template.replace("{}", &quote(&path.to_string_lossy())),
+        None => template.to_string(),
+    };
+
+    let result = execute(&command_line, temp.is_none().then_some(text), capture);
+    if let Some(path) = &temp {
+        let _ = std::fs::remove_file(path);
+    }
+    result
+}
+
+pub fn pipe(command_line: &str, text: &str) -> Result<Output, String> {
+    execute(command_line, Some(text), true)
+}
+
+fn execute(command_line: &str, input: Option<&str>, capture: bool) -> Result<Output, String> {
+    let mut command = shell(command_line);
+    command.stdin(if input.is_some() { Stdio::piped() } else { Stdio::inherit() });
+    if capture {
+        command.stdout(Stdio::piped()).stderr(Stdio::piped());
+    }
+    let output = command.spawn()
+        .map_err(|e| format!("No se pudo ejecutar \"{}\": {}", command_line, e))
+        .and_then(|mut child| {
+            if let (Some(mut stdin), Some(input)) = (child.stdin.take(), input) {
+                let _ = stdin.write_all(input.as_bytes());
+            }
+            child.wait_with_output().map_err(|e| format!("\"{}\" falló: {}", command_line, e))
+        })?;
+    Ok(Output {
+        status: output.status,
+        stdout: String::from_utf8_lossy(&output.stdout).into_owned(),
+        stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+    })
+}
+
+pub fn edit(text: &str, line: usize, keep: bool) -> Result<Option<PathBuf>, String> {
+    let editor = ["VISUAL", "EDITOR"].iter()
+        .filter_map(|name| std::env::var(name).ok())
+        .find(|editor| !editor.trim().is_empty())
+        .ok_or_else(|| "Define $EDITOR o $VISUAL para abrir el texto en un editor".to_string())?;
+    let path = temp_file("documento", text)?;
+    let command_line = format!("{} +{} {}", editor, line, quote(&path.to_string_lossy()));
+    let result = shell(&command_line).status().map_err(|e| format!("No se pudo ejecutar \"{}\": {}", command_line, e));
+    if !keep {
+        let _ = std::fs::remove_file(&path);
+    }
+    match result? {
+        status if status.success() => Ok(keep.then_some(path)),
+        status => Err(format!("{} terminó con código {}", editor, status.code().map_or("señal".to_string(), |code| code.to_string()))),
+    }
+}
+
+fn temp_file(label: &str, text: &str) -> Result<PathBuf, String> {
+    let mut last_error = None;
+    for _ in 0..8 {
+        let suffix = RandomState::new().hash_one(std::time::SystemTime::now());
+        let path = std::env::temp_dir().join(format!("pdfview-{}-{}-{:016x}.txt", std::process::id(), label, suffix));
+        match create_private(&path).and_then(|mut file| file.write_all(text.as_bytes())) {
+            Ok(()) => return Ok(path),
+            Err(e) if e.kind() == ErrorKind::AlreadyExists => last_error = Some(e),
+            Err(e) => {
+                let _ = std::fs::remove_file(&path);
+                return Err(format!("No se pudo escribir {}: {}", path.display(), e));
+            }
+        }
+    }
+    Err(format!("No se pudo crear un archivo temporal: {}", last_error.map_or_else(String::new, |e| e.to_string())))
+}
+
+fn create_private(path: &std::path::Path) -> std::io::Result<File> {
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    options.open(path)
+}
+
+fn shell(command_line: &str) -> Command {
+    if cfg!(windows) {
+        let mut command = Command::new("cmd");
+        command.args(["/C", command_line]);
+        command
+    } else {
+        let mut command = Command::new("sh");
+        command.args(["-c", command_line]);
+        command
+    }
+}
+
+fn quote(path: &str) -> String {
+    if cfg!(windows) {
+        format!("\"{}\"", path)
+    } else {
+        format!("'{}'", path.replace('\'', "'\\''"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn temp_files_are_new_and_private() {
+        let first = temp_file("prueba", "hola").unwrap();
+        let second = temp_file("prueba", "adiós").unwrap();
+        assert_ne!(first, second);
+        assert_eq!(std::fs::read_to_string(&first).unwrap(), "hola");
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            assert_eq!(std::fs::metadata(&first).unwrap().permissions().mode() & 0o777, 0o600);
+        }
+        assert_eq!(create_private(&first).unwrap_err().kind(), ErrorKind::AlreadyExists);
+        std::fs::remove_file(first).unwrap();
+        std::fs::remove_file(second).unwrap();
+    }
+}
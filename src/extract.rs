@@ -0,0 +1,403 @@
+use std::io::ErrorKind;
+use std::process::Command;
+
+use lopdf::Document;
+use pdf_extract::{MediaBox, OutputDev, OutputError, Transform};
+
+use crate::images;
+use crate::links::Link;
+
+const COLUMN_GAP: &str = "   ";
+
+struct PageTextOutput<'a> {
+    doc: &'a Document,
+    text: String,
+    images: Vec<(f64, String)>,
+    links: &'a mut [Link],
+    page: usize,
+    page_height: f64,
+    page_left: f64,
+    page_width: f64,
+    last_end: f64,
+    last_y: f64,
+    first_char: bool,
+    mark_columns: bool,
+}
+
+impl OutputDev for PageTextOutput<'_> {
+    fn begin_page(&mut self, page_num: u32, media_box: &MediaBox, _: Option<(f64, f64, f64, f64)>) -> Result<(), OutputError> {
+        self.page = page_num as usize - 1;
+        self.page_height = media_box.ury - media_box.lly;
+        self.page_left = media_box.llx;
+        self.page_width = media_box.urx - media_box.llx;
+        self.last_end = 100000.;
+        self.last_y = 0.;
+        self.images = images::image_placements(self.doc, self.page)
+            .into_iter()
+            .map(|(top, image)| (self.page_height - top, self.image_block(&image)))
+            .collect();
+        self.images.sort_by(|a, b| b.0.total_cmp(&a.0));
+        Ok(())
+    }
+
+    fn end_page(&mut self) -> Result<(), OutputError> {
+        self.flush_images(f64::MAX);
+        self.text.push('\x0C');
+        Ok(())
+    }
+
+    fn output_character(&mut self, trm: &Transform, width: f64, _spacing: f64, font_size: f64, char: &str) -> Result<(), OutputError> {
+        let (x, y) = (trm.m31, self.page_height - trm.m32);
+        let scaled = ((trm.m11 + trm.m21) * font_size * (trm.m12 + trm.m22) * font_size).abs().sqrt();
+        if self.flush_images(y) {
+            self.last_end = 100000.;
+        }
+
+        let mut separator = String::new();
+        if self.first_char {
+            if (y - self.last_y).abs() > scaled * 1.5 {
+                separator.push('\n');
+            }
+            if x < self.last_end && (y - self.last_y).abs() > scaled * 0.5 {
+                separator.push('\n');
+            }
+            let right_half = separator.ends_with('\n') && x > self.page_left + self.page_width * 0.4;
+            let gutter = x > self.last_end + scaled * 2.0 && (y - self.last_y).abs() <= scaled * 0.5;
+            if self.mark_columns && (right_half || gutter) {
+                separator.push_str(COLUMN_GAP);
+            } else if x > self.last_end + scaled * 0.1 {
+                separator.push(' ');
+            }
+        }
+        self.text.push_str(&separator);
+        self.text.push_str(char);
+
+        let page = self.page;
+        for link in self.links.iter_mut().filter(|l| l.pdf_page == page && l.contains(x, trm.m32)) {
+            if !separator.is_empty() && !link.anchor.is_empty() {
+                link.anchor.push(' ');
+            }
+            link.anchor.push_str(char);
+        }
+
+        self.first_char = false;
+        self.last_y = y;
+        self.last_end = x + width * scaled;
+        Ok(())
+    }
+
+    fn begin_word(&mut self) -> Result<(), OutputError> {
+        self.first_char = true;
+        Ok(())
+    }
+
+    fn end_word(&mut self) -> Result<(), OutputError> {
+        Ok(())
+    }
+
+    fn end_line(&mut self) -> Result<(), OutputError> {
+        Ok(())
+    }
+}
+
+impl PageTextOutput<'_> {
+    fn flush_images(&mut self, y: f64) -> bool {
+        let mut flushed = false;
+        while self.images.last().is_some_and(|(top, _)| *top <= y) {
+            let (_, block) = self.images.pop().unwrap_or_default();
+            if !self.text.is_empty() && !self.text.ends_with(['\n', '\x0C']) {
+                self.text.push('\n');
+            }
+            self.text.push_str(&block);
+            self.text.push('\n');
+            flushed = true;
+        }
+        flushed
+    }
+
+    #[cfg(not(feature = "inline-art"))]
+    fn image_block(&self, image: &images::ImageRef) -> String {
+        images::placeholder(image)
+    }
+
+    #[cfg(feature = "inline-art")]
+    fn image_block(&self, image: &images::ImageRef) -> String {
+        let mut block = images::placeholder(image);
+        if let Ok(thumbnail) = images::load_image(self.doc, image, 32, 24) {
+            for line in images::render_shades(&thumbnail) {
+                block.push('\n');
+                block.push_str(&line);
+            }
+        }
+        block
+    }
+}
+
+pub trait Extractor {
+    fn name(&self) -> &'static str;
+    fn extract(&self, pdf_path: &str, doc: &Document, links: &mut [Link]) -> Result<String, String>;
+}
+
+pub struct PdfExtract {
+    pub mark_columns: bool,
+}
+
+impl Extractor for PdfExtract {
+    fn name(&self) -> &'static str {
+        "pdf-extract"
+    }
+
+    fn extract(&self, _pdf_path: &str, doc: &Document, links: &mut [Link]) -> Result<String, String> {
+        extract_text(doc, links, self.mark_columns).map_err(|e| e.to_string())
+    }
+}
+
+pub struct PdfToText;
+
+impl Extractor for PdfToText {
+    fn name(&self) -> &'static str {
+        "pdftotext"
+    }
+
+    fn extract(&self, pdf_path: &str, _doc: &Document, _links: &mut [Link]) -> Result<String, String> {
+        let output = Command::new("pdftotext")
+            .args(["-enc", "UTF-8", pdf_path, "-"])
+            .output()
+            .map_err(|e| match e.kind() {
+                ErrorKind::NotFound => "pdftotext no está instalado (paquete poppler-utils)".to_string(),
+                _ => format!("No se pudo ejecutar pdftotext: {}", e),
+            })?;
+        if !output.status.success() {
+            return Err(format!("pdftotext falló: {}", String::from_utf8_lossy(&output.stderr).trim()));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+}
+
+pub fn extractor(name: &str, columns: &str) -> Box<dyn Extractor> {
+    match name {
+        "pdftotext" => Box::new(PdfToText),
+        _ => Box::new(PdfExtract { mark_columns: columns != "1" }),
+    }
+}
+
+pub fn extract_with_fallback(name: &str, columns: &str, pdf_path: &str, doc: &Document, links: &mut [Link]) -> Result<(String, &'static str), String> {
+    let chosen = extractor(name, columns);
+    let result = chosen.extract(pdf_path, doc, links);
+    if chosen.name() != PdfToText.name() && result.as_ref().map_or(true, |text| text.trim().is_empty()) {
+        if let Ok(text) = PdfToText.extract(pdf_path, doc, links) {
+            if !text.trim().is_empty() {
+                return Ok((text, PdfToText.name()));
+            }
+        }
+    }
+    result.map(|text| (text, chosen.name()))
+}
+
+pub fn normalize(text: &str, encoding: &str) -> String {
+    let text = text.strip_prefix('\u{feff}').unwrap_or(text);
+    let text = match encoding {
+        "latin1" => reinterpret_latin1(text),
+        _ => text.to_string(),
+    };
+    text.replace(char::REPLACEMENT_CHARACTER, "␣")
+}
+
+pub fn linearize_columns(text: String, mode: &str) -> String {
+    if mode == "1" {
+        return text;
+    }
+    text.split('\x0C')
+        .map(|section| linearize_section(section, mode == "2"))
+        .collect::<Vec<_>>()
+        .join("\x0C")
+}
+
+enum ColumnLine<'a> {
+    Both(&'a str, &'a str),
+    Right(&'a str),
+    Blank,
+    Full(&'a str),
+}
+
+fn column_line(line: &str) -> ColumnLine<'_> {
+    let content = line.trim_end();
+    let indent = content.len() - content.trim_start().len();
+    if content.is_empty() {
+        return ColumnLine::Blank;
+    }
+    if indent >= COLUMN_GAP.len() && !content[indent..].contains(COLUMN_GAP) {
+        return ColumnLine::Right(&content[indent..]);
+    }
+    let Some(gap) = content[indent..].find(COLUMN_GAP).map(|gap| indent + gap) else {
+        return ColumnLine::Full(line);
+    };
+    let right = content[gap..].trim_start();
+    if right.contains(COLUMN_GAP) {
+        return ColumnLine::Full(line);
+    }
+    ColumnLine::Both(&content[..gap], right)
+}
+
+fn linearize_section(section: &str, force: bool) -> String {
+    let lines: Vec<ColumnLine> = section.split('\n').map(column_line).collect();
+    let split = lines.iter().filter(|line| matches!(line, ColumnLine::Both(..))).count();
+    let filled = lines.iter().filter(|line| !matches!(line, ColumnLine::Blank)).count();
+    if split == 0 || !force && (split < 6 || split * 10 < filled * 4) {
+        return section.to_string();
+    }
+
+    let widest = lines.iter()
+        .filter_map(|line| match line {
+            ColumnLine::Both(left, _) => Some(left.chars().count()),
+            _ => None,
+        })
+        .max()
+        .unwrap_or(0);
+    let continues = |index: usize| {
+        lines[index + 1..].iter()
+            .find(|line| !matches!(line, ColumnLine::Blank))
+            .is_some_and(|line| matches!(line, ColumnLine::Both(..) | ColumnLine::Right(_)))
+    };
+
+    let mut out: Vec<&str> = Vec::with_capacity(lines.len());
+    let mut left: Vec<&str> = Vec::new();
+    let mut right: Vec<&str> = Vec::new();
+    let mut blanks = 0;
+    for (index, line) in lines.iter().enumerate() {
+        match line {
+            ColumnLine::Both(l, r) => {
+                left.extend(std::iter::repeat_n("", blanks));
+                right.extend(std::iter::repeat_n("", blanks));
+                blanks = 0;
+                left.push(l);
+                right.push(r);
+            }
+            ColumnLine::Right(r) if !left.is_empty() => {
+                right.extend(std::iter::repeat_n("", blanks));
+                blanks = 0;
+                left.push("");
+                right.push(r);
+            }
+            ColumnLine::Full(l) if !left.is_empty() && l.trim_end().chars().count() <= widest + 8 && (blanks == 0 || continues(index)) => {
+                left.extend(std::iter::repeat_n("", blanks));
+                blanks = 0;
+                left.push(l.trim_end());
+                right.push("");
+            }
+            ColumnLine::Blank if !left.is_empty() => blanks += 1,
+            ColumnLine::Right(text) | ColumnLine::Full(text) => {
+                flush_columns(&mut out, &mut left, &mut right);
+                out.extend(std::iter::repeat_n("", blanks));
+                blanks = 0;
+                out.push(text);
+            }
+            ColumnLine::Blank => out.push(""),
+        }
+    }
+    flush_columns(&mut out, &mut left, &mut right);
+    out.extend(std::iter::repeat_n("", blanks));
+    out.join("\n")
+}
+
+fn flush_columns<'a>(out: &mut Vec<&'a str>, left: &mut Vec<&'a str>, right: &mut Vec<&'a str>) {
+    for column in [left, right] {
+        while column.last().is_some_and(|line| line.is_empty()) {
+            column.pop();
+        }
+        out.append(column);
+    }
+}
+
+fn reinterpret_latin1(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut run: Vec<u8> = Vec::new();
+    for c in text.chars() {
+        if ('\u{80}'..='\u{ff}').contains(&c) {
+            run.push(c as u8);
+            continue;
+        }
+        flush_latin1(&mut out, &mut run);
+        out.push(c);
+    }
+    flush_latin1(&mut out, &mut run);
+    out
+}
+
+fn flush_latin1(out: &mut String, run: &mut Vec<u8>) {
+    match String::from_utf8(std::mem::take(run)) {
+        Ok(decoded) => out.push_str(&decoded),
+        Err(e) => out.extend(e.into_bytes().into_iter().map(char::from)),
+    }
+}
+
+fn extract_text(doc: &Document, links: &mut [Link], mark_columns: bool) -> Result<String, OutputError> {
+    let mut output = PageTextOutput {
+        doc,
+        text: String::new(),
+        images: Vec::new(),
+        links,
+        page: 0,
+        page_height: 0.,
+        page_left: 0.,
+        page_width: 0.,
+        last_end: 100000.,
+        last_y: 0.,
+        first_char: false,
+        mark_columns,
+    };
+    pdf_extract::output_doc(doc, &mut output)?;
+    Ok(output.text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TWO_COLUMNS: &str = include_str!("../tests/fixtures/two_columns.txt");
+
+    #[test]
+    fn latin1_repairs_mojibake_accents() {
+        let garbled: String = "Canción del niño, año 1997".bytes().map(char::from).collect();
+        assert_eq!(normalize(&garbled, "latin1"), "Canción del niño, año 1997");
+    }
+
+    #[test]
+    fn latin1_keeps_text_that_is_already_correct() {
+        assert_eq!(normalize("Canción y café", "latin1"), "Canción y café");
+        assert_eq!(normalize("Canción y café", "utf8"), "Canción y café");
+    }
+
+    #[test]
+    fn two_column_page_reads_left_column_first() {
+        let text = linearize_columns(TWO_COLUMNS.to_string(), "auto");
+        let page = text.split('\x0C').next().unwrap_or("");
+        let lines: Vec<&str> = page.lines().collect();
+        assert_eq!(lines[0], "Reading Two-Column Papers in the Terminal");
+        assert_eq!(lines[3], "1 Introduction");
+        let left_end = lines.iter().position(|line| *line == "mode that keeps the horizontal").unwrap();
+        assert_eq!(lines[left_end + 1], "position of every glyph, which is");
+        assert_eq!(lines.last(), Some(&"Figure 1: A page with two columns."));
+        assert!(page.contains("in turn.\n\n2 Related work"));
+        assert!(page.contains("side by side on screen.\n\n3 Method"));
+        assert!(page.contains("are read first and the right parts\nafterwards, so each column reads"));
+    }
+
+    #[test]
+    fn linearizing_keeps_every_word() {
+        let text = linearize_columns(TWO_COLUMNS.to_string(), "2");
+        let mut before: Vec<&str> = TWO_COLUMNS.split_whitespace().collect();
+        let mut after: Vec<&str> = text.split_whitespace().collect();
+        before.sort_unstable();
+        after.sort_unstable();
+        assert_eq!(before, after);
+        assert_eq!(text.matches('\x0C').count(), TWO_COLUMNS.matches('\x0C').count());
+    }
+
+    #[test]
+    fn single_column_pages_are_left_alone() {
+        assert_eq!(linearize_columns(TWO_COLUMNS.to_string(), "1"), TWO_COLUMNS);
+        let prose = "Una línea normal.\nOtra con   un hueco aislado.\nY una tercera.\n";
+        assert_eq!(linearize_columns(prose.to_string(), "auto"), prose);
+    }
+}
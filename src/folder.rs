@@ -0,0 +1,86 @@
+use std::cmp::Ordering;
+use std::path::{Path, PathBuf};
+
+pub fn neighbour(current: &Path, forward: bool, wrap: bool) -> Option<PathBuf> {
+    let mut pdfs: Vec<PathBuf> = std::fs::read_dir(current.parent()?).ok()?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file() && path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("pdf")))
+        .collect();
+    pdfs.sort_by(|a, b| natural_cmp(&a.to_string_lossy(), &b.to_string_lossy()));
+    let index = pdfs.iter().position(|path| path == current)?;
+    let next = if forward {
+        match index + 1 {
+            next if next < pdfs.len() => next,
+            _ if wrap => 0,
+            _ => return None,
+        }
+    } else {
+        match index.checked_sub(1) {
+            Some(previous) => previous,
+            None if wrap => pdfs.len() - 1,
+            None => return None,
+        }
+    };
+    (next != index).then(|| pdfs.swap_remove(next))
+}
+
+fn natural_cmp(a: &str, b: &str) -> Ordering {
+    let (mut a, mut b) = (a.chars().peekable(), b.chars().peekable());
+    loop {
+        match (a.peek().copied(), b.peek().copied()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(x), Some(y)) if x.is_ascii_digit() && y.is_ascii_digit() => {
+                let number = |chars: &mut std::iter::Peekable<std::str::Chars>| {
+                    let mut digits = String::new();
+                    while let Some(c) = chars.next_if(char::is_ascii_digit) {
+                        digits.push(c);
+                    }
+                    digits.trim_start_matches('0').to_string()
+                };
+                let (x, y) = (number(&mut a), number(&mut b));
+                match x.len().cmp(&y.len()).then_with(|| x.cmp(&y)) {
+                    Ordering::Equal => {}
+                    order => return order,
+                }
+            }
+            (Some(x), Some(y)) => {
+                match x.to_lowercase().cmp(y.to_lowercase()) {
+                    Ordering::Equal => {}
+                    order => return order,
+                }
+                a.next();
+                b.next();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testdir::TestDir;
+
+    #[test]
+    fn chapters_sort_by_number_not_by_text() {
+        let mut names = vec!["cap10.pdf", "Cap2.pdf", "cap1.pdf", "apéndice.pdf", "cap02b.pdf"];
+        names.sort_by(|a, b| natural_cmp(a, b));
+        assert_eq!(names, ["apéndice.pdf", "cap1.pdf", "Cap2.pdf", "cap02b.pdf", "cap10.pdf"]);
+    }
+
+    #[test]
+    fn neighbours_stop_or_wrap_at_the_ends() {
+        let dir = TestDir::new("carpeta");
+        for name in ["parte10.pdf", "parte2.PDF", "parte1.pdf", "notas.txt"] {
+            std::fs::write(dir.path(name), "").unwrap();
+        }
+        let (first, second, last) = (dir.path("parte1.pdf"), dir.path("parte2.PDF"), dir.path("parte10.pdf"));
+        assert_eq!(neighbour(&first, true, false), Some(second.clone()));
+        assert_eq!(neighbour(&last, false, false), Some(second));
+        assert_eq!(neighbour(&last, true, false), None);
+        assert_eq!(neighbour(&last, true, true), Some(first.clone()));
+        assert_eq!(neighbour(&first, false, true), Some(last));
+    }
+}
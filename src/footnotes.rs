@@ -0,0 +1,134 @@
+const SUPERSCRIPTS: [char; 10] = ['⁰', '¹', '²', '³', '⁴', '⁵', '⁶', '⁷', '⁸', '⁹'];
+
+const SEPARATOR: &str = "──────────";
+
+struct Footnote {
+    number: usize,
+    lines: std::ops::Range<usize>,
+    removed: std::ops::Range<usize>,
+}
+
+pub fn move_to_bottom(section: &str) -> Option<String> {
+    let lines: Vec<&str> = section.split('\n').collect();
+    let mut footnotes: Vec<Footnote> = Vec::new();
+    let mut index = 0;
+    while index < lines.len() {
+        let Some((number, _)) = leading_number(lines[index]) else {
+            index += 1;
+            continue;
+        };
+        let end = (index + 1..lines.len())
+            .find(|&next| lines[next].trim().is_empty() || leading_number(lines[next]).is_some())
+            .unwrap_or(lines.len());
+        let inline = index > 0 && !lines[index - 1].trim().is_empty();
+        let removed = if inline && end < lines.len() && lines[end].trim().is_empty() { index..end + 1 } else { index..end };
+        footnotes.push(Footnote { number, lines: index..end, removed: removed.clone() });
+        index = removed.end;
+    }
+    footnotes.retain(|footnote| footnote.lines.start > 0);
+    if footnotes.is_empty() || footnotes.windows(2).any(|pair| pair[1].number != pair[0].number + 1) {
+        return None;
+    }
+
+    let is_footnote = |line: usize| footnotes.iter().any(|footnote| footnote.removed.contains(&line));
+    let mut body: String = lines.iter()
+        .enumerate()
+        .filter(|(line, _)| !is_footnote(*line))
+        .map(|(_, line)| *line)
+        .collect::<Vec<_>>()
+        .join("\n");
+    for footnote in &footnotes {
+        let marker = find_marker(&body, footnote.number)?;
+        body.replace_range(marker, &format!("[{}]", footnote.number));
+    }
+
+    let mut out = body.trim_end().to_string();
+    out.push_str("\n\n");
+    out.push_str(SEPARATOR);
+    for footnote in &footnotes {
+        let (_, text_start) = leading_number(lines[footnote.lines.start])?;
+        out.push_str(&format!("\n[{}] {}", footnote.number, &lines[footnote.lines.start][text_start..]));
+        for line in &lines[footnote.lines.start + 1..footnote.lines.end] {
+            out.push('\n');
+            out.push_str(line);
+        }
+    }
+    Some(out)
+}
+
+fn digit(c: char) -> Option<u32> {
+    c.to_digit(10).or_else(|| SUPERSCRIPTS.iter().position(|s| *s == c).map(|d| d as u32))
+}
+
+fn leading_number(line: &str) -> Option<(usize, usize)> {
+    let trimmed = line.trim_start();
+    let indent = line.len() - trimmed.len();
+    let digits: String = trimmed.chars().take_while(|c| digit(*c).is_some()).collect();
+    let digit_count = digits.chars().count();
+    if digit_count == 0 || digit_count > 2 {
+        return None;
+    }
+    let rest = &trimmed[digits.len()..];
+    let text = rest.trim_start();
+    let superscript = digits.chars().all(|c| !c.is_ascii_digit());
+    if !(superscript || rest.starts_with(' ')) || !text.starts_with(char::is_uppercase) {
+        return None;
+    }
+    let number = digits.chars().filter_map(digit).fold(0, |n, d| n * 10 + d as usize);
+    (number > 0).then_some((number, indent + digits.len() + (rest.len() - text.len())))
+}
+
+fn find_marker(body: &str, number: usize) -> Option<std::ops::Range<usize>> {
+    let mut chars = body.char_indices().peekable();
+    let mut previous = ' ';
+    while let Some((start, c)) = chars.next() {
+        if digit(c).is_none() || !(previous.is_alphabetic() || ".,;:)\"'’”".contains(previous)) {
+            previous = c;
+            continue;
+        }
+        let mut end = start + c.len_utf8();
+        let mut value = digit(c).unwrap_or(0) as usize;
+        while let Some(&(position, next)) = chars.peek() {
+            let Some(d) = digit(next) else { break };
+            value = value * 10 + d as usize;
+            end = position + next.len_utf8();
+            chars.next();
+        }
+        let following = body[end..].chars().next();
+        if value == number && following.is_none_or(|next| next.is_whitespace() || ".,;:)".contains(next)) {
+            return Some(start..end);
+        }
+        previous = body[..end].chars().next_back().unwrap_or(' ');
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn footnotes_move_below_the_body() {
+        let section = "El resultado fue claro.1 Otros autores\n\
+                       1 Véase el apéndice para los datos.\n\
+                       \n\
+                       llegaron a lo mismo² en su estudio.\n\
+                       ² Smith, 2001.";
+        let moved = move_to_bottom(section).unwrap();
+        assert_eq!(
+            moved,
+            "El resultado fue claro.[1] Otros autores\n\
+             llegaron a lo mismo[2] en su estudio.\n\
+             \n\
+             ──────────\n\
+             [1] Véase el apéndice para los datos.\n\
+             [2] Smith, 2001."
+        );
+    }
+
+    #[test]
+    fn unmatched_numbers_are_left_alone() {
+        assert!(move_to_bottom("Capítulo uno\n3 Métodos usados en el estudio").is_none());
+        assert!(move_to_bottom("Texto sin notas.\nOtra línea.").is_none());
+    }
+}
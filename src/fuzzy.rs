@@ -0,0 +1,27 @@
+pub struct Match {
+    pub score: i64,
+    pub positions: Vec<usize>,
+}
+
+pub fn fuzzy_match(query: &str, candidate: &str) -> Option<Match> {
+    let query: Vec<char> = query.chars().filter(|c| !c.is_whitespace()).flat_map(char::to_lowercase).collect();
+    let chars: Vec<char> = candidate.chars().collect();
+    let mut positions: Vec<usize> = Vec::with_capacity(query.len());
+    let mut score: i64 = 0;
+    let mut next = 0;
+    for wanted in &query {
+        let found = (next..chars.len()).find(|&i| chars[i].to_lowercase().eq(std::iter::once(*wanted)))?;
+        score += 10;
+        if found == 0 || !chars[found - 1].is_alphanumeric() {
+            score += 8;
+        }
+        match positions.last() {
+            Some(&last) if last + 1 == found => score += 12,
+            Some(&last) => score -= (found - last - 1).min(10) as i64,
+            None => score -= found.min(10) as i64,
+        }
+        positions.push(found);
+        next = found + 1;
+    }
+    Some(Match { score: score - chars.len() as i64 / 8, positions })
+}
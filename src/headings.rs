@@ -0,0 +1,152 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::links::OutlineEntry;
+
+const SMALL_WORDS: &[&str] = &[
+    "a", "an", "and", "at", "by", "for", "in", "of", "on", "or", "the", "to", "with",
+    "con", "de", "del", "el", "en", "la", "las", "los", "para", "por", "un", "una", "y",
+];
+
+struct Line<'a> {
+    text: &'a str,
+    offset: usize,
+    pdf_page: usize,
+}
+
+pub fn detect(text: &str) -> Vec<OutlineEntry> {
+    let mut lines = Vec::new();
+    let mut offset = 0;
+    for (pdf_page, section) in text.split('\x0C').enumerate() {
+        for line in section.split('\n') {
+            let indent = line.len() - line.trim_start().len();
+            lines.push(Line { text: line.trim(), offset: offset + indent, pdf_page });
+            offset += line.len() + 1;
+        }
+    }
+
+    let candidates: Vec<Option<usize>> = lines.iter()
+        .enumerate()
+        .map(|(index, line)| {
+            let opens = index == 0 || lines[index - 1].text.is_empty() || lines[index - 1].pdf_page != line.pdf_page;
+            level(line.text).filter(|_| opens)
+        })
+        .collect();
+    let mut accepted = vec![false; lines.len()];
+    for index in (0..lines.len()).rev() {
+        if candidates[index].is_none() {
+            continue;
+        }
+        let next = (index + 1..lines.len()).find(|&next| !lines[next].text.is_empty());
+        accepted[index] = next.is_some_and(|next| is_prose(lines[next].text) || accepted[next]);
+    }
+
+    let mut pages_per_title: HashMap<&str, HashSet<usize>> = HashMap::new();
+    for (line, _) in lines.iter().zip(&accepted).filter(|(_, accepted)| **accepted) {
+        pages_per_title.entry(line.text).or_default().insert(line.pdf_page);
+    }
+    lines.iter()
+        .zip(candidates)
+        .zip(accepted)
+        .filter(|((line, _), accepted)| *accepted && pages_per_title.get(line.text).is_none_or(|pages| pages.len() <= 2))
+        .filter_map(|((line, level), _)| Some(OutlineEntry {
+            level: level?,
+            title: line.text.to_string(),
+            pdf_page: line.pdf_page,
+            detected_at: Some(line.offset),
+        }))
+        .collect()
+}
+
+fn level(line: &str) -> Option<usize> {
+    let words: Vec<&str> = line.split_whitespace().collect();
+    if words.is_empty() || words.len() > 10 || line.chars().count() > 80 || line.ends_with(['.', ',', ';', ':', '!', '?']) {
+        return None;
+    }
+    if let Some(depth) = numbering(words[0]) {
+        let title = &words[1..];
+        return (!title.is_empty() && title.len() <= 8 && title[0].starts_with(char::is_uppercase)).then_some(depth);
+    }
+    let letters: Vec<char> = line.chars().filter(|c| c.is_alphabetic()).collect();
+    if letters.len() < 3 || words.len() > 8 {
+        return None;
+    }
+    if letters.iter().all(|c| c.is_uppercase()) {
+        return Some(0);
+    }
+    let title_case = words[0].starts_with(char::is_uppercase)
+        && words.iter().all(|word| {
+            word.starts_with(char::is_uppercase) || !word.starts_with(char::is_alphabetic) || SMALL_WORDS.contains(&word.to_lowercase().as_str())
+        });
+    (title_case && (words.len() > 1 || letters.len() >= 4)).then_some(1)
+}
+
+fn numbering(word: &str) -> Option<usize> {
+    let parts: Vec<&str> = word.trim_end_matches('.').split('.').collect();
+    let numeric = parts.iter().all(|part| !part.is_empty() && part.len() <= 2 && part.bytes().all(|b| b.is_ascii_digit()));
+    numeric.then(|| parts.len() - 1)
+}
+
+fn is_prose(line: &str) -> bool {
+    line.chars().count() >= 45
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn titles(text: &str) -> Vec<(usize, String)> {
+        detect(text).into_iter().map(|entry| (entry.level, entry.title)).collect()
+    }
+
+    #[test]
+    fn numbered_sections_nest_by_depth() {
+        let text = "A Study of Extraction\n\n\
+                    1 Introduction\n\
+                    Text extracted from PDF files rarely keeps the structure of the original.\n\n\
+                    2 Methods\n\n\
+                    2.1 Data Sources\n\
+                    We collected two hundred documents from public archives and journals.\n\
+                    3. Results\n\n\
+                    The detector found most headings, as the tables in section 4 show in detail.";
+        assert_eq!(
+            titles(text),
+            [(1, "A Study of Extraction".to_string()), (0, "1 Introduction".to_string()), (0, "2 Methods".to_string()), (1, "2.1 Data Sources".to_string())]
+        );
+        let entries = detect(text);
+        assert_eq!(&text[entries[1].detected_at.unwrap()..][..14], "1 Introduction");
+    }
+
+    #[test]
+    fn capitals_and_title_case_across_pages() {
+        let text = "CAPÍTULO PRIMERO\n\n\
+                    Era una mañana fría cuando el tren llegó por fin a la estación del pueblo.\n\x0C\
+                    El Regreso a Casa\n\
+                    Nadie esperaba en el andén, y la maleta pesaba más de lo que recordaba.";
+        let entries = detect(text);
+        assert_eq!(entries.iter().map(|entry| (entry.level, entry.pdf_page)).collect::<Vec<_>>(), [(0, 0), (1, 1)]);
+        assert_eq!(entries[1].title, "El Regreso a Casa");
+    }
+
+    #[test]
+    fn short_lines_are_not_mistaken_for_headings() {
+        let poem = "Caminante, No Hay Camino\n\n\
+                    Caminante, son tus huellas\n\
+                    El Camino y Nada Más\n\
+                    Caminante, No Hay Camino\n\
+                    Se Hace Camino al Andar\n\n\
+                    Al Andar Se Hace Camino\n\
+                    Y al Volver la Vista Atrás\n\n\
+                    Lista de la Compra\n\n\
+                    Leche\n\n\
+                    Pan Integral\n\n\
+                    2 Kilos de Naranjas";
+        assert!(titles(poem).is_empty());
+    }
+
+    #[test]
+    fn running_headers_are_ignored() {
+        let page = "JOURNAL OF DOCUMENT ENGINEERING\n\nEach page of this journal repeats its name at the very top of the page.";
+        let text = [page, page, page].join("\x0C");
+        assert!(titles(&text).is_empty());
+    }
+}
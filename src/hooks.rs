@@ -0,0 +1,37 @@
+/// Eventos del visor para quien lo integra en otra aplicación.
+///
+/// Todos los métodos tienen una implementación vacía, así que basta con
+/// escribir los que interesen:
+///
+/// ```no_run
+/// use viewerpdf::{Hooks, PdfViewer};
+///
+/// struct Sidebar(std::sync::mpsc::Sender<usize>);
+///
+/// impl Hooks for Sidebar {
+///     fn on_page_change(&mut self, page: usize, _pdf_page: usize, _total: usize) {
+///         let _ = self.0.send(page);
+///     }
+/// }
+///
+/// let (sender, _pages) = std::sync::mpsc::channel();
+/// let mut viewer = PdfViewer::new("libro.pdf", "pdf-extract", "utf8", "1")?;
+/// viewer.set_hooks(Box::new(Sidebar(sender)));
+/// viewer.run()?;
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub trait Hooks {
+    /// El documento está paginado y el visor va a empezar a dibujarlo.
+    fn on_load_complete(&mut self, _pages: usize, _chars: usize) {}
+
+    /// La página del visor cambió (índices desde 0).
+    fn on_page_change(&mut self, _page: usize, _pdf_page: usize, _total: usize) {}
+
+    /// Resultado de buscar con / o de repetir la búsqueda con n: la coincidencia
+    /// actual (desde 0) si la hay y el total en todo el documento.
+    fn on_search(&mut self, _query: &str, _current: Option<usize>, _total: usize) {}
+}
+
+pub struct NoHooks;
+
+impl Hooks for NoHooks {}
@@ -0,0 +1,49 @@
+use std::sync::OnceLock;
+
+use hyphenation::{Load, Standard};
+use textwrap::WordSplitter;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Language {
+    English,
+    Spanish,
+}
+
+pub const NAMES: &[&str] = &["off", "en", "es"];
+
+static ENGLISH: OnceLock<Option<Standard>> = OnceLock::new();
+static SPANISH: OnceLock<Option<Standard>> = OnceLock::new();
+
+pub fn language(name: &str) -> Option<Language> {
+    match name {
+        "en" => Some(Language::English),
+        "es" => Some(Language::Spanish),
+        _ => None,
+    }
+}
+
+pub fn splitter(language: Language) -> WordSplitter {
+    let (dictionary, patterns) = match language {
+        Language::English => (&ENGLISH, hyphenation::Language::EnglishUS),
+        Language::Spanish => (&SPANISH, hyphenation::Language::Spanish),
+    };
+    match dictionary.get_or_init(|| Standard::from_embedded(patterns).ok()) {
+        Some(dictionary) => WordSplitter::Hyphenation(dictionary.clone()),
+        None => WordSplitter::HyphenSplitter,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn short_words_with_suffixes_split_without_panicking() {
+        let english = splitter(Language::English);
+        for word in ["table", "cable", "bible", "bless", "table.", "Tables,"] {
+            assert!(english.split_points(word).iter().all(|point| *point > 0 && *point < word.len()), "{}", word);
+        }
+        assert!(!english.split_points("internationalization").is_empty());
+        assert!(!splitter(Language::Spanish).split_points("extraordinariamente").is_empty());
+    }
+}
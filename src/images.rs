@@ -0,0 +1,621 @@
+use std::io::Read;
+
+use crossterm::style::{Color, Stylize};
+use flate2::read::ZlibDecoder;
+use lopdf::content::Content;
+use lopdf::{Dictionary, Document, Object, ObjectId, Stream};
+
+const MAX_SOURCE_PIXELS: u64 = 400_000_000;
+
+pub struct PageImage {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<[u8; 3]>,
+}
+
+pub struct ImageRef {
+    pub id: ObjectId,
+    pub width: u32,
+    pub height: u32,
+}
+
+#[derive(Clone)]
+enum ColorSpace {
+    Gray,
+    Rgb,
+    Cmyk,
+    Indexed(Box<ColorSpace>, Vec<u8>),
+}
+
+impl ColorSpace {
+    fn components(&self) -> usize {
+        match self {
+            ColorSpace::Gray | ColorSpace::Indexed(..) => 1,
+            ColorSpace::Rgb => 3,
+            ColorSpace::Cmyk => 4,
+        }
+    }
+
+    fn to_rgb(&self, samples: &[u8]) -> [u8; 3] {
+        match self {
+            ColorSpace::Gray => [samples[0]; 3],
+            ColorSpace::Rgb => [samples[0], samples[1], samples[2]],
+            ColorSpace::Cmyk => cmyk_to_rgb(samples[0], samples[1], samples[2], samples[3]),
+            ColorSpace::Indexed(base, lookup) => {
+                let n = base.components();
+                let start = samples[0] as usize * n;
+                match lookup.get(start..start + n) {
+                    Some(entry) => base.to_rgb(entry),
+                    None => [0, 0, 0],
+                }
+            }
+        }
+    }
+}
+
+fn cmyk_to_rgb(c: u8, m: u8, y: u8, k: u8) -> [u8; 3] {
+    let channel = |v: u8| ((255 - v as u16) * (255 - k as u16) / 255) as u8;
+    [channel(c), channel(m), channel(y)]
+}
+
+pub fn page_images(doc: &Document, pdf_page: usize) -> Vec<ImageRef> {
+    let Some(page_id) = doc.get_pages().get(&(pdf_page as u32 + 1)).copied() else {
+        return Vec::new();
+    };
+    let (resources, inherited) = doc.get_page_resources(page_id);
+    let mut images = Vec::new();
+    let mut dictionaries: Vec<&Dictionary> = resources.into_iter().collect();
+    dictionaries.extend(inherited.into_iter().filter_map(|id| doc.get_dictionary(id).ok()));
+    for resources in dictionaries {
+        collect_images(doc, resources, &mut images, 0);
+    }
+    images
+}
+
+fn collect_images(doc: &Document, resources: &Dictionary, images: &mut Vec<ImageRef>, depth: usize) {
+    if depth > 4 {
+        return;
+    }
+    let Some(xobjects) = resources.get(b"XObject").ok()
+        .and_then(|x| doc.dereference(x).ok())
+        .and_then(|(_, x)| x.as_dict().ok())
+    else {
+        return;
+    };
+    for (_, xobject) in xobjects.iter() {
+        let Ok(id) = xobject.as_reference() else { continue };
+        let Ok(Object::Stream(stream)) = doc.get_object(id) else { continue };
+        match stream.dict.get(b"Subtype").and_then(Object::as_name).ok() {
+            Some(b"Image") => {
+                if images.iter().any(|image| image.id == id) || is_mask(&stream.dict) {
+                    continue;
+                }
+                let width = integer(doc, &stream.dict, b"Width").unwrap_or(0) as u32;
+                let height = integer(doc, &stream.dict, b"Height").unwrap_or(0) as u32;
+                if width > 0 && height > 0 {
+                    images.push(ImageRef { id, width, height });
+                }
+            }
+            Some(b"Form") => {
+                if let Some(nested) = stream.dict.get(b"Resources").ok()
+                    .and_then(|r| doc.dereference(r).ok())
+                    .and_then(|(_, r)| r.as_dict().ok())
+                {
+                    collect_images(doc, nested, images, depth + 1);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+pub fn image_placements(doc: &Document, pdf_page: usize) -> Vec<(f64, ImageRef)> {
+    let Some(page_id) = doc.get_pages().get(&(pdf_page as u32 + 1)).copied() else {
+        return Vec::new();
+    };
+    let Ok(content) = doc.get_page_content(page_id) else {
+        return Vec::new();
+    };
+    let (resources, inherited) = doc.get_page_resources(page_id);
+    let mut dictionaries: Vec<&Dictionary> = resources.into_iter().collect();
+    dictionaries.extend(inherited.into_iter().filter_map(|id| doc.get_dictionary(id).ok()));
+    let mut placements = Vec::new();
+    walk_content(doc, &content, &dictionaries, [1., 0., 0., 1., 0., 0.], &mut placements, 0);
+    placements
+}
+
+fn walk_content(doc: &Document, content: &[u8], resources: &[&Dictionary], ctm: [f64; 6], placements: &mut Vec<(f64, ImageRef)>, depth: usize) {
+    if depth > 4 {
+        return;
+    }
+    let Ok(content) = Content::decode(content) else {
+        return;
+    };
+    let mut stack = Vec::new();
+    let mut ctm = ctm;
+    for operation in content.operations {
+        match operation.operator.as_str() {
+            "q" => stack.push(ctm),
+            "Q" => ctm = stack.pop().unwrap_or(ctm),
+            "cm" => {
+                let values: Vec<f64> = operation.operands.iter().filter_map(number).collect();
+                if let [a, b, c, d, e, f] = values[..] {
+                    ctm = multiply([a, b, c, d, e, f], ctm);
+                }
+            }
+            "Do" => {
+                let Some(name) = operation.operands.first().and_then(|o| o.as_name().ok()) else { continue };
+                let Some(id) = resources.iter().find_map(|r| xobject_id(doc, r, name)) else { continue };
+                let Ok(Object::Stream(stream)) = doc.get_object(id) else { continue };
+                match stream.dict.get(b"Subtype").and_then(Object::as_name).ok() {
+                    Some(b"Image") if !is_mask(&stream.dict) => {
+                        let width = integer(doc, &stream.dict, b"Width").unwrap_or(0) as u32;
+                        let height = integer(doc, &stream.dict, b"Height").unwrap_or(0) as u32;
+                        let top = [ctm[5], ctm[3] + ctm[5], ctm[1] + ctm[5], ctm[1] + ctm[3] + ctm[5]]
+                            .into_iter()
+                            .fold(f64::MIN, f64::max);
+                        if width > 0 && height > 0 {
+                            placements.push((top, ImageRef { id, width, height }));
+                        }
+                    }
+                    Some(b"Form") => {
+                        let matrix: Vec<f64> = stream.dict.get(b"Matrix").and_then(Object::as_array)
+                            .map(|items| items.iter().filter_map(number).collect())
+                            .unwrap_or_default();
+                        let form_ctm = match matrix[..] {
+                            [a, b, c, d, e, f] => multiply([a, b, c, d, e, f], ctm),
+                            _ => ctm,
+                        };
+                        let nested = stream.dict.get(b"Resources").ok()
+                            .and_then(|r| doc.dereference(r).ok())
+                            .and_then(|(_, r)| r.as_dict().ok());
+                        let form_resources: Vec<&Dictionary> = nested.into_iter().chain(resources.iter().copied()).collect();
+                        let form_content = stream.decompressed_content().unwrap_or_else(|_| stream.content.clone());
+                        walk_content(doc, &form_content, &form_resources, form_ctm, placements, depth + 1);
+                    }
+                    _ => {}
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn xobject_id(doc: &Document, resources: &Dictionary, name: &[u8]) -> Option<ObjectId> {
+    let (_, xobjects) = doc.dereference(resources.get(b"XObject").ok()?).ok()?;
+    xobjects.as_dict().ok()?.get(name).ok()?.as_reference().ok()
+}
+
+fn number(object: &Object) -> Option<f64> {
+    match object {
+        Object::Integer(value) => Some(*value as f64),
+        Object::Real(value) => Some(*value as f64),
+        _ => None,
+    }
+}
+
+fn multiply(m: [f64; 6], n: [f64; 6]) -> [f64; 6] {
+    [
+        m[0] * n[0] + m[1] * n[2],
+        m[0] * n[1] + m[1] * n[3],
+        m[2] * n[0] + m[3] * n[2],
+        m[2] * n[1] + m[3] * n[3],
+        m[4] * n[0] + m[5] * n[2] + n[4],
+        m[4] * n[1] + m[5] * n[3] + n[5],
+    ]
+}
+
+pub fn placeholder(image: &ImageRef) -> String {
+    format!("[imagen: {}×{}]", image.width, image.height)
+}
+
+#[cfg(feature = "inline-art")]
+pub fn render_shades(image: &PageImage) -> Vec<String> {
+    const SHADES: [char; 5] = ['█', '▓', '▒', '░', ' '];
+    let luminance = |[r, g, b]: [u8; 3]| (r as u32 * 299 + g as u32 * 587 + b as u32 * 114) / 1000;
+    (0..image.height.div_ceil(2))
+        .map(|row| {
+            (0..image.width)
+                .map(|x| {
+                    let top = image.pixels[row * 2 * image.width + x];
+                    let bottom = image.pixels.get((row * 2 + 1) * image.width + x).copied().unwrap_or(top);
+                    SHADES[((luminance(top) + luminance(bottom)) / 2 * 5 / 256) as usize]
+                })
+                .collect::<String>()
+                .trim_end()
+                .to_string()
+        })
+        .collect()
+}
+
+fn is_mask(dict: &Dictionary) -> bool {
+    dict.get(b"ImageMask").and_then(Object::as_bool).unwrap_or(false)
+}
+
+fn integer(doc: &Document, dict: &Dictionary, key: &[u8]) -> Option<i64> {
+    doc.dereference(dict.get(key).ok()?).ok()?.1.as_i64().ok()
+}
+
+fn color_space(doc: &Document, object: &Object, depth: usize) -> Option<ColorSpace> {
+    if depth > 4 {
+        return None;
+    }
+    match doc.dereference(object).ok()?.1 {
+        Object::Name(name) => match name.as_slice() {
+            b"DeviceGray" | b"CalGray" | b"G" => Some(ColorSpace::Gray),
+            b"DeviceRGB" | b"CalRGB" | b"RGB" => Some(ColorSpace::Rgb),
+            b"DeviceCMYK" | b"CMYK" => Some(ColorSpace::Cmyk),
+            _ => None,
+        },
+        Object::Array(items) => match items.first()?.as_name().ok()? {
+            b"ICCBased" => {
+                let (_, stream) = doc.dereference(items.get(1)?).ok()?;
+                match stream.as_stream().ok()?.dict.get(b"N").and_then(Object::as_i64).ok()? {
+                    1 => Some(ColorSpace::Gray),
+                    4 => Some(ColorSpace::Cmyk),
+                    _ => Some(ColorSpace::Rgb),
+                }
+            }
+            b"Indexed" | b"I" => {
+                let base = color_space(doc, items.get(1)?, depth + 1)?;
+                let lookup = match doc.dereference(items.get(3)?).ok()?.1 {
+                    Object::String(bytes, _) => bytes.clone(),
+                    Object::Stream(stream) => stream.decompressed_content().unwrap_or_else(|_| stream.content.clone()),
+                    _ => return None,
+                };
+                Some(ColorSpace::Indexed(Box::new(base), lookup))
+            }
+            b"CalGray" => Some(ColorSpace::Gray),
+            b"CalRGB" | b"Lab" => Some(ColorSpace::Rgb),
+            _ => color_space(doc, items.get(1)?, depth + 1),
+        },
+        _ => None,
+    }
+}
+
+fn filters(stream: &Stream) -> Vec<Vec<u8>> {
+    match stream.dict.get(b"Filter") {
+        Ok(Object::Name(name)) => vec![name.clone()],
+        Ok(Object::Array(items)) => items.iter().filter_map(|f| f.as_name().ok().map(<[u8]>::to_vec)).collect(),
+        _ => Vec::new(),
+    }
+}
+
+pub fn load_image(doc: &Document, image: &ImageRef, max_width: usize, max_height: usize) -> Result<PageImage, String> {
+    if !within_limit(image.width as u64, image.height as u64) {
+        return Err(format!("imagen demasiado grande ({}×{})", image.width, image.height));
+    }
+    let (width, height) = fit(image.width, image.height, max_width, max_height);
+    let stream = doc.get_object(image.id)
+        .and_then(Object::as_stream)
+        .map_err(|e| e.to_string())?;
+    let mut pixels = decode_stream(doc, stream, width, height)?;
+
+    let mask = stream.dict.get(b"SMask").ok()
+        .and_then(|m| doc.dereference(m).ok())
+        .and_then(|(_, m)| m.as_stream().ok());
+    if let Some(mask) = mask {
+        if let Ok(alpha) = decode_stream(doc, mask, width, height) {
+            for (pixel, [a, _, _]) in pixels.iter_mut().zip(alpha) {
+                for channel in pixel.iter_mut() {
+                    *channel = ((*channel as u16 * a as u16 + 255 * (255 - a as u16)) / 255) as u8;
+                }
+            }
+        }
+    }
+
+    Ok(PageImage { width, height, pixels })
+}
+
+fn within_limit(width: u64, height: u64) -> bool {
+    width.checked_mul(height).is_some_and(|pixels| pixels <= MAX_SOURCE_PIXELS)
+}
+
+fn decode_stream(doc: &Document, stream: &Stream, width: usize, height: usize) -> Result<Vec<[u8; 3]>, String> {
+    let dimension = |key: &[u8]| integer(doc, &stream.dict, key).and_then(|value| u64::try_from(value).ok()).unwrap_or(0);
+    let (source_width, source_height) = (dimension(b"Width"), dimension(b"Height"));
+    if source_width == 0 || source_height == 0 || !within_limit(source_width, source_height) {
+        return Err("dimensiones de imagen no válidas".to_string());
+    }
+    let (source_width, source_height) = (source_width as usize, source_height as usize);
+    let filters = filters(stream);
+    let mut thumbnail = Thumbnail::new(width, height);
+
+    match filters.last().map(Vec::as_slice) {
+        Some(b"DCTDecode") | Some(b"DCT") => decode_jpeg(stream, &mut thumbnail)?,
+        Some(b"FlateDecode") | Some(b"Fl") | None => {
+            let space = stream.dict.get(b"ColorSpace").ok()
+                .and_then(|cs| color_space(doc, cs, 0))
+                .unwrap_or(ColorSpace::Gray);
+            let bits = integer(doc, &stream.dict, b"BitsPerComponent").unwrap_or(8);
+            let bits = match bits {
+                1 | 2 | 4 | 8 => bits as usize,
+                16 if !matches!(space, ColorSpace::Indexed(..)) => 16,
+                _ => return Err(format!("profundidad de color no soportada ({} bits)", bits)),
+            };
+            let invert = stream.dict.get(b"Decode").and_then(Object::as_array).ok()
+                .and_then(|d| d.first()?.as_float().ok())
+                .is_some_and(|first| first > 0.5);
+            let parms = stream.dict.get(b"DecodeParms").ok()
+                .and_then(|p| doc.dereference(p).ok())
+                .and_then(|(_, p)| match p {
+                    Object::Array(items) => items.last()?.as_dict().ok(),
+                    other => other.as_dict().ok(),
+                });
+            let predictor = parms.and_then(|p| p.get(b"Predictor").and_then(Object::as_i64).ok()).unwrap_or(1);
+            let decoder = RasterDecoder { width: source_width, height: source_height, space, bits, invert, predictor };
+            if filters.is_empty() {
+                decoder.decode(stream.content.as_slice(), &mut thumbnail)?;
+            } else {
+                decoder.decode(ZlibDecoder::new(stream.content.as_slice()), &mut thumbnail)?;
+            }
+        }
+        Some(other) => {
+            return Err(format!("formato no soportado ({})", String::from_utf8_lossy(other)));
+        }
+    }
+    Ok(thumbnail.finish())
+}
+
+fn fit(width: u32, height: u32, max_width: usize, max_height: usize) -> (usize, usize) {
+    let scale = (max_width as f64 / width as f64).min(max_height as f64 / height as f64).min(1.0);
+    (((width as f64 * scale).round() as usize).max(1), ((height as f64 * scale).round() as usize).max(1))
+}
+
+struct Thumbnail {
+    width: usize,
+    height: usize,
+    sums: Vec<[u32; 4]>,
+}
+
+impl Thumbnail {
+    fn new(width: usize, height: usize) -> Self {
+        Thumbnail {
+            width,
+            height,
+            sums: vec![[0; 4]; width * height],
+        }
+    }
+
+    fn add_row(&mut self, y: usize, row: &[[u8; 3]], row_width: usize, rows: usize) {
+        let ty = (y * self.height / rows.max(1)).min(self.height - 1);
+        for (x, pixel) in row.iter().enumerate() {
+            let tx = (x * self.width / row_width.max(1)).min(self.width - 1);
+            let cell = &mut self.sums[ty * self.width + tx];
+            cell[0] += pixel[0] as u32;
+            cell[1] += pixel[1] as u32;
+            cell[2] += pixel[2] as u32;
+            cell[3] += 1;
+        }
+    }
+
+    fn finish(self) -> Vec<[u8; 3]> {
+        self.sums.iter()
+            .map(|[r, g, b, n]| {
+                let n = (*n).max(1);
+                [(r / n) as u8, (g / n) as u8, (b / n) as u8]
+            })
+            .collect()
+    }
+}
+
+struct RasterDecoder {
+    width: usize,
+    height: usize,
+    space: ColorSpace,
+    bits: usize,
+    invert: bool,
+    predictor: i64,
+}
+
+impl RasterDecoder {
+    fn decode(&self, mut reader: impl Read, thumbnail: &mut Thumbnail) -> Result<(), String> {
+        let components = self.space.components();
+        let row_bytes = (self.width * components * self.bits).div_ceil(8);
+        let png = self.predictor >= 10;
+        let bytes_per_pixel = (components * self.bits).div_ceil(8).max(1);
+        let mut previous = vec![0u8; row_bytes];
+        let mut raw = vec![0u8; row_bytes + png as usize];
+        let mut row = Vec::with_capacity(self.width);
+        let mut samples = vec![0u8; components];
+        let max_value = (1u32 << self.bits) - 1;
+
+        for y in 0..self.height {
+            if reader.read_exact(&mut raw).is_err() {
+                break;
+            }
+            let data = if png {
+                unfilter(raw[0], &mut raw[1..], &previous, bytes_per_pixel);
+                previous.copy_from_slice(&raw[1..]);
+                &raw[1..]
+            } else {
+                &raw[..]
+            };
+
+            row.clear();
+            for x in 0..self.width {
+                for (c, sample) in samples.iter_mut().enumerate() {
+                    let value = read_sample(data, (x * components + c) * self.bits, self.bits);
+                    let value = if self.invert { max_value - value.min(max_value) } else { value };
+                    *sample = if matches!(self.space, ColorSpace::Indexed(..)) {
+                        value as u8
+                    } else {
+                        (value * 255 / max_value) as u8
+                    };
+                }
+                row.push(self.space.to_rgb(&samples));
+            }
+            thumbnail.add_row(y, &row, self.width, self.height);
+        }
+        Ok(())
+    }
+}
+
+fn read_sample(data: &[u8], bit_offset: usize, bits: usize) -> u32 {
+    let byte = |index: usize| *data.get(index).unwrap_or(&0) as u32;
+    match bits {
+        8 => byte(bit_offset / 8),
+        16 => byte(bit_offset / 8) << 8 | byte(bit_offset / 8 + 1),
+        _ => (byte(bit_offset / 8) >> (8 - bits - bit_offset % 8)) & ((1 << bits) - 1),
+    }
+}
+
+fn unfilter(filter: u8, row: &mut [u8], previous: &[u8], bpp: usize) {
+    for i in 0..row.len() {
+        let left = if i >= bpp { row[i - bpp] } else { 0 };
+        let up = previous[i];
+        let up_left = if i >= bpp { previous[i - bpp] } else { 0 };
+        row[i] = row[i].wrapping_add(match filter {
+            1 => left,
+            2 => up,
+            3 => ((left as u16 + up as u16) / 2) as u8,
+            4 => paeth(left, up, up_left),
+            _ => 0,
+        });
+    }
+}
+
+fn paeth(a: u8, b: u8, c: u8) -> u8 {
+    let p = a as i16 + b as i16 - c as i16;
+    let (pa, pb, pc) = ((p - a as i16).abs(), (p - b as i16).abs(), (p - c as i16).abs());
+    if pa <= pb && pa <= pc {
+        a
+    } else if pb <= pc {
+        b
+    } else {
+        c
+    }
+}
+
+fn decode_jpeg(stream: &Stream, thumbnail: &mut Thumbnail) -> Result<(), String> {
+    let mut decoder = jpeg_decoder::Decoder::new(stream.content.as_slice());
+    decoder.scale(thumbnail.width as u16, thumbnail.height as u16).map_err(|e| e.to_string())?;
+    let pixels = decoder.decode().map_err(|e| e.to_string())?;
+    let info = decoder.info().ok_or("JPEG sin información")?;
+    let (width, height) = (info.width as usize, info.height as usize);
+    let components = match info.pixel_format {
+        jpeg_decoder::PixelFormat::L8 => 1,
+        jpeg_decoder::PixelFormat::L16 => 2,
+        jpeg_decoder::PixelFormat::RGB24 => 3,
+        jpeg_decoder::PixelFormat::CMYK32 => 4,
+    };
+
+    let mut row = Vec::with_capacity(width);
+    for (y, line) in pixels.chunks(width * components).take(height).enumerate() {
+        row.clear();
+        row.extend(line.chunks(components).map(|p| match components {
+            1 | 2 => [p[0]; 3],
+            3 => [p[0], p[1], p[2]],
+            _ => cmyk_to_rgb(255 - p[0], 255 - p[1], 255 - p[2], 255 - p[3]),
+        }));
+        thumbnail.add_row(y, &row, width, height);
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageBackend {
+    Blocks,
+    Sixel,
+    Kitty,
+}
+
+impl ImageBackend {
+    pub fn detect(preference: &str) -> ImageBackend {
+        match preference {
+            "sixel" => ImageBackend::Sixel,
+            "kitty" => ImageBackend::Kitty,
+            "blocks" => ImageBackend::Blocks,
+            _ if kitty_supported() => ImageBackend::Kitty,
+            _ if sixel_supported() => ImageBackend::Sixel,
+            _ => ImageBackend::Blocks,
+        }
+    }
+}
+
+fn kitty_supported() -> bool {
+    let term = std::env::var("TERM").unwrap_or_default();
+    let program = std::env::var("TERM_PROGRAM").unwrap_or_default();
+    std::env::var_os("KITTY_WINDOW_ID").is_some()
+        || ["kitty", "ghostty"].iter().any(|name| term.contains(name))
+        || ["WezTerm", "ghostty"].contains(&program.as_str())
+}
+
+fn sixel_supported() -> bool {
+    let term = std::env::var("TERM").unwrap_or_default();
+    let program = std::env::var("TERM_PROGRAM").unwrap_or_default();
+    ["foot", "mlterm", "yaft", "contour"].iter().any(|name| term.contains(name))
+        || ["mintty", "iTerm.app"].contains(&program.as_str())
+}
+
+pub fn truecolor_supported() -> bool {
+    std::env::var("COLORTERM")
+        .map(|value| value.contains("truecolor") || value.contains("24bit"))
+        .unwrap_or(false)
+}
+
+fn terminal_color([r, g, b]: [u8; 3], truecolor: bool) -> Color {
+    if truecolor {
+        return Color::Rgb { r, g, b };
+    }
+    let level = |v: u8| if v < 48 { 0 } else if v < 115 { 1 } else { (v - 35) / 40 };
+    let cube = 16 + 36 * level(r) + 6 * level(g) + level(b);
+    let gray_level = ((r as u16 + g as u16 + b as u16) / 3) as u8;
+    let gray = if gray_level > 238 { 231 } else { 232 + gray_level.saturating_sub(3) / 10 };
+    let is_gray = r.abs_diff(g) < 10 && g.abs_diff(b) < 10;
+    Color::AnsiValue(if is_gray { gray } else { cube })
+}
+
+pub fn render_half_blocks(image: &PageImage, truecolor: bool) -> Vec<String> {
+    (0..image.height.div_ceil(2))
+        .map(|row| {
+            let mut line = String::new();
+            for x in 0..image.width {
+                let top = image.pixels[row * 2 * image.width + x];
+                let bottom = image.pixels.get((row * 2 + 1) * image.width + x).copied().unwrap_or(top);
+                line.push_str(&"▀"
+                    .with(terminal_color(top, truecolor))
+                    .on(terminal_color(bottom, truecolor))
+                    .to_string());
+            }
+            line
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn samples_keep_their_full_depth() {
+        assert_eq!(read_sample(&[0b1011_0100], 2, 2), 0b11);
+        assert_eq!(read_sample(&[0b1011_0100], 4, 4), 0b0100);
+        assert_eq!(read_sample(&[0x12, 0x34, 0xab, 0xcd], 16, 16), 0xabcd);
+
+        let decoder = RasterDecoder { width: 2, height: 1, space: ColorSpace::Gray, bits: 16, invert: false, predictor: 1 };
+        let mut thumbnail = Thumbnail::new(2, 1);
+        decoder.decode([0xff, 0xff, 0x80, 0x00].as_slice(), &mut thumbnail).unwrap();
+        assert_eq!(thumbnail.finish(), [[255, 255, 255], [127, 127, 127]]);
+    }
+
+    #[test]
+    fn unusual_bit_depths_are_skipped() {
+        let doc = Document::with_version("1.5");
+        for bits in [3, 5, 12, 32] {
+            let dict = lopdf::dictionary! { "Width" => 2, "Height" => 1, "BitsPerComponent" => bits };
+            let stream = Stream::new(dict, vec![0xff; 8]);
+            assert!(decode_stream(&doc, &stream, 2, 1).unwrap_err().contains("no soportada"));
+        }
+    }
+
+    #[test]
+    fn hostile_dimensions_are_rejected_without_overflow() {
+        let doc = Document::with_version("1.5");
+        for (width, height) in [(i64::MAX, 3_i64), (1 << 32, 1 << 33), (-1, 5), (20_001, 20_000)] {
+            let dict = lopdf::dictionary! { "Width" => width, "Height" => height };
+            let stream = Stream::new(dict, Vec::new());
+            assert_eq!(decode_stream(&doc, &stream, 2, 1).unwrap_err(), "dimensiones de imagen no válidas");
+        }
+    }
+}
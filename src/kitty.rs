@@ -0,0 +1,65 @@
+use std::io::Write;
+
+use flate2::write::ZlibEncoder;
+use flate2::{Compression, Crc};
+
+use crate::clipboard::base64;
+use crate::images::PageImage;
+
+pub const IMAGE_ID: u32 = 1;
+const CHUNK: usize = 4096;
+
+pub fn transmit(image: &PageImage) -> String {
+    let payload = base64(&encode_png(image));
+    let chunks: Vec<&str> = payload
+        .as_bytes()
+        .chunks(CHUNK)
+        .map(|chunk| std::str::from_utf8(chunk).unwrap_or_default())
+        .collect();
+    let mut out = String::with_capacity(payload.len() + chunks.len() * 16);
+    for (n, chunk) in chunks.iter().enumerate() {
+        let more = (n + 1 < chunks.len()) as u8;
+        if n == 0 {
+            out.push_str(&format!("\x1b_Ga=T,f=100,i={},q=2,C=1,m={};{}\x1b\\", IMAGE_ID, more, chunk));
+        } else {
+            out.push_str(&format!("\x1b_Gm={};{}\x1b\\", more, chunk));
+        }
+    }
+    out
+}
+
+pub fn delete() -> String {
+    format!("\x1b_Ga=d,d=I,i={},q=2\x1b\\", IMAGE_ID)
+}
+
+fn encode_png(image: &PageImage) -> Vec<u8> {
+    let mut raw = Vec::with_capacity((image.width * 3 + 1) * image.height);
+    for row in image.pixels.chunks(image.width.max(1)) {
+        raw.push(0);
+        raw.extend(row.iter().flatten());
+    }
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::fast());
+    let _ = encoder.write_all(&raw);
+    let data = encoder.finish().unwrap_or_default();
+
+    let mut header = Vec::with_capacity(13);
+    header.extend((image.width as u32).to_be_bytes());
+    header.extend((image.height as u32).to_be_bytes());
+    header.extend([8, 2, 0, 0, 0]);
+
+    let mut png = b"\x89PNG\r\n\x1a\n".to_vec();
+    push_chunk(&mut png, b"IHDR", &header);
+    push_chunk(&mut png, b"IDAT", &data);
+    push_chunk(&mut png, b"IEND", &[]);
+    png
+}
+
+fn push_chunk(png: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    let mut crc = Crc::new();
+    crc.update(kind);
+    crc.update(data);
+    png.extend((data.len() as u32).to_be_bytes());
+    png.extend(kind);
+    png.extend(data);
+    png.extend(crc.sum().to_be_bytes());
+}
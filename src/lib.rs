@@ -0,0 +1,4789 @@
+mod action;
+#[cfg(feature = "battery")]
+mod battery;
+mod breaks;
+mod cache;
+mod cli;
+mod clipboard;
+mod clock;
+mod code;
+mod completions;
+mod diff;
+mod direction;
+mod exit;
+mod extract;
+mod external;
+mod folder;
+mod footnotes;
+mod fuzzy;
+mod headings;
+pub mod hooks;
+mod hyphenate;
+mod images;
+mod kitty;
+mod links;
+mod notes;
+mod prefs;
+mod prompt;
+mod search;
+mod session;
+mod settings;
+mod sidecar;
+mod sixel;
+mod state;
+mod statusline;
+#[cfg(test)]
+mod testdir;
+mod theme;
+mod timing;
+#[cfg(feature = "tts")]
+mod tts;
+mod urls;
+
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::ops::Range;
+use std::path::Path;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+use std::fmt::Write as _;
+use std::io::{stdout, IsTerminal, Write};
+use crossterm::{
+    event::{self, DisableBracketedPaste, DisableMouseCapture, EnableBracketedPaste, EnableMouseCapture, Event, KeyCode, KeyEventKind, MouseButton, MouseEvent, MouseEventKind},
+    execute,
+    terminal::{self, ClearType},
+    cursor,
+    style::{ContentStyle, StyledContent, Stylize}
+};
+use lopdf::Document;
+use serde::Serialize;
+use similar::ChangeTag;
+use textwrap::fill;
+
+use action::{Action, KeyState, Keymap};
+use cache::Lru;
+use exit::{ExitCode, Failure};
+
+pub use hooks::{Hooks, NoHooks};
+use links::{Link, LinkTarget, OutlineEntry};
+use notes::{Highlight, Note, Notes, HIGHLIGHT_COLORS};
+use prefs::Prefs;
+use search::Search;
+use session::{Session, SessionDocument};
+use settings::{Setting, Value};
+use state::DocState;
+use theme::Theme;
+use timing::Timings;
+
+struct Page {
+    text: String,
+    pdf_page: usize,
+    offset: usize,
+    chars: usize,
+    lines: Option<Range<usize>>,
+    rtl: bool,
+    synthetic: bool,
+}
+
+impl Page {
+    fn new(text: String, pdf_page: usize, offset: usize) -> Page {
+        let chars = text.chars().count();
+        Page { text, pdf_page, offset, chars, lines: None, rtl: false, synthetic: false }
+    }
+
+    fn marker(count: usize, pdf_page: usize, offset: usize) -> Page {
+        Page { synthetic: true, ..Page::new(blank_marker(count), pdf_page, offset) }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+struct SplitOptions {
+    anchor: usize,
+    min_chars: usize,
+    squeeze: bool,
+    tables: bool,
+    code: bool,
+    direction: bool,
+    footnotes: bool,
+    hyphenate: Option<hyphenate::Language>,
+    breaks: breaks::Break,
+    blank_marker: bool,
+}
+
+#[derive(Default)]
+struct Layout {
+    header: Range<u16>,
+    content_top: u16,
+    content_lines: usize,
+    tile_width: u16,
+    controls: u16,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Pane {
+    offset: usize,
+    vertical: bool,
+    first: bool,
+}
+
+struct LoadedDocument {
+    doc: Document,
+    text: String,
+    links: Vec<Link>,
+    extractor: &'static str,
+    timings: Timings,
+}
+
+struct VisibleLink {
+    ranges: Vec<Range<usize>>,
+    target: LinkTarget,
+}
+
+pub struct PdfViewer {
+    doc: Document,
+    full_text: String,
+    pages: Vec<Page>,
+    current_page: usize,
+    total_pages: usize,
+    terminal_width: u16,
+    terminal_height: u16,
+    pdf_name: String,
+    title: Option<String>,
+    links: Vec<Link>,
+    outline: Vec<OutlineEntry>,
+    selected_link: Option<(usize, usize)>,
+    jump_list: Vec<usize>,
+    recent: Vec<usize>,
+    dwell: (usize, Instant),
+    idle_dim: Option<Duration>,
+    last_input: Instant,
+    dimmed: bool,
+    widgets: String,
+    widgets_at: Instant,
+    window_title: String,
+    clock_shown: String,
+    reading: Duration,
+    reading_tick: Instant,
+    keymap: Keymap,
+    selection: Option<(usize, usize)>,
+    horizontal: (usize, usize),
+    pipe_history: Vec<String>,
+    pane: Option<Pane>,
+    continuous: bool,
+    scroll: (usize, usize),
+    line_prefix: Vec<usize>,
+    prefs: Prefs,
+    theme: Theme,
+    cursor_row: usize,
+    cursor_line_before_ruler: Option<bool>,
+    status: Option<String>,
+    help_hint: bool,
+    #[cfg(feature = "tts")]
+    speaker: Option<tts::Speaker>,
+    compare_path: Option<String>,
+    diff_mode: bool,
+    other_pages: Option<Vec<Page>>,
+    other_page: usize,
+    hunks: Vec<(usize, usize)>,
+    diff_lines: (usize, usize),
+    minimap: bool,
+    minimap_row: Option<usize>,
+    notes: Notes,
+    extractor: String,
+    extractor_used: &'static str,
+    encoding: String,
+    columns: String,
+    timings: Timings,
+    keep_output: bool,
+    sidecar_settings: Vec<(&'static Setting, Value)>,
+    state: DocState,
+    search: Option<Search>,
+    current_match: Option<usize>,
+    raw_mode: bool,
+    char_prefix: Vec<usize>,
+    layout: Layout,
+    cols: u8,
+    sections: Vec<Range<usize>>,
+    split: SplitOptions,
+    hidden_pages: usize,
+    at_boundary: bool,
+    step_failed: bool,
+    hooks: Box<dyn Hooks>,
+    macros: BTreeMap<char, Macro>,
+    recording: Option<(char, Macro)>,
+    typed: Option<String>,
+    transient: Option<(String, Instant, bool)>,
+    wrap_cache: RefCell<Lru<(usize, usize, usize), Vec<String>>>,
+}
+
+const MINIMAP_WIDTH: u16 = 18;
+const PAGE_WINDOW: usize = 8;
+const RECENT_PAGES: usize = 8;
+const DWELL: Duration = Duration::from_secs(1);
+const WIDGET_REFRESH: Duration = Duration::from_secs(1);
+type Macro = Vec<(Action, Option<usize>, Option<String>)>;
+
+const FRAME_MARGIN: usize = 1;
+const TRANSIENT: Duration = Duration::from_millis(500);
+const WRAP_STEP: u16 = 4;
+const PROGRESS_WIDTH: usize = 20;
+const MIN_WRAP: u16 = 20;
+
+static TITLE_SET: AtomicBool = AtomicBool::new(false);
+
+impl PdfViewer {
+    pub fn new(pdf_path: &str, extractor: &str, encoding: &str, columns: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        let LoadedDocument { doc, text: full_text, links, extractor: extractor_used, mut timings } = Self::load_document(pdf_path, extractor, encoding, columns)?;
+        let (terminal_width, terminal_height) = terminal::size()?;
+        let prefs = Prefs::load();
+        let mut viewer = timings.measure("paginación", || Self::from_text(pdf_path, doc, full_text, terminal_width, terminal_height, prefs));
+        viewer.links = links;
+        viewer.extractor = extractor.to_string();
+        viewer.extractor_used = extractor_used;
+        viewer.encoding = encoding.to_string();
+        viewer.columns = columns.to_string();
+        viewer.timings = timings;
+        Ok(viewer)
+    }
+
+    fn from_text(pdf_path: &str, doc: Document, full_text: String, terminal_width: u16, terminal_height: u16, prefs: Prefs) -> Self {
+        let split = SplitOptions {
+            anchor: 0,
+            min_chars: if prefs.skip_blank { prefs.blank_threshold } else { 0 },
+            squeeze: prefs.squeeze_whitespace,
+            tables: prefs.preserve_tables,
+            direction: prefs.detect_direction,
+            code: prefs.highlight_code,
+            footnotes: prefs.move_footnotes,
+            hyphenate: hyphenate::language(&prefs.hyphenate),
+            breaks: breaks::policy(&prefs.line_break),
+            blank_marker: prefs.blank_pages == "marker",
+        };
+        let pages = Self::split_into_pages_with(&full_text, zoomed_width(terminal_width, prefs.wrap_width), terminal_height, split);
+        let total_pages = pages.len();
+        let theme = Theme::named(&prefs.theme);
+        let title = links::info_field(&doc, b"Title");
+        let mut outline = links::collect_outline(&doc);
+        if outline.is_empty() {
+            outline = headings::detect(&full_text);
+        }
+        
+        let wrap_cache = RefCell::new(Lru::new(prefs.wrap_cache_size));
+        let mut viewer = PdfViewer {
+            doc,
+            full_text,
+            pages,
+            current_page: 0,
+            total_pages: if total_pages == 0 { 1 } else { total_pages },
+            terminal_width,
+            terminal_height,
+            pdf_name: display_name(pdf_path),
+            title,
+            links: Vec::new(),
+            outline,
+            selected_link: None,
+            jump_list: Vec::new(),
+            recent: Vec::new(),
+            dwell: (0, Instant::now()),
+            idle_dim: None,
+            last_input: Instant::now(),
+            widgets: String::new(),
+            widgets_at: Instant::now(),
+            window_title: String::new(),
+            clock_shown: String::new(),
+            reading: Duration::ZERO,
+            reading_tick: Instant::now(),
+            dimmed: false,
+            keymap: Keymap::named(&prefs.keymap).unwrap_or_default(),
+            selection: None,
+            horizontal: (0, 0),
+            pipe_history: Vec::new(),
+            pane: None,
+            continuous: false,
+            scroll: (0, 0),
+            line_prefix: Vec::new(),
+            prefs,
+            theme,
+            cursor_row: 0,
+            cursor_line_before_ruler: None,
+            status: None,
+            help_hint: false,
+            #[cfg(feature = "tts")]
+            speaker: None,
+            compare_path: None,
+            diff_mode: false,
+            other_pages: None,
+            other_page: 0,
+            hunks: Vec::new(),
+            diff_lines: (0, 0),
+            minimap: false,
+            minimap_row: None,
+            notes: Notes::load(pdf_path),
+            extractor: String::new(),
+            extractor_used: "",
+            encoding: String::new(),
+            columns: String::new(),
+            timings: Timings::default(),
+            keep_output: false,
+            sidecar_settings: Vec::new(),
+            state: DocState::load(pdf_path),
+            search: None,
+            current_match: None,
+            raw_mode: false,
+            char_prefix: Vec::new(),
+            layout: Layout::default(),
+            cols: 1,
+            sections: Vec::new(),
+            split,
+            hidden_pages: 0,
+            at_boundary: false,
+            step_failed: false,
+            hooks: Box::new(NoHooks),
+            macros: BTreeMap::new(),
+            recording: None,
+            typed: None,
+            transient: None,
+            wrap_cache,
+        };
+        viewer.sections = viewer.full_text.split('\x0C')
+            .scan(0, |start, section| {
+                let range = *start..*start + section.len();
+                *start = range.end + 1;
+                Some(range)
+            })
+            .collect();
+        viewer.index_pages();
+        let unknown = statusline::unknown(&viewer.prefs.statusline);
+        if !unknown.is_empty() {
+            viewer.status = Some(format!("statusline: marcadores desconocidos {}", unknown.join(" ")));
+        }
+        if viewer.prefs.ruler {
+            viewer.cursor_line_before_ruler = Some(viewer.prefs.cursor_line);
+            viewer.prefs.cursor_line = true;
+        }
+        if let Some(error) = viewer.notes.error() {
+            viewer.status = Some(error);
+        }
+        if viewer.prefs.help_key != "?" && viewer.help_key().is_none() {
+            viewer.status = Some(format!("help_key: tecla no válida \"{}\" (un carácter o F1-F12)", viewer.prefs.help_key));
+        }
+        viewer
+    }
+
+    fn load_document(pdf_path: &str, extractor: &str, encoding: &str, columns: &str) -> Result<LoadedDocument, Box<dyn std::error::Error>> {
+        let mut timings = Timings::default();
+        let bytes = timings.measure("lectura", || std::fs::read(pdf_path))?;
+        let doc = timings.measure("análisis", || Document::load_mem(&bytes))
+            .map_err(|e| format!("Error al extraer texto del PDF: {}", e))?;
+        if doc.trailer.get(b"Encrypt").is_ok() {
+            return Err(Failure::new(ExitCode::Encrypted, "El PDF está cifrado y necesita contraseña").into());
+        }
+        let mut links = timings.measure("enlaces", || links::collect_links(&doc));
+        let (text, extractor_used) = timings.measure("extracción", || extract::extract_with_fallback(extractor, columns, pdf_path, &doc, &mut links))
+            .map_err(|e| format!("Error al extraer texto del PDF: {}", e))?;
+        let full_text = timings.measure("limpieza", || extract::linearize_columns(extract::normalize(&text, encoding), columns));
+        Ok(LoadedDocument { doc, text: full_text, links, extractor: extractor_used, timings })
+    }
+
+    fn split_into_pages_with(text: &str, width: u16, height: u16, options: SplitOptions) -> Vec<Page> {
+        let content_width = (width as usize).saturating_sub(6);
+        let content_height = (height as usize).saturating_sub(8);
+        
+        if text.trim().is_empty() {
+            return Vec::new();
+        }
+
+        let mut pages = Vec::new();
+        let mut current_page = String::new();
+        let mut lines_in_page = 0;
+        let mut page_offset = None;
+        let mut section_start = 0;
+        
+        let page_sections: Vec<&str> = text.split('\x0C').collect(); 
+        
+        let mut first_line = 0;
+        let mut line_count = 0;
+        let mut blank_run: Option<(usize, usize, usize)> = None;
+        for (section_index, section) in page_sections.iter().enumerate() {
+            if section.trim().is_empty() && page_sections.len() > 1 {
+                if options.blank_marker {
+                    let (_, _, count) = blank_run.get_or_insert((section_index, section_start, 0));
+                    *count += 1;
+                }
+                section_start += section.len() + 1;
+                continue;
+            }
+            if let Some((first, offset, count)) = blank_run.take() {
+                pages.push(Page::marker(count, first, offset));
+            }
+            if is_blank(section, options.min_chars) {
+                section_start += section.len() + 1;
+                continue;
+            }
+            let (lines, break_at) = wrap_section(section, section_start, content_width, options);
+            let mut cursor = 0;
+            first_line = 0;
+            line_count = lines.len();
+            
+            for (line_index, line) in lines.into_iter().enumerate() {
+                if lines_in_page >= content_height || (Some(line_index) == break_at && lines_in_page > 0) {
+                    let offset = page_offset.take().unwrap_or(section_start);
+                    let mut page = Page::new(trim_page(&current_page).to_string(), section_index, offset);
+                    page.lines = Some(first_line..line_index);
+                    pages.push(page);
+                    current_page = String::new();
+                    lines_in_page = 0;
+                    first_line = line_index;
+                }
+                
+                let line_offset = section_start + advance_past(section, &mut cursor, &line);
+                if page_offset.is_none() && !line.trim().is_empty() {
+                    page_offset = Some(line_offset);
+                }
+                current_page.push_str(&line);
+                current_page.push('\n');
+                lines_in_page += 1;
+            }
+            
+            if page_sections.len() > 1 && !current_page.trim().is_empty() {
+                let offset = page_offset.take().unwrap_or(section_start);
+                let mut page = Page::new(trim_page(&current_page).to_string(), section_index, offset);
+                page.lines = Some(first_line..line_count);
+                pages.push(page);
+                current_page = String::new();
+                lines_in_page = 0;
+            }
+            section_start += section.len() + 1;
+        }
+        
+        if let Some((first, offset, count)) = blank_run.filter(|_| !pages.is_empty()) {
+            pages.push(Page::marker(count, first, offset));
+        }
+        if !current_page.trim().is_empty() {
+            let offset = page_offset.unwrap_or(0);
+            let mut page = Page::new(trim_page(&current_page).to_string(), page_sections.len() - 1, offset);
+            page.lines = Some(first_line..line_count);
+            pages.push(page);
+        }
+        
+        if pages.is_empty() {
+            let wrapped_text = fill(text, content_width);
+            let lines: Vec<&str> = wrapped_text.lines().collect();
+            
+            let mut page_content = String::new();
+            let mut lines_count = 0;
+            
+            for line in lines {
+                if lines_count >= content_height {
+                    pages.push(Page::new(page_content.trim().to_string(), 0, 0));
+                    page_content = String::new();
+                    lines_count = 0;
+                }
+                page_content.push_str(line);
+                page_content.push('\n');
+                lines_count += 1;
+            }
+            
+            if !page_content.trim().is_empty() {
+                pages.push(Page::new(page_content.trim().to_string(), 0, 0));
+            }
+        }
+
+        if options.direction && text.len() <= direction::MAX_BYTES {
+            for page in &mut pages {
+                page.rtl = direction::is_rtl(&page.text);
+            }
+        }
+        pages
+    }
+
+    fn empty_message(&self) -> String {
+        let image_pages = (0..self.doc.get_pages().len())
+            .filter(|page| !images::page_images(&self.doc, *page).is_empty())
+            .count();
+        if image_pages > 0 {
+            return format!(
+                "No se pudo extraer texto de este PDF, pero {} de sus páginas tienen imágenes: probablemente es un documento escaneado.\n\nPulsa i para ver las imágenes de la página.",
+                image_pages
+            );
+        }
+        "El PDF parece estar vacío o el texto no se pudo extraer.\n\nEsto puede suceder con:\n• PDFs que son principalmente imágenes\n• PDFs con texto incrustado\n• PDFs con codificación especial\n\nIntenta con un PDF que contenga texto seleccionable.".to_string()
+    }
+
+    fn split_raw(text: &str, width: u16, height: u16, anchor: usize) -> Vec<Page> {
+        let content_width = (width as usize).saturating_sub(6).max(1);
+        let content_height = (height as usize).saturating_sub(8).max(1);
+        let mut pages = Vec::new();
+        let mut current = String::new();
+        let mut lines_in_page = 0;
+        let mut page_offset = 0;
+        let mut pdf_page = 0;
+        let mut page_pdf_page = 0;
+        let mut line_start = 0;
+
+        for line in text.split('\n') {
+            let chars: Vec<char> = line.chars().collect();
+            let mut chunk_offset = line_start;
+            for chunk in chars.chunks(content_width).chain(chars.is_empty().then_some(&[][..])) {
+                let chunk_len = chunk.iter().map(|c| c.len_utf8()).sum::<usize>();
+                let holds_anchor = anchor > page_offset && (chunk_offset..chunk_offset + chunk_len.max(1)).contains(&anchor);
+                if lines_in_page == content_height || (holds_anchor && lines_in_page > 0) {
+                    pages.push(Page::new(std::mem::take(&mut current), page_pdf_page, page_offset));
+                    lines_in_page = 0;
+                }
+                if lines_in_page == 0 {
+                    page_offset = chunk_offset;
+                    page_pdf_page = pdf_page;
+                } else {
+                    current.push('\n');
+                }
+                current.extend(chunk.iter().map(|c| control_picture(*c)));
+                lines_in_page += 1;
+                chunk_offset += chunk_len;
+            }
+            pdf_page += line.matches('\x0C').count();
+            line_start += line.len() + 1;
+        }
+        if lines_in_page > 0 {
+            pages.push(Page::new(current, page_pdf_page, page_offset));
+        }
+        pages
+    }
+
+    fn toggle_raw(&mut self) {
+        self.raw_mode = !self.raw_mode;
+        self.reflow();
+    }
+
+    fn index_pages(&mut self) {
+        self.char_prefix = std::iter::once(0)
+            .chain(self.pages.iter().scan(0, |total, page| {
+                *total += page.chars;
+                Some(*total)
+            }))
+            .collect();
+        self.hidden_pages = self.count_hidden_pages();
+        self.materialize();
+        self.index_lines();
+    }
+
+    fn index_lines(&mut self) {
+        self.line_prefix = if self.continuous {
+            std::iter::once(0)
+                .chain((0..self.pages.len()).scan(0, |total, index| {
+                    *total += self.page_text(index).split('\n').count();
+                    Some(*total)
+                }))
+                .collect()
+        } else {
+            Vec::new()
+        };
+    }
+
+    fn set_continuous(&mut self, enabled: bool) {
+        if enabled == self.continuous {
+            return;
+        }
+        if enabled && (self.cols > 1 || self.pane.is_some()) {
+            self.cols = 1;
+            self.pane = None;
+            self.reflow();
+        }
+        self.continuous = enabled;
+        self.scroll = (self.current_page, 0);
+        self.index_lines();
+    }
+
+    fn at_end(&self) -> bool {
+        if self.pages.is_empty() {
+            return false;
+        }
+        if self.continuous {
+            let content_height = (self.page_height() as usize).saturating_sub(8);
+            return self.top_line() + content_height >= self.line_prefix.last().copied().unwrap_or(0);
+        }
+        self.shown_pages().end >= self.total_pages
+    }
+
+    fn scroll_row(&self) -> usize {
+        if self.continuous && self.scroll.0 == self.current_page { self.scroll.1 } else { 0 }
+    }
+
+    fn top_line(&self) -> usize {
+        self.line_prefix.get(self.current_page).copied().unwrap_or(0) + self.scroll_row()
+    }
+
+    fn scroll_to_line(&mut self, line: usize) {
+        let total = self.line_prefix.last().copied().unwrap_or(0);
+        let content_height = (self.page_height() as usize).saturating_sub(8);
+        let line = line.min(total.saturating_sub(content_height));
+        let page = self.line_prefix.partition_point(|start| *start <= line).saturating_sub(1).min(self.total_pages.saturating_sub(1));
+        self.current_page = page;
+        self.scroll = (page, line - self.line_prefix.get(page).copied().unwrap_or(0));
+    }
+
+    fn scroll_lines(&mut self, delta: isize) -> bool {
+        let top = self.top_line();
+        self.scroll_to_line(top.saturating_add_signed(delta));
+        if self.top_line() == top && delta != 0 {
+            self.hit_boundary(if delta > 0 { "Ya estás al final del documento" } else { "Ya estás al principio del documento" });
+            return false;
+        }
+        true
+    }
+
+    fn continuous_rows(&self, content_width: usize, content_height: usize) -> Vec<String> {
+        let mut rows = Vec::new();
+        let mut skip = self.scroll_row();
+        for index in self.current_page..self.pages.len() {
+            let lines = self.line_prefix.get(index + 1).zip(self.line_prefix.get(index)).map_or(0, |(end, start)| end - start);
+            rows.extend(self.tile_rows(index, content_width, lines).0.into_iter().skip(skip));
+            skip = 0;
+            if rows.len() >= content_height {
+                break;
+            }
+        }
+        rows.resize(content_height, format!("│ {:<width$} │", "", width = content_width));
+        rows
+    }
+
+    fn materialize(&mut self) {
+        let window = self.current_page.saturating_sub(PAGE_WINDOW)..self.current_page + self.cols as usize + PAGE_WINDOW;
+        let mut wrapped: Option<(usize, Rc<Vec<String>>)> = None;
+        for index in 0..self.pages.len() {
+            let page = &self.pages[index];
+            let Some(lines) = page.lines.clone() else { continue };
+            if !window.contains(&index) {
+                if !page.text.is_empty() {
+                    self.pages[index].text = String::new();
+                }
+                continue;
+            }
+            if !page.text.is_empty() {
+                continue;
+            }
+            if wrapped.as_ref().is_none_or(|(section, _)| *section != page.pdf_page) {
+                wrapped = Some((page.pdf_page, self.section_lines(page.pdf_page)));
+            }
+            if let Some((_, section_lines)) = &wrapped {
+                self.pages[index].text = section_lines.get(lines).map_or(String::new(), |lines| trim_page(&lines.join("\n")).to_string());
+            }
+        }
+    }
+
+    fn section_lines(&self, section: usize) -> Rc<Vec<String>> {
+        let Some(range) = self.sections.get(section) else {
+            return Rc::default();
+        };
+        let content_width = (self.wrap_width() as usize).saturating_sub(6);
+        self.wrap_cache.borrow_mut().get_or_insert_with((section, content_width, self.split.anchor), || {
+            wrap_section(&self.full_text[range.clone()], range.start, content_width, self.split).0
+        })
+    }
+
+    fn page_text(&self, index: usize) -> Cow<'_, str> {
+        let Some(page) = self.pages.get(index) else {
+            return Cow::Borrowed("");
+        };
+        match &page.lines {
+            Some(lines) if page.text.is_empty() => {
+                let section_lines = self.section_lines(page.pdf_page);
+                Cow::Owned(section_lines.get(lines.clone()).map_or(String::new(), |lines| trim_page(&lines.join("\n")).to_string()))
+            }
+            _ => Cow::Borrowed(page.text.as_str()),
+        }
+    }
+
+    fn source_text(&self, index: usize) -> Cow<'_, str> {
+        if self.split.hyphenate.is_none() || self.raw_mode || self.diff_mode {
+            return self.page_text(index);
+        }
+        let (start, end) = self.page_range(index);
+        let end = end.min(self.full_text.len());
+        Cow::Owned(self.full_text.get(start..end).unwrap_or("").replace('\x0C', "\n").trim().to_string())
+    }
+
+    fn available_width(&self) -> u16 {
+        if self.minimap {
+            self.terminal_width.saturating_sub(MINIMAP_WIDTH)
+        } else {
+            self.terminal_width
+        }
+    }
+
+    fn text_width(&self) -> u16 {
+        zoomed_width(self.available_width(), self.prefs.wrap_width)
+    }
+
+    fn margin(&self) -> u16 {
+        self.available_width().saturating_sub(self.text_width()) / 2
+    }
+
+    fn zoom(&mut self, delta: i32) {
+        let widest = self.available_width().saturating_sub(6);
+        let current = self.text_width().saturating_sub(6) as i32;
+        let width = (current + delta).clamp(MIN_WRAP.min(widest) as i32, widest as i32) as u16;
+        self.prefs.wrap_width = if width >= widest { 0 } else { width };
+        self.reflow();
+        self.persist("wrap_width", i64::from(self.prefs.wrap_width));
+        self.notify(&format!("ancho: {}{}", width, if self.prefs.wrap_width == 0 { " (terminal)" } else { "" }));
+    }
+
+    fn zoom_reset(&mut self) {
+        self.prefs.wrap_width = 0;
+        self.reflow();
+        self.persist("wrap_width", 0);
+        self.notify(&format!("ancho: {} (terminal)", self.text_width().saturating_sub(6)));
+    }
+
+    fn tile_width(&self) -> u16 {
+        match self.pane {
+            Some(pane) if pane.vertical => self.text_width() / 2,
+            _ => self.text_width() / self.cols.max(1) as u16,
+        }
+    }
+
+    fn gutter_width(&self) -> usize {
+        if self.prefs.number == "off" || self.raw_mode || self.diff_mode {
+            return 0;
+        }
+        self.page_height().to_string().len() + 1
+    }
+
+    fn wrap_width(&self) -> u16 {
+        self.tile_width().saturating_sub(self.gutter_width() as u16)
+    }
+
+    fn gutter(&self, row: usize, cursor_row: Option<usize>) -> String {
+        let width = self.gutter_width().saturating_sub(1);
+        match cursor_row.filter(|_| self.prefs.number == "relative") {
+            Some(cursor) if cursor == row => format!("{:<width$} ", row + 1).bold().to_string(),
+            Some(cursor) => format!("{:>width$} ", row.abs_diff(cursor)).dark_grey().to_string(),
+            None => format!("{:>width$} ", row + 1).dark_grey().to_string(),
+        }
+    }
+
+    fn cycle_numbers(&mut self) {
+        let next = settings::NUMBERS.iter().position(|mode| *mode == self.prefs.number).map_or(0, |index| (index + 1) % settings::NUMBERS.len());
+        self.prefs.number = settings::NUMBERS[next].to_string();
+        self.reflow();
+        self.persist("number", self.prefs.number.clone());
+        self.notify(&format!("números de línea: {}", self.prefs.number));
+    }
+
+    fn page_height(&self) -> u16 {
+        match self.pane {
+            Some(pane) if !pane.vertical => self.terminal_height.saturating_sub(10) / 2 + 8,
+            _ => self.terminal_height,
+        }
+    }
+
+    fn frame_rows(&self, content_height: usize) -> usize {
+        if !self.prefs.shrink_frame || self.continuous || self.pane.is_some() || self.pages.is_empty() {
+            return content_height;
+        }
+        let used = self.shown_pages()
+            .map(|index| self.page_text(index).trim_end().split('\n').count())
+            .max()
+            .unwrap_or(content_height);
+        (used + FRAME_MARGIN).min(content_height)
+    }
+
+    fn shown_pages(&self) -> Range<usize> {
+        let start = self.current_page.min(self.pages.len());
+        start..(start + self.cols as usize).min(self.pages.len())
+    }
+
+    fn draw_page(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        self.materialize();
+        self.update_title()?;
+        self.layout = self.frame_layout();
+        execute!(stdout(), terminal::Clear(ClearType::All), cursor::MoveTo(0, 0))?;
+        let frame = self.render();
+        if self.dimmed {
+            print!("{}", strip_ansi(&frame).lines().map(|line| format!("{}\n", line.dark_grey())).collect::<String>());
+        } else {
+            print!("{}", frame);
+        }
+        stdout().flush()?;
+        Ok(())
+    }
+
+    fn update_title(&mut self) -> std::io::Result<()> {
+        if !self.prefs.set_title {
+            return Ok(());
+        }
+        let title = format!("{} — p. {}/{} — pdfview", self.pdf_name, self.current_page + 1, self.total_pages);
+        if title == self.window_title {
+            return Ok(());
+        }
+        if !TITLE_SET.swap(true, Ordering::SeqCst) {
+            print!("\x1b[22;0t");
+        }
+        execute!(stdout(), terminal::SetTitle(&title))?;
+        self.window_title = title;
+        Ok(())
+    }
+
+    fn header(&self) -> String {
+        let shown = self.shown_pages();
+        let mut name = match (&self.compare_path, self.diff_mode) {
+            (Some(other), true) => format!(
+                "{} ↔ {} (DIFF: {} cambios, +{} −{} líneas)",
+                self.pdf_name,
+                display_name(other),
+                self.hunks.len(),
+                self.diff_lines.0,
+                self.diff_lines.1
+            ),
+            _ => self.pdf_name.clone(),
+        };
+        if self.raw_mode && !self.diff_mode {
+            name.push_str(" (RAW)");
+        }
+        let (start, _) = self.page_range(self.current_page);
+        let (_, end) = self.page_range(shown.end.saturating_sub(1).max(self.current_page));
+        let note_count = self.notes.count_in(start, end);
+        let (page_label, total_label) = self.page_labels();
+        let mut header = format!(
+            "📄 {} - Página {}/{} 📄", 
+            name,
+            page_label, 
+            total_label
+        );
+        if self.hidden_pages > 0 {
+            if let Some(page) = self.pages.get(self.current_page) {
+                header.push_str(&format!(" (PDF p. {})", page.pdf_page + 1));
+            }
+        }
+        if note_count > 0 {
+            header.push_str(&format!(" 📝 {}", note_count));
+        }
+        if let Some(pane) = self.pane {
+            header.push_str(&format!(" | panel {}/2, el otro en p. {}", if pane.first { 1 } else { 2 }, self.page_for_offset(pane.offset).0 + 1));
+        }
+        header
+    }
+
+    fn page_labels(&self) -> (String, String) {
+        let shown = self.shown_pages();
+        if self.pages.is_empty() {
+            ("—".to_string(), "—".to_string())
+        } else if shown.len() > 1 {
+            (format!("{}-{}", shown.start + 1, shown.end), self.total_pages.to_string())
+        } else {
+            ((self.current_page + 1).to_string(), self.total_pages.to_string())
+        }
+    }
+
+    fn progress(&self) -> (f32, &'static str) {
+        if self.continuous {
+            let total = self.line_prefix.last().copied().unwrap_or(0).max(1);
+            let content_height = (self.page_height() as usize).saturating_sub(8);
+            return ((self.top_line() + content_height).min(total) as f32 / total as f32, "Progreso (líneas)");
+        }
+        match self.prefs.progress.as_str() {
+            "chars" => {
+                let read = self.char_prefix.get(self.current_page + 1).copied().unwrap_or(0);
+                let total = self.char_prefix.last().copied().unwrap_or(0).max(1);
+                (read as f32 / total as f32, "Progreso (caracteres)")
+            }
+            _ => (page_fraction(self.current_page, self.total_pages), "Progreso"),
+        }
+    }
+
+    fn statusline_value(&self, name: &str) -> Option<String> {
+        Some(match name {
+            "name" => self.pdf_name.clone(),
+            "title" => self.title.clone().unwrap_or_else(|| self.pdf_name.clone()),
+            "page" => self.page_labels().0,
+            "pages" => self.page_labels().1,
+            "pdfpage" => self.pages.get(self.current_page).map_or("—".to_string(), |page| (page.pdf_page + 1).to_string()),
+            "percent" => format!("{:.0}", self.progress().0 * 100.0),
+            "matches" => match &self.search {
+                Some(search) => match search.matches.binary_search_by_key(&self.current_match, |m| Some(m.start)) {
+                    Ok(index) => format!("{}/{}", index + 1, search.matches.len()),
+                    Err(_) => search.matches.len().to_string(),
+                },
+                None => String::new(),
+            },
+            "clock" => clock::local_time(),
+            "zoom" => {
+                let content_width = (self.tile_width() as usize).saturating_sub(6);
+                format!("{}%", content_width * 100 / (self.terminal_width as usize).saturating_sub(6).max(1))
+            }
+            _ => return None,
+        })
+    }
+
+    fn frame_layout(&self) -> Layout {
+        let content_width = (self.tile_width() as usize).saturating_sub(6);
+        let content_height = (self.page_height() as usize).saturating_sub(8);
+        let header_rows = (self.header().chars().count() as u16).div_ceil(self.terminal_width.max(1)).max(1);
+        let below = self.pane.is_some_and(|pane| !pane.vertical && !pane.first);
+        Layout {
+            header: 0..header_rows,
+            content_top: header_rows + 2 + if below { content_height as u16 + 2 } else { 0 },
+            content_lines: self.tile_rows(self.current_page, content_width, content_height).1,
+            tile_width: self.margin() + content_width as u16 + 4,
+            controls: header_rows + 2 + self.frame_rows(content_height) as u16 + 2 + if self.pane.is_some_and(|pane| !pane.vertical) { content_height as u16 + 2 } else { 0 },
+        }
+    }
+
+    fn render(&self) -> String {
+        let mut frame = String::new();
+        let content_width = (self.tile_width() as usize).saturating_sub(6);
+        let content_height = (self.page_height() as usize).saturating_sub(8);
+        let shown = self.shown_pages();
+        let (start, _) = self.page_range(self.current_page);
+        let (_, end) = self.page_range(shown.end.saturating_sub(1).max(self.current_page));
+        let hidden = self.hidden_pages;
+        
+        let _ = writeln!(frame, "{}", self.header().bold().blue());
+        let _ = writeln!(frame); 
+        
+        let boxes: Vec<Vec<(usize, bool)>> = match self.pane {
+            Some(pane) => {
+                let other = (self.page_for_offset(pane.offset).0, false);
+                let focused = (self.current_page, true);
+                let pair = if pane.first { [focused, other] } else { [other, focused] };
+                if pane.vertical { vec![pair.to_vec()] } else { pair.iter().map(|tile| vec![*tile]).collect() }
+            }
+            None => vec![(0..self.cols as usize).map(|tile| (self.current_page + tile, false)).collect()],
+        };
+        let border = "─".repeat(content_width + 2);
+        let frameless = self.continuous && !self.prefs.scroll_frame;
+        let edge = |left: char, right: char, focused: bool| {
+            let edge = format!("{}{}{}", left, border, right);
+            if frameless {
+                String::new()
+            } else if self.transient.as_ref().is_some_and(|(_, _, flash)| *flash) {
+                edge.yellow().bold().to_string()
+            } else if focused {
+                edge.cyan().bold().to_string()
+            } else {
+                edge
+            }
+        };
+        
+        let minimap = self.minimap_rows((self.terminal_height as usize).saturating_sub(8));
+        let mut minimap_row = 0;
+        let frame_rows = self.frame_rows(content_height);
+        let indent = " ".repeat(self.margin() as usize);
+        for tiles in &boxes {
+            let rows: Vec<(Vec<String>, usize)> = if self.continuous {
+                vec![(self.continuous_rows(content_width, content_height), 0)]
+            } else {
+                tiles.iter().map(|(index, _)| self.tile_rows(*index, content_width, content_height)).collect()
+            };
+            let _ = writeln!(frame, "{}{}", indent, tiles.iter().map(|(_, focused)| edge('┌', '┐', *focused)).collect::<String>());
+            for row in 0..frame_rows {
+                let mut line: String = rows.iter().map(|(rows, _)| rows[row].as_str()).collect();
+                if frameless {
+                    line = line.strip_prefix('│').map_or(line.clone(), |rest| format!(" {}", rest));
+                    line = line.strip_suffix('│').map_or(line.clone(), |rest| format!("{} ", rest));
+                }
+                let side = minimap.get(minimap_row + row).map_or(String::new(), |row| row.to_string());
+                let _ = writeln!(frame, "{}{}{}", indent, line, side);
+            }
+            let _ = writeln!(frame, "{}{}", indent, tiles.iter().map(|(_, focused)| edge('└', '┘', *focused)).collect::<String>());
+            minimap_row += frame_rows + 2;
+        }
+        let _ = writeln!(frame); 
+        
+        let controls = if self.pages.is_empty() {
+            "⌨️  Controles: q/ESC Salir | ? Ayuda | i Imágenes"
+        } else if self.total_pages > 1 {
+            "⌨️  Controles: ← Anterior | → Siguiente | q/ESC Salir | r Refrescar"
+        } else {
+            "⌨️  Controles: q/ESC Salir | r Refrescar"
+        };
+        
+        if self.widgets.is_empty() {
+            let _ = writeln!(frame, "{}", controls.italic().dark_grey());
+        } else {
+            let padding = (self.terminal_width as usize).saturating_sub(controls.chars().count() + self.widgets.chars().count() + 2);
+            let _ = writeln!(frame, "{}{}{}", controls.italic().dark_grey(), " ".repeat(padding), self.widgets.as_str().dark_cyan());
+        }
+        
+        if let Some((message, _, _)) = &self.transient {
+            let _ = writeln!(frame, "{}", message.as_str().yellow().bold());
+        } else if let Some(status) = &self.status {
+            let _ = writeln!(frame, "{}", status.as_str().yellow());
+        } else if self.help_hint {
+            let key = if self.help_key().is_some() { self.prefs.help_key.as_str() } else { "?" };
+            let _ = writeln!(frame, "{}", format!("Pulsa {} para ayuda", key).dark_grey());
+        }
+        
+        let marks: Vec<String> = self.state.quickmarks.iter()
+            .map(|(slot, offset)| {
+                let slot = slot.to_string();
+                if (start..end).contains(offset) {
+                    slot.reverse().to_string()
+                } else {
+                    slot
+                }
+            })
+            .collect();
+        let mut marks = if marks.is_empty() { String::new() } else { format!("  🔖 {}", marks.join(" ")) };
+        if hidden > 0 {
+            marks.push_str(&format!("  {} en blanco ocultas", hidden));
+        }
+        let modes = self.mode_flags();
+        if !modes.is_empty() {
+            marks.push_str(&format!("  {}", format!("[{}]", modes.join(" ")).bold()));
+        }
+        if self.at_end() {
+            marks.push_str(&format!("  {}", "(END)".reverse()));
+        }
+        
+        if !self.prefs.statusline.is_empty() && !self.pages.is_empty() {
+            let line = statusline::expand(&self.prefs.statusline, |name| self.statusline_value(name));
+            let _ = writeln!(frame, "{}", statusline::layout(&line, self.terminal_width as usize).dark_cyan());
+            if !marks.is_empty() {
+                let _ = writeln!(frame, "{}", marks.trim_start());
+            }
+        } else if self.total_pages > 1 && !self.pages.is_empty() {
+            let (fraction, label) = self.progress();
+            let filled = progress_fill(fraction);
+            let progress = format!(
+                "{}: [{}{}] {:.1}%",
+                label,
+                "█".repeat(filled),
+                "░".repeat(PROGRESS_WIDTH.saturating_sub(filled)),
+                fraction * 100.0
+            );
+            let _ = writeln!(frame, "{}{}", progress.dark_cyan(), marks);
+        } else if !marks.is_empty() {
+            let _ = writeln!(frame, "{}", marks.trim_start());
+        }
+        
+        frame
+    }
+
+    fn blank_threshold(&self) -> usize {
+        if self.prefs.skip_blank { self.prefs.blank_threshold } else { 0 }
+    }
+
+    fn count_hidden_pages(&self) -> usize {
+        if self.raw_mode || self.diff_mode {
+            return 0;
+        }
+        let min_chars = self.blank_threshold();
+        self.sections.iter()
+            .filter(|range| !self.full_text[(*range).clone()].trim().is_empty())
+            .filter(|range| is_blank(&self.full_text[(*range).clone()], min_chars))
+            .count()
+    }
+
+    fn mode_flags(&self) -> Vec<&'static str> {
+        #[cfg(feature = "tts")]
+        let speaking = self.speaker.is_some();
+        #[cfg(not(feature = "tts"))]
+        let speaking = false;
+        [
+            (self.prefs.cursor_line, "C"),
+            (self.minimap, "M"),
+            (self.diff_mode, "D"),
+            (self.raw_mode, "R"),
+            (self.search.is_some(), "/"),
+            (speaking, "P"),
+            (self.cols > 1, "T"),
+            (self.pane.is_some(), "S"),
+            (self.continuous, "L"),
+            (self.prefs.ruler && self.prefs.cursor_line, "U"),
+            (self.recording.is_some(), "Q"),
+        ]
+        .into_iter()
+        .filter(|(active, _)| *active)
+        .map(|(_, flag)| flag)
+        .collect()
+    }
+
+    fn tile_rows(&self, index: usize, frame_width: usize, content_height: usize) -> (Vec<String>, usize) {
+        let gutter_width = if self.pages.is_empty() { 0 } else { self.gutter_width().min(frame_width) };
+        let content_width = frame_width - gutter_width;
+        let empty_message = (self.pages.is_empty() && index == self.current_page).then(|| center_text(&fill(&self.empty_message(), content_width), content_width, content_height));
+        let text = self.page_text(index);
+        let page_content = match &empty_message {
+            Some(message) => message.as_str(),
+            None => &text,
+        };
+        
+        let selected = self.selected_link_index();
+        let links = if index == self.current_page { self.visible_links() } else { Vec::new() };
+        let mut link_spans: Vec<(Range<usize>, ContentStyle)> = links
+            .into_iter()
+            .enumerate()
+            .flat_map(|(link_index, link)| {
+                let style = if selected == Some(link_index) {
+                    ContentStyle::new().with(self.theme.selected_link_fg).on(self.theme.selected_link_bg)
+                } else {
+                    ContentStyle::new().with(self.theme.link).underlined()
+                };
+                link.ranges.into_iter().map(move |range| (range, style))
+            })
+            .collect();
+        if let Some(search) = &self.search {
+            let style = ContentStyle::new().with(self.theme.search_match_fg).on(self.theme.search_match_bg);
+            let matches = search::find_all(page_content, &search.query, self.prefs.whole_word);
+            let active = self.active_match(index, page_content, &matches);
+            link_spans.retain(|(range, _)| !matches.iter().any(|m| m.start < range.end && m.end > range.start));
+            link_spans.extend(matches.into_iter().map(|range| {
+                let style = if active.as_ref() == Some(&range) { style.reverse() } else { style };
+                (range, style)
+            }));
+        }
+        if let Some(selection) = self.selection_range().filter(|_| index == self.current_page) {
+            link_spans.retain(|(range, _)| range.start >= selection.end || range.end <= selection.start);
+            link_spans.push((selection, ContentStyle::new().on(self.theme.selection)));
+        }
+        link_spans.sort_by_key(|(range, _)| range.start);
+        
+        let line_ranges = self.line_ranges(index);
+        let cursor_row = (self.prefs.cursor_line && index == self.current_page)
+            .then(|| self.cursor_row.min(page_content.split('\n').count().saturating_sub(1)));
+        let structured = !self.raw_mode && !self.diff_mode && empty_message.is_none();
+        let tables = if self.prefs.preserve_tables && structured {
+            table_rows(&page_content.split('\n').collect::<Vec<_>>(), 2)
+        } else {
+            Vec::new()
+        };
+        let code = if self.prefs.highlight_code && structured {
+            code::blocks(&page_content.split('\n').collect::<Vec<_>>())
+        } else {
+            Vec::new()
+        };
+        let shift = if self.horizontal.0 == self.current_page { self.horizontal.1 } else { 0 };
+        let rtl = self.prefs.detect_direction && !self.raw_mode && !self.diff_mode && self.pages.get(index).is_some_and(|page| page.rtl);
+        let mut rows = Vec::new();
+        let mut line_start = 0;
+        
+        for line in page_content.split('\n') {
+            if rows.len() >= content_height {
+                break;
+            }
+            
+            let full_line = line;
+            let skip = line.char_indices().nth(shift).map_or(line.len(), |(start, _)| start);
+            let visible = &line[skip..];
+            let line = visible.char_indices().nth(content_width).map_or(visible, |(end, _)| &visible[..end]);
+            let (panned_left, more_right) = (skip > 0, line.len() < visible.len());
+            let line_range = line_start + skip..line_start + skip + line.len();
+            let mut spans: Vec<(Range<usize>, ContentStyle)> = link_spans.iter()
+                .filter(|(range, _)| range.start < line_range.end && range.end > line_range.start)
+                .map(|(range, style)| (range.start.max(line_range.start) - line_range.start..range.end.min(line_range.end) - line_range.start, *style))
+                .collect();
+            if let Some(Some(language)) = code.get(rows.len()) {
+                let tokens: Vec<(Range<usize>, ContentStyle)> = code::tokens(full_line, *language)
+                    .into_iter()
+                    .filter(|(token, _)| token.start < skip + line.len() && token.end > skip)
+                    .map(|(token, kind)| (token.start.max(skip) - skip..token.end.min(skip + line.len()) - skip, kind))
+                    .filter(|(token, _)| !spans.iter().any(|(range, _)| range.start < token.end && range.end > token.start))
+                    .map(|(token, kind)| (token, ContentStyle::new().with(self.theme.code(kind))))
+                    .collect();
+                spans.extend(tokens);
+                spans.sort_by_key(|(range, _)| range.start);
+            }
+            if self.diff_mode {
+                let visible = skip..skip + line.len();
+                for (cell, tag) in diff::marks(full_line).into_iter().filter(|(cell, _)| cell.start < visible.end && cell.end > visible.start) {
+                    let style = ContentStyle::new().with(if tag == ChangeTag::Insert { self.theme.diff_added } else { self.theme.diff_removed });
+                    let cell = cell.start.max(skip) - skip..cell.end.min(visible.end) - skip;
+                    let mut covered: Vec<Range<usize>> = spans.iter()
+                        .map(|(range, _)| range.clone())
+                        .filter(|range| range.start < cell.end && range.end > cell.start)
+                        .collect();
+                    covered.sort_by_key(|range| range.start);
+                    let mut start = cell.start;
+                    for range in covered.into_iter().chain(std::iter::once(cell.end..cell.end)) {
+                        if range.start > start {
+                            spans.push((start..range.start, style));
+                        }
+                        start = start.max(range.end);
+                    }
+                }
+                spans.sort_by_key(|(range, _)| range.start);
+            }
+            line_start += full_line.len() + 1;
+            
+            let mut base = ContentStyle::new();
+            if empty_message.is_some() {
+                base = base.yellow().italic();
+            }
+            if let Some((start, end)) = line_ranges.get(rows.len()) {
+                if let Some(highlight) = self.notes.highlights.iter().find(|h| h.start < *end && h.end > *start) {
+                    base = base.on(self.theme.highlight(&highlight.color));
+                }
+            }
+            match cursor_row {
+                Some(row) if row == rows.len() => base = base.on(if self.prefs.ruler { self.theme.ruler } else { self.theme.cursor_line }),
+                Some(_) if self.prefs.ruler => base = base.with(self.theme.ruler_dim),
+                _ => {}
+            }
+            let table = tables.get(rows.len()).copied().unwrap_or(false);
+            if table {
+                base = base.with(self.theme.table);
+            }
+            let border = if panned_left {
+                "<".bold().to_string()
+            } else if table {
+                "┆".with(self.theme.table).to_string()
+            } else {
+                "│".to_string()
+            };
+            let right = if more_right { ">".bold().to_string() } else { "│".to_string() };
+            let (body, width) = if rtl {
+                let visual = direction::visual(line);
+                let width = visual.chars().count();
+                (base.apply(format!("{}{}", " ".repeat(content_width.saturating_sub(width)), visual)).to_string(), width.max(content_width))
+            } else {
+                (styled_line(line, &spans, base), line.chars().count())
+            };
+            let padding = content_width.saturating_sub(width);
+            let gutter = if gutter_width > 0 { self.gutter(rows.len(), cursor_row) } else { String::new() };
+            rows.push(format!("{} {}{}{} {}", border, gutter, body, base.apply(" ".repeat(padding)), right));
+        }
+        
+        let displayed = if empty_message.is_some() { 0 } else { rows.len() };
+        rows.resize(content_height, format!("│ {:<width$} │", "", width = frame_width));
+        (rows, displayed)
+    }
+
+    fn minimap_rows(&self, height: usize) -> Vec<StyledContent<String>> {
+        if !self.minimap || height == 0 {
+            return Vec::new();
+        }
+        let densest = self.pages.iter().map(|page| page.chars).max().unwrap_or(0).max(1);
+        let selected = self.minimap_selection();
+        let first = selected.saturating_sub(height / 2).min(self.pages.len().saturating_sub(height));
+        self.pages.iter()
+            .enumerate()
+            .skip(first)
+            .take(height)
+            .map(|(index, page)| {
+                let filled = (page.chars * 10).div_ceil(densest);
+                let marker = if index == self.current_page { '▶' } else { ' ' };
+                let row = format!(" {}{:>4} {}{}", marker, index + 1, "█".repeat(filled), "░".repeat(10 - filled));
+                if index == selected {
+                    row.reverse()
+                } else if index == self.current_page {
+                    row.bold()
+                } else {
+                    row.dark_grey()
+                }
+            })
+            .collect()
+    }
+
+    fn toggle_minimap(&mut self) {
+        self.minimap = !self.minimap;
+        self.reflow();
+        self.minimap_row = None;
+    }
+
+    fn minimap_selection(&self) -> usize {
+        self.minimap_row.unwrap_or(self.current_page).min(self.total_pages.saturating_sub(1))
+    }
+
+    fn reflow(&mut self) {
+        let diff_mode = self.diff_mode;
+        if diff_mode {
+            self.toggle_diff();
+        }
+        let offset = self.pages.get(self.current_page).map_or(0, |page| page.offset);
+        let top = self.continuous
+            .then(|| self.line_ranges(self.current_page).get(self.scroll_row()).map(|(start, _)| *start))
+            .flatten();
+        let cursor = self.prefs.cursor_line
+            .then(|| self.line_ranges(self.current_page).get(self.cursor_row).map(|(start, _)| *start))
+            .flatten();
+        self.split = SplitOptions {
+            anchor: offset,
+            min_chars: self.blank_threshold(),
+            squeeze: self.prefs.squeeze_whitespace,
+            tables: self.prefs.preserve_tables,
+            direction: self.prefs.detect_direction,
+            code: self.prefs.highlight_code,
+            footnotes: self.prefs.move_footnotes,
+            hyphenate: hyphenate::language(&self.prefs.hyphenate),
+            breaks: breaks::policy(&self.prefs.line_break),
+            blank_marker: self.prefs.blank_pages == "marker",
+        };
+        self.wrap_cache.borrow_mut().clear();
+        self.pages = if self.raw_mode {
+            Self::split_raw(&self.full_text, self.wrap_width(), self.page_height(), offset)
+        } else {
+            Self::split_into_pages_with(&self.full_text, self.wrap_width(), self.page_height(), self.split)
+        };
+        self.total_pages = self.pages.len().max(1);
+        self.current_page = self.page_for_offset(offset).0;
+        self.index_pages();
+        if let Some(cursor) = cursor {
+            (self.current_page, self.cursor_row) = self.page_for_offset(cursor);
+        }
+        if let Some(top) = top {
+            self.scroll = self.page_for_offset(top);
+            self.current_page = self.scroll.0;
+        }
+        self.selected_link = None;
+        self.current_match = None;
+        self.other_pages = None;
+        self.other_page = 0;
+        if diff_mode {
+            self.toggle_diff();
+        }
+    }
+
+    fn next_page(&mut self) -> bool {
+        self.minimap_row = None;
+        let step = self.cols as usize;
+        if self.current_page + step < self.total_pages {
+            self.current_page += step;
+        } else if self.prefs.page_boundary == "wrap" {
+            self.current_page = 0;
+            self.flash("↻ Vuelta al principio");
+        } else {
+            self.hit_boundary("Ya estás en la última página");
+            return false;
+        }
+        true
+    }
+
+    fn prev_page(&mut self) -> bool {
+        self.minimap_row = None;
+        if self.current_page > 0 {
+            self.current_page = self.current_page.saturating_sub(self.cols as usize);
+        } else if self.prefs.page_boundary == "wrap" {
+            self.current_page = self.total_pages.saturating_sub(1);
+            self.flash("↺ Vuelta al final");
+        } else {
+            self.hit_boundary("Ya estás en la primera página");
+            return false;
+        }
+        true
+    }
+
+    fn hit_boundary(&mut self, message: &str) {
+        self.at_boundary = true;
+        self.step_failed = true;
+        self.flash(message);
+    }
+
+    fn flash(&mut self, message: &str) {
+        self.transient = Some((message.to_string(), Instant::now(), true));
+    }
+
+    fn notify(&mut self, message: &str) {
+        self.transient = Some((message.to_string(), Instant::now(), false));
+    }
+
+    fn expire_transient(&mut self) -> bool {
+        if self.transient.as_ref().is_none_or(|(_, at, _)| at.elapsed() < TRANSIENT) {
+            return false;
+        }
+        self.transient = None;
+        true
+    }
+
+    fn ring_bell(&mut self, page_before: usize) -> Result<(), Box<dyn std::error::Error>> {
+        let boundary = std::mem::take(&mut self.at_boundary);
+        let ring = match self.prefs.bell.as_str() {
+            "turn" => boundary || self.current_page != page_before,
+            "boundary" => boundary,
+            _ => false,
+        };
+        if ring && !self.prefs.quiet {
+            print!("\x07");
+            stdout().flush()?;
+        }
+        Ok(())
+    }
+
+    fn page_line_count(&self) -> usize {
+        self.page_text(self.current_page).split('\n').count()
+    }
+
+    fn toggle_ruler(&mut self) {
+        self.prefs.ruler = !self.prefs.ruler;
+        if self.prefs.ruler {
+            self.cursor_line_before_ruler = Some(self.prefs.cursor_line);
+            self.prefs.cursor_line = true;
+            self.restore_ruler_row();
+        } else {
+            self.prefs.cursor_line = self.cursor_line_before_ruler.take().unwrap_or(false);
+        }
+        self.persist("ruler", self.prefs.ruler);
+    }
+
+    fn restore_ruler_row(&mut self) {
+        if let Some(row) = self.pages.get(self.current_page).and_then(|page| self.state.ruler_rows.get(&page.offset)) {
+            self.cursor_row = *row;
+        }
+    }
+
+    fn sync_ruler(&mut self, page_before: usize, row_before: usize) {
+        if !self.prefs.ruler || !self.prefs.cursor_line {
+            return;
+        }
+        if let Some(page) = self.pages.get(page_before) {
+            self.state.ruler_rows.insert(page.offset, row_before);
+        }
+        if page_before != self.current_page && self.cursor_row == row_before {
+            self.restore_ruler_row();
+        }
+        if let Some(page) = self.pages.get(self.current_page) {
+            self.state.ruler_rows.insert(page.offset, self.cursor_row);
+        }
+    }
+
+    fn toggle_cursor_line(&mut self) {
+        self.prefs.cursor_line = !self.prefs.cursor_line;
+        self.persist("cursor_line", self.prefs.cursor_line);
+    }
+
+    fn persist(&mut self, key: &str, value: impl Into<toml_edit::Value>) {
+        let setting = if key == "cursor_line" { "cursorline" } else { key };
+        if self.sidecar_settings.iter().any(|(sidecar, _)| sidecar.name == setting) {
+            let name = display_name(&sidecar::path(&self.state.path).to_string_lossy());
+            self.status = Some(format!("{} viene de {}: el cambio solo vale para esta sesión", setting, name));
+            return;
+        }
+        if let Err(e) = Prefs::persist(key, value) {
+            self.status = Some(e);
+        }
+    }
+
+    fn cursor_down(&mut self) {
+        let lines = self.page_line_count();
+        self.cursor_row = self.cursor_row.min(lines.saturating_sub(1));
+        if self.cursor_row + 1 < lines {
+            self.cursor_row += 1;
+        } else if self.next_page() {
+            self.cursor_row = 0;
+        }
+    }
+
+    fn cursor_up(&mut self) {
+        self.cursor_row = self.cursor_row.min(self.page_line_count().saturating_sub(1));
+        if self.cursor_row > 0 {
+            self.cursor_row -= 1;
+        } else if self.prev_page() {
+            self.cursor_row = self.page_line_count().saturating_sub(1);
+        }
+    }
+
+    fn scroll_horizontally(&mut self, right: bool) {
+        let step = (self.wrap_width() as usize).saturating_sub(6).max(2) / 2;
+        let widest = self.shown_pages()
+            .map(|index| self.page_text(index).split('\n').map(|line| line.chars().count()).max().unwrap_or(0))
+            .max()
+            .unwrap_or(0);
+        let limit = widest.saturating_sub((self.wrap_width() as usize).saturating_sub(6));
+        let offset = if self.horizontal.0 == self.current_page { self.horizontal.1 } else { 0 };
+        let offset = if right { (offset + step).min(limit) } else { offset.saturating_sub(step) };
+        if limit == 0 {
+            self.status = Some("No hay líneas más anchas que la pantalla".to_string());
+        }
+        self.horizontal = (self.current_page, offset);
+    }
+
+    fn split_view(&mut self, vertical: bool) {
+        if self.pages.is_empty() {
+            self.status = Some("No hay texto que dividir".to_string());
+            return;
+        }
+        let offset = self.pane.map_or(self.page_range(self.current_page).0, |pane| pane.offset);
+        let first = self.pane.is_none_or(|pane| pane.first);
+        self.pane = Some(Pane { offset, vertical, first });
+        self.cols = 1;
+        self.reflow();
+    }
+
+    fn close_pane(&mut self) {
+        let Some(pane) = self.pane.take() else {
+            self.status = Some("No hay vista dividida".to_string());
+            return;
+        };
+        self.reflow();
+        let other = self.page_for_offset(pane.offset).0;
+        if other != self.current_page {
+            self.jump_list.push(self.page_range(other).0);
+            self.status = Some(format!("Vista única; Ctrl+O vuelve a la p. {} del otro panel", other + 1));
+        }
+    }
+
+    fn switch_pane(&mut self) {
+        let offset = self.page_range(self.current_page).0;
+        let Some(pane) = self.pane.as_mut() else {
+            self.status = Some("No hay vista dividida (:split o :vsplit)".to_string());
+            return;
+        };
+        let other = std::mem::replace(&mut pane.offset, offset);
+        pane.first = !pane.first;
+        self.current_page = self.page_for_offset(other).0;
+        self.selected_link = None;
+        self.selection = None;
+        self.minimap_row = None;
+    }
+
+    fn jump_to(&mut self, page: usize) {
+        self.minimap_row = None;
+        if page != self.current_page {
+            self.jump_list.push(self.page_range(self.current_page).0);
+        }
+        self.current_page = page.min(self.total_pages.saturating_sub(1));
+    }
+
+    fn open_at(&mut self, page: Option<usize>, query: Option<String>) {
+        if let Some(page) = page {
+            let pdf_pages = self.doc.get_pages().len().max(self.pages.last().map_or(0, |page| page.pdf_page + 1));
+            if page == 0 || page > pdf_pages {
+                self.status = Some(format!("La página {} no existe; el PDF tiene {}", page, pdf_pages));
+            }
+            let target = page.clamp(1, pdf_pages.max(1)) - 1;
+            self.current_page = self.pages.iter()
+                .position(|page| page.pdf_page >= target)
+                .unwrap_or(self.total_pages.saturating_sub(1));
+        }
+        if let Some(query) = query.filter(|query| !query.trim().is_empty()) {
+            self.set_search(query, true);
+            self.current_match = None;
+            self.repeat_search(false);
+        }
+    }
+
+    fn show_file_info(&mut self) {
+        let read = self.char_prefix.get(self.current_page + 1).copied().unwrap_or(0);
+        let total = self.char_prefix.last().copied().unwrap_or(0).max(1);
+        let pdf_page = self.pages.get(self.current_page).map_or(0, |page| page.pdf_page) + 1;
+        self.status = Some(format!(
+            "{} · página {}/{} · PDF p. {} · {}%",
+            self.pdf_name,
+            self.current_page + 1,
+            self.total_pages,
+            pdf_page,
+            read.saturating_mul(100) / total
+        ));
+    }
+
+    fn goto_percent(&mut self, percent: usize) {
+        let percent = percent.min(100);
+        let page = match percent {
+            0 => 0,
+            100 => self.total_pages.saturating_sub(1),
+            _ => {
+                let chars = self.full_text.chars().count();
+                let offset = self.full_text.char_indices().nth(chars * percent / 100).map_or(self.full_text.len(), |(offset, _)| offset);
+                self.page_for_offset(offset).0
+            }
+        };
+        self.jump_to(page);
+        self.status = Some(format!("{}% · página {}/{}", percent, self.current_page + 1, self.total_pages));
+    }
+
+    fn click(&mut self, row: u16, column: u16) -> Result<(), Box<dyn std::error::Error>> {
+        if self.layout.header.contains(&row) && !self.pages.is_empty() {
+            self.show_outline()?;
+        } else if row == self.layout.controls {
+            self.show_help()?;
+        } else if let Some(line) = row.checked_sub(self.layout.content_top).map(usize::from) {
+            if line < self.layout.content_lines && column < self.layout.tile_width && !self.continuous {
+                if !self.prefs.cursor_line {
+                    self.toggle_cursor_line();
+                }
+                self.cursor_row = line;
+            }
+        }
+        Ok(())
+    }
+
+    fn status_widgets(&self) -> String {
+        let mut widgets = Vec::new();
+        if self.prefs.clock {
+            widgets.push(format!("🕒 {}", clock::local_time()));
+        }
+        if self.prefs.reading_timer {
+            widgets.push(format!("{} en sesión", clock::duration(self.reading)));
+        }
+        #[cfg(feature = "battery")]
+        if self.prefs.battery {
+            widgets.extend(battery::status());
+        }
+        widgets.join(" · ")
+    }
+
+    fn track_reading(&mut self) {
+        let now = Instant::now();
+        if self.last_input.elapsed() < Duration::from_secs(self.prefs.timer_idle_secs) {
+            self.reading += now - self.reading_tick;
+        }
+        self.reading_tick = now;
+    }
+
+    fn refresh_widgets(&mut self) -> bool {
+        if self.widgets_at.elapsed() < WIDGET_REFRESH {
+            return false;
+        }
+        self.widgets_at = Instant::now();
+        let widgets = self.status_widgets();
+        let clock = if statusline::uses(&self.prefs.statusline, "clock") { clock::local_time() } else { String::new() };
+        if widgets == self.widgets && clock == self.clock_shown {
+            return false;
+        }
+        self.widgets = widgets;
+        self.clock_shown = clock;
+        true
+    }
+
+    fn dim_when_idle(&mut self) -> bool {
+        if self.dimmed || self.idle_dim.is_none_or(|timeout| self.last_input.elapsed() < timeout) {
+            return false;
+        }
+        self.dimmed = true;
+        true
+    }
+
+    fn track_dwell(&mut self) {
+        let Some(offset) = self.pages.get(self.current_page).map(|page| page.offset) else {
+            return;
+        };
+        let (dwelled, since) = self.dwell;
+        if self.page_for_offset(dwelled).0 == self.current_page {
+            return;
+        }
+        if since.elapsed() >= DWELL {
+            self.recent.retain(|&recent| recent != dwelled);
+            self.recent.push(dwelled);
+            if self.recent.len() > RECENT_PAGES {
+                self.recent.remove(0);
+            }
+        }
+        self.dwell = (offset, Instant::now());
+    }
+
+    fn toggle_recent(&mut self) {
+        self.track_dwell();
+        let target = self.recent.iter().rev()
+            .map(|&offset| self.page_for_offset(offset).0)
+            .find(|&page| page != self.current_page);
+        let Some(page) = target else {
+            self.status = Some("Aún no hay otra página leída (quédate más de un segundo en una)".to_string());
+            return;
+        };
+        self.minimap_row = None;
+        self.current_page = page;
+        let first_line = self.page_text(page).lines().map(str::trim).find(|line| !line.is_empty()).unwrap_or("").to_string();
+        let width = self.text_width() as usize;
+        let label = format!("Página {}: {}", page + 1, first_line);
+        self.status = Some(label.chars().take(width.saturating_sub(4)).collect());
+    }
+
+    fn save_position(&mut self) {
+        if self.pages.is_empty() || self.diff_mode {
+            return;
+        }
+        self.state.position = Some(self.page_range(self.current_page).0);
+        let _ = self.state.save();
+    }
+
+    fn switch_file(&mut self, forward: bool) {
+        let wrap = self.prefs.file_boundary == "wrap";
+        let Some(path) = folder::neighbour(Path::new(&self.state.path), forward, wrap) else {
+            self.hit_boundary(if forward { "No hay más PDFs en la carpeta" } else { "Es el primer PDF de la carpeta" });
+            return;
+        };
+        let path = path.to_string_lossy().into_owned();
+        let mut next = match PdfViewer::new(&path, &self.extractor, &self.encoding, &self.columns) {
+            Ok(next) => next,
+            Err(e) => {
+                self.status = Some(format!("No se pudo abrir {}: {}", display_name(&path), e));
+                return;
+            }
+        };
+        self.save_position();
+        self.hand_over(&mut next);
+        next.reflow();
+        next.status = Some(format!("📄 {}", next.pdf_name));
+        next.apply_sidecar();
+        if let Some(offset) = next.state.position {
+            next.current_page = next.page_for_offset(offset).0;
+        }
+        *self = next;
+        self.load_complete();
+    }
+
+    fn hand_over(&mut self, next: &mut PdfViewer) {
+        next.prefs = self.prefs.clone();
+        next.keymap = self.keymap;
+        next.keep_output = self.keep_output;
+        next.idle_dim = self.idle_dim;
+        next.cols = self.cols;
+        next.minimap = self.minimap;
+        next.reading = self.reading;
+        next.pipe_history = std::mem::take(&mut self.pipe_history);
+        next.hooks = std::mem::replace(&mut self.hooks, Box::new(NoHooks));
+        next.macros = std::mem::take(&mut self.macros);
+        next.recording = self.recording.take();
+        for (setting, value) in std::mem::take(&mut self.sidecar_settings) {
+            next.apply_setting(setting, value);
+        }
+        next.theme = Theme::named(&next.prefs.theme);
+    }
+
+    fn jump_back(&mut self) {
+        if let Some(offset) = self.jump_list.pop() {
+            self.current_page = self.page_for_offset(offset).0;
+        }
+    }
+
+    fn visible_links(&self) -> Vec<VisibleLink> {
+        let Some(page) = self.pages.get(self.current_page) else {
+            return Vec::new();
+        };
+        let text = self.page_text(self.current_page);
+        let mut visible: Vec<VisibleLink> = self.links.iter()
+            .filter(|link| link.pdf_page == page.pdf_page)
+            .filter_map(|link| {
+                links::find_loose(&text, &link.anchor)
+                    .map(|range| VisibleLink { ranges: vec![range], target: link.target.clone() })
+            })
+            .collect();
+
+        let content_width = (self.wrap_width() as usize).saturating_sub(6);
+        for found in urls::find_urls(&text, content_width) {
+            let overlaps = visible.iter().flat_map(|link| &link.ranges).any(|range| {
+                found.ranges.iter().any(|r| r.start < range.end && r.end > range.start)
+            });
+            if !overlaps {
+                visible.push(VisibleLink { ranges: found.ranges, target: LinkTarget::Uri(found.url) });
+            }
+        }
+        visible.sort_by_key(|link| link.ranges.first().map_or(0, |range| range.start));
+        visible
+    }
+
+    fn selected_link_index(&self) -> Option<usize> {
+        self.selected_link
+            .filter(|(page, _)| *page == self.current_page)
+            .map(|(_, index)| index)
+    }
+
+    fn cycle_link(&mut self, forward: bool) {
+        let count = self.visible_links().len();
+        if count == 0 {
+            self.selected_link = None;
+            return;
+        }
+        let next = match self.selected_link_index().filter(|&index| index < count) {
+            Some(index) if forward => (index + 1) % count,
+            Some(index) => (index + count - 1) % count,
+            None if forward => 0,
+            None => count - 1,
+        };
+        self.selected_link = Some((self.current_page, next));
+    }
+
+    fn selected_target(&self) -> Option<LinkTarget> {
+        let index = self.selected_link_index()?;
+        self.visible_links().into_iter().nth(index).map(|link| link.target)
+    }
+
+    fn activate_link(&mut self, target: LinkTarget) {
+        match target {
+            LinkTarget::Page(target) => {
+                let page = self.pages.iter()
+                    .position(|page| page.pdf_page >= target)
+                    .unwrap_or(self.total_pages.saturating_sub(1));
+                self.selected_link = None;
+                self.jump_to(page);
+            }
+            LinkTarget::Uri(url) => {
+                self.status = Some(match urls::open_url(&url) {
+                    Ok(()) => format!("Abriendo {}", url),
+                    Err(e) => format!("No se pudo abrir {}: {}", url, e),
+                });
+            }
+        }
+    }
+
+    fn copy_selected_url(&mut self) {
+        if let Some(LinkTarget::Uri(url)) = self.selected_target() {
+            self.status = Some(match clipboard::copy(&url) {
+                Ok(()) => format!("URL copiada: {}", url),
+                Err(e) => format!("No se pudo copiar la URL: {}", e),
+            });
+        }
+    }
+
+    fn selection_range(&self) -> Option<Range<usize>> {
+        let (anchor, caret) = self.selection?;
+        let text = self.page_text(self.current_page);
+        let end = anchor.max(caret);
+        let end = end + text[end..].chars().next().map_or(0, char::len_utf8);
+        Some(anchor.min(caret)..end)
+    }
+
+    fn selected_text(&self, page_text: &str) -> String {
+        let Some(range) = self.selection_range() else {
+            return String::new();
+        };
+        match self.source_range(self.current_page, range.clone()) {
+            Some(source) => self.full_text[source].to_string(),
+            None if self.diff_mode || self.raw_mode => page_text[range].to_string(),
+            None => String::new(),
+        }
+    }
+
+    fn visual_mode(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        let text = self.page_text(self.current_page).into_owned();
+        if text.trim().is_empty() {
+            self.status = Some("No hay texto que seleccionar en esta página".to_string());
+            return Ok(());
+        }
+        let line_starts: Vec<usize> = std::iter::once(0).chain(text.match_indices('\n').map(|(index, _)| index + 1)).collect();
+        let row = if self.prefs.cursor_line { self.cursor_row.min(line_starts.len() - 1) } else { 0 };
+        let start = line_starts[row];
+        let mut caret = start + text[start..].find(|c: char| !c.is_whitespace()).unwrap_or(0);
+        self.selection = Some((caret, caret));
+        loop {
+            self.status = Some("-- VISUAL -- y copia, H resalta, ESC sale".to_string());
+            self.draw_page()?;
+            let Event::Key(key_event) = event::read()? else {
+                continue;
+            };
+            if key_event.kind != KeyEventKind::Press {
+                continue;
+            }
+            let line = line_starts.partition_point(|start| *start <= caret) - 1;
+            let column = text[line_starts[line]..caret].chars().count();
+            let line_end = |line: usize| line_starts.get(line + 1).map_or(text.len(), |next| next - 1);
+            let moved_line = |target: usize| {
+                let start = line_starts[target];
+                text[start..line_end(target)].char_indices().nth(column).map_or(line_end(target), |(offset, _)| start + offset)
+            };
+            caret = match key_event.code {
+                KeyCode::Char('h') | KeyCode::Left => text[..caret].char_indices().next_back().map_or(caret, |(offset, _)| offset),
+                KeyCode::Char('l') | KeyCode::Right => {
+                    let next = caret + text[caret..].chars().next().map_or(0, char::len_utf8);
+                    if next < text.len() { next } else { caret }
+                }
+                KeyCode::Char('j') | KeyCode::Down if line + 1 < line_starts.len() => moved_line(line + 1),
+                KeyCode::Char('k') | KeyCode::Up if line > 0 => moved_line(line - 1),
+                KeyCode::Char('w') => next_word(&text, caret),
+                KeyCode::Char('b') => previous_word(&text, caret),
+                KeyCode::Char('0') | KeyCode::Home => line_starts[line],
+                KeyCode::Char('$') | KeyCode::End => text[line_starts[line]..line_end(line)]
+                    .char_indices()
+                    .next_back()
+                    .map_or(caret, |(offset, _)| line_starts[line] + offset),
+                KeyCode::Char('y') => {
+                    let copied = self.selected_text(&text);
+                    self.status = Some(match clipboard::copy(&copied) {
+                        Ok(()) => format!("{} caracteres copiados", copied.chars().count()),
+                        Err(e) => format!("No se pudo copiar: {}", e),
+                    });
+                    break;
+                }
+                KeyCode::Char('H') => {
+                    self.add_highlight();
+                    break;
+                }
+                KeyCode::Char('|') => {
+                    let selected = self.selected_text(&text);
+                    self.selection = None;
+                    self.pipe_text(&selected)?;
+                    break;
+                }
+                KeyCode::Esc | KeyCode::Char('v' | 'q') => {
+                    self.status = None;
+                    break;
+                }
+                _ => caret,
+            };
+            self.selection = self.selection.map(|(anchor, _)| (anchor, caret));
+        }
+        self.selection = None;
+        Ok(())
+    }
+
+    fn show_link_list(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        if self.links.is_empty() {
+            return Ok(());
+        }
+        let mut selected: usize = 0;
+        let visible_rows = (self.terminal_height as usize).saturating_sub(6).max(1);
+        loop {
+            execute!(stdout(), terminal::Clear(ClearType::All), cursor::MoveTo(0, 0))?;
+            println!("{}", "ENLACES - PDF Viewer".bold().green());
+            println!();
+            let first = selected.saturating_sub(visible_rows - 1);
+            for (index, link) in self.links.iter().enumerate().skip(first).take(visible_rows) {
+                let anchor = if link.anchor.trim().is_empty() { "(sin texto)" } else { link.anchor.trim() };
+                let entry = match &link.target {
+                    LinkTarget::Page(target) => format!(" pág. {:>4} → pág. {:>4}  {}", link.pdf_page + 1, target + 1, anchor),
+                    LinkTarget::Uri(url) => format!(" pág. {:>4} → {}  {}", link.pdf_page + 1, url, anchor),
+                };
+                if index == selected {
+                    println!("{}", entry.reverse());
+                } else {
+                    println!("{}", entry);
+                }
+            }
+            println!("\n {}", "↑/↓ Mover | Enter Seguir | q/ESC Volver".italic().dark_grey());
+
+            if let Event::Key(key_event) = event::read()? {
+                if key_event.kind != KeyEventKind::Press {
+                    continue;
+                }
+                match key_event.code {
+                    KeyCode::Up | KeyCode::Char('k') => selected = selected.saturating_sub(1),
+                    KeyCode::Down | KeyCode::Char('j') => selected = (selected + 1).min(self.links.len() - 1),
+                    KeyCode::Enter => {
+                        self.activate_link(self.links[selected].target.clone());
+                        break;
+                    }
+                    KeyCode::Char('q') | KeyCode::Esc => break,
+                    _ => {}
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn outline_page(&self, entry: &OutlineEntry) -> Option<usize> {
+        match entry.detected_at {
+            Some(offset) if !self.diff_mode => Some(self.page_for_offset(offset).0),
+            _ => self.pages.iter().position(|page| page.pdf_page >= entry.pdf_page),
+        }
+    }
+
+    fn chapters(&self) -> Vec<(usize, String)> {
+        let top = self.outline.iter().map(|entry| entry.level).min().unwrap_or(0);
+        let mut chapters: Vec<(usize, String)> = if self.outline.is_empty() || self.diff_mode {
+            self.pages.iter()
+                .enumerate()
+                .filter(|(index, page)| *index == 0 || self.pages[index - 1].pdf_page != page.pdf_page)
+                .map(|(index, page)| (index, format!("Página {} del PDF", page.pdf_page + 1)))
+                .collect()
+        } else {
+            self.outline.iter()
+                .filter(|entry| entry.level == top)
+                .filter_map(|entry| Some((self.outline_page(entry)?, entry.title.clone())))
+                .collect()
+        };
+        chapters.dedup_by_key(|(page, _)| *page);
+        chapters
+    }
+
+    fn jump_to_chapter(&mut self, forward: bool) {
+        let chapters = self.chapters();
+        let target = if forward {
+            chapters.into_iter().find(|(page, _)| *page > self.current_page)
+        } else {
+            chapters.into_iter().rev().find(|(page, _)| *page < self.current_page)
+        };
+        match target {
+            Some((page, title)) => {
+                self.jump_to(page);
+                self.status = Some(format!("§ {}", title));
+            }
+            None => {
+                self.hit_boundary(if forward { "No hay más capítulos" } else { "Ya estás en el primer capítulo" });
+            }
+        }
+    }
+
+    fn open_outline_entry(&mut self, entry: &OutlineEntry) {
+        match self.outline_page(entry).filter(|_| entry.detected_at.is_some()) {
+            Some(page) => {
+                self.selected_link = None;
+                self.jump_to(page);
+            }
+            None => self.activate_link(LinkTarget::Page(entry.pdf_page)),
+        }
+    }
+
+    fn jump_to_heading(&mut self, forward: bool) {
+        let pages: Vec<(usize, &str)> = self.outline.iter()
+            .filter_map(|entry| Some((self.outline_page(entry)?, entry.title.as_str())))
+            .collect();
+        let target = if forward {
+            pages.iter().find(|(page, _)| *page > self.current_page)
+        } else {
+            pages.iter().rev().find(|(page, _)| *page < self.current_page)
+        };
+        match target.map(|(page, title)| (*page, title.to_string())) {
+            Some((page, title)) => {
+                self.jump_to(page);
+                self.status = Some(format!("§ {}", title));
+            }
+            None if self.outline.is_empty() => self.status = Some("El documento no tiene índice ni títulos reconocibles".to_string()),
+            None => {
+                self.hit_boundary(if forward { "No hay más títulos" } else { "No hay títulos antes" });
+            }
+        }
+    }
+
+    fn show_outline(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        let outline = self.outline.clone();
+        if outline.is_empty() {
+            self.status = Some("El documento no tiene índice".to_string());
+            return Ok(());
+        }
+        let pages: Vec<Option<usize>> = outline.iter().map(|entry| self.outline_page(entry)).collect();
+        let detected = outline.iter().any(|entry| entry.detected_at.is_some());
+        let current_entry = pages.iter().rposition(|page| page.is_some_and(|page| page <= self.current_page)).unwrap_or(0);
+        let mut query = String::new();
+        let mut selected = current_entry;
+        let visible_rows = (self.terminal_height as usize).saturating_sub(7).max(1);
+        let match_style = ContentStyle::new().with(self.theme.search_match_fg).on(self.theme.search_match_bg);
+        loop {
+            let mut matches: Vec<(usize, Vec<usize>, i64)> = outline.iter()
+                .enumerate()
+                .filter_map(|(index, entry)| {
+                    let found = fuzzy::fuzzy_match(&query, &entry.title)?;
+                    Some((index, found.positions, found.score))
+                })
+                .collect();
+            if !query.is_empty() {
+                matches.sort_by_key(|(index, _, score)| (std::cmp::Reverse(*score), *index));
+            }
+            selected = selected.min(matches.len().saturating_sub(1));
+
+            execute!(stdout(), terminal::Clear(ClearType::All), cursor::MoveTo(0, 0))?;
+            if detected {
+                println!("{} {}", "ÍNDICE - PDF Viewer".bold().green(), "(≈ títulos detectados en el texto, no del PDF)".dark_grey());
+            } else {
+                println!("{}", "ÍNDICE - PDF Viewer".bold().green());
+            }
+            println!("{} {}", "🔎".bold(), query);
+            println!();
+            let first = selected.saturating_sub(visible_rows - 1);
+            for (row, (index, positions, _)) in matches.iter().enumerate().skip(first).take(visible_rows) {
+                let entry = &outline[*index];
+                let title = &entry.title;
+                let spans: Vec<(Range<usize>, ContentStyle)> = title.char_indices()
+                    .enumerate()
+                    .filter(|(n, _)| positions.contains(n))
+                    .map(|(_, (at, c))| (at..at + c.len_utf8(), match_style))
+                    .collect();
+                let base = if row == selected { ContentStyle::new().reverse() } else { ContentStyle::new() };
+                let marker = if entry.detected_at.is_some() { "≈" } else { " " };
+                let prefix = format!(" pág. {:>4} {}{}", entry.pdf_page + 1, marker, "  ".repeat(entry.level));
+                println!("{}{}", base.apply(prefix), styled_line(title, &spans, base));
+            }
+            if matches.is_empty() {
+                println!(" {}", "Sin coincidencias".dark_grey());
+            }
+            println!("\n {}", "Escribe para filtrar | ↑/↓ Mover | Enter/clic Ir | ESC Volver".italic().dark_grey());
+
+            match event::read()? {
+                Event::Key(key_event) if key_event.kind == KeyEventKind::Press => match key_event.code {
+                    KeyCode::Up => selected = selected.saturating_sub(1),
+                    KeyCode::Down => selected += 1,
+                    KeyCode::Enter => {
+                        if let Some((index, _, _)) = matches.get(selected) {
+                            self.open_outline_entry(&outline[*index]);
+                            break;
+                        }
+                    }
+                    KeyCode::Backspace => {
+                        query.pop();
+                        selected = if query.is_empty() { current_entry } else { 0 };
+                    }
+                    KeyCode::Char(c) => {
+                        query.push(c);
+                        selected = 0;
+                    }
+                    KeyCode::Esc => break,
+                    _ => {}
+                },
+                Event::Mouse(MouseEvent { kind: MouseEventKind::Down(MouseButton::Left), row, .. }) => {
+                    let clicked = (row as usize).checked_sub(3).map(|offset| first + offset);
+                    if let Some((index, _, _)) = clicked.filter(|row| *row < first + visible_rows).and_then(|row| matches.get(row)) {
+                        self.open_outline_entry(&outline[*index]);
+                        break;
+                    }
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn palette_entries(&self) -> Vec<(Action, String, String)> {
+        action::ALL.iter()
+            .filter(|action| !matches!(action, Action::ShowPalette | Action::SetMark(_) | Action::JumpToMark(_) | Action::RepeatLast))
+            .map(|action| (*action, action.keys(self.keymap).join(" / "), format!("{} ({})", action.describe().1, action.name())))
+            .collect()
+    }
+
+    fn palette_matches(entries: &[(Action, String, String)], query: &str) -> Vec<(usize, Vec<usize>, i64)> {
+        let mut matches: Vec<(usize, Vec<usize>, i64)> = entries.iter()
+            .enumerate()
+            .filter_map(|(index, (_, _, text))| {
+                let found = fuzzy::fuzzy_match(query, text)?;
+                Some((index, found.positions, found.score))
+            })
+            .collect();
+        if !query.is_empty() {
+            matches.sort_by_key(|(index, _, score)| (std::cmp::Reverse(*score), *index));
+        }
+        matches
+    }
+
+    fn show_palette(&mut self) -> Result<Option<Action>, Box<dyn std::error::Error>> {
+        let entries = self.palette_entries();
+        let mut query = String::new();
+        let mut selected = 0;
+        let visible_rows = (self.terminal_height as usize).saturating_sub(7).max(1);
+        let match_style = ContentStyle::new().with(self.theme.search_match_fg).on(self.theme.search_match_bg);
+        loop {
+            let matches = Self::palette_matches(&entries, &query);
+            selected = selected.min(matches.len().saturating_sub(1));
+
+            execute!(stdout(), terminal::Clear(ClearType::All), cursor::MoveTo(0, 0))?;
+            println!("{}", "PALETA DE ACCIONES - PDF Viewer".bold().green());
+            println!("{} {}", "🔎".bold(), query);
+            println!();
+            let first = selected.saturating_sub(visible_rows - 1);
+            for (row, (index, positions, _)) in matches.iter().enumerate().skip(first).take(visible_rows) {
+                let (_, keys, text) = &entries[*index];
+                let spans: Vec<(Range<usize>, ContentStyle)> = text.char_indices()
+                    .enumerate()
+                    .filter(|(n, _)| positions.contains(n))
+                    .map(|(_, (at, c))| (at..at + c.len_utf8(), match_style))
+                    .collect();
+                let base = if row == selected { ContentStyle::new().reverse() } else { ContentStyle::new() };
+                let keys = if keys.is_empty() { "(sin asignar)" } else { keys.as_str() };
+                println!("{}{}", base.apply(format!(" {:<16} ", keys)), styled_line(text, &spans, base));
+            }
+            if matches.is_empty() {
+                println!(" {}", "Sin coincidencias".dark_grey());
+            }
+            println!("\n {}", "Escribe para filtrar | ↑/↓ Mover | Enter/clic Ejecutar | ESC Volver".italic().dark_grey());
+
+            match event::read()? {
+                Event::Key(key_event) if key_event.kind == KeyEventKind::Press => match key_event.code {
+                    KeyCode::Up => selected = selected.saturating_sub(1),
+                    KeyCode::Down => selected += 1,
+                    KeyCode::Enter => {
+                        if let Some((index, _, _)) = matches.get(selected) {
+                            return Ok(Some(entries[*index].0));
+                        }
+                    }
+                    KeyCode::Backspace => {
+                        query.pop();
+                        selected = 0;
+                    }
+                    KeyCode::Char(c) => {
+                        query.push(c);
+                        selected = 0;
+                    }
+                    KeyCode::Esc => return Ok(None),
+                    _ => {}
+                },
+                Event::Mouse(MouseEvent { kind: MouseEventKind::Down(MouseButton::Left), row, .. }) => {
+                    let clicked = (row as usize).checked_sub(3).map(|offset| first + offset);
+                    if let Some((index, _, _)) = clicked.filter(|row| *row < first + visible_rows).and_then(|row| matches.get(row)) {
+                        return Ok(Some(entries[*index].0));
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn page_range(&self, index: usize) -> (usize, usize) {
+        let start = self.pages.get(index).map_or(0, |page| page.offset);
+        let end = self.pages.get(index + 1).map_or(usize::MAX, |page| page.offset);
+        (start, end)
+    }
+
+    fn page_for_offset(&self, offset: usize) -> (usize, usize) {
+        let page = self.pages.partition_point(|page| page.offset <= offset).saturating_sub(1);
+        let row = self.line_ranges(page).iter().rposition(|(start, end)| *start <= offset && end > start).unwrap_or(0);
+        (page, row)
+    }
+
+    fn active_match(&self, index: usize, page_content: &str, matches: &[Range<usize>]) -> Option<Range<usize>> {
+        let offset = self.current_match?;
+        let (page, row) = self.page_for_offset(offset);
+        if page != index {
+            return None;
+        }
+        let (line_start, _) = *self.line_ranges(index).get(row)?;
+        let row_start: usize = page_content.split('\n').take(row).map(|line| line.len() + 1).sum();
+        let column = offset.saturating_sub(line_start);
+        let row_end = row_start + page_content.split('\n').nth(row)?.len();
+        matches.iter()
+            .filter(|m| m.start >= row_start && m.start < row_end)
+            .min_by_key(|m| (m.start - row_start).abs_diff(column))
+            .cloned()
+    }
+
+    fn reveal_match(&mut self, offset: usize) {
+        let (page, row) = self.page_for_offset(offset);
+        let column = self.line_ranges(page).get(row).map_or(0, |(start, _)| self.full_text[*start..offset.max(*start)].chars().count());
+        let width = (self.wrap_width() as usize).saturating_sub(6).max(2);
+        let shift = if self.horizontal.0 == page { self.horizontal.1 } else { 0 };
+        if column < shift || column >= shift + width {
+            self.horizontal = (page, column.saturating_sub(width / 2));
+        }
+        if self.prefs.cursor_line {
+            self.cursor_row = row;
+        }
+    }
+
+    /// Cambia los eventos que recibe quien integra el visor; por defecto no hay ninguno.
+    pub fn set_hooks(&mut self, hooks: Box<dyn Hooks>) {
+        self.hooks = hooks;
+    }
+
+    fn load_complete(&mut self) {
+        self.hooks.on_load_complete(self.total_pages, self.char_prefix.last().copied().unwrap_or(0));
+    }
+
+    fn page_changed(&mut self) {
+        let pdf_page = self.pages.get(self.current_page).map_or(0, |page| page.pdf_page);
+        self.hooks.on_page_change(self.current_page, pdf_page, self.total_pages);
+    }
+
+    fn sync_match(&mut self) {
+        let (start, end) = self.page_range(self.current_page);
+        if self.current_match.is_some_and(|offset| !(start..end).contains(&offset)) {
+            self.current_match = None;
+        }
+    }
+
+    fn goto_offset(&mut self, offset: usize) {
+        let (page, row) = self.page_for_offset(offset);
+        self.jump_to(page);
+        self.cursor_row = row;
+        self.status = Some(format!("Byte {}: página {}, línea {}", offset, page + 1, row + 1));
+    }
+
+    fn line_ranges(&self, index: usize) -> Vec<(usize, usize)> {
+        let Some(page) = self.pages.get(index) else {
+            return Vec::new();
+        };
+        if self.diff_mode || self.raw_mode || page.synthetic {
+            return Vec::new();
+        }
+        let mut cursor = page.offset.min(self.full_text.len());
+        self.page_text(index).split('\n')
+            .map(|line| {
+                let start = advance_past(&self.full_text, &mut cursor, line);
+                (start, cursor)
+            })
+            .collect()
+    }
+
+    fn word_offsets(&self, index: usize) -> Vec<(Range<usize>, usize)> {
+        let Some(page) = self.pages.get(index) else {
+            return Vec::new();
+        };
+        if self.diff_mode || self.raw_mode || page.synthetic {
+            return Vec::new();
+        }
+        let text = self.page_text(index);
+        let mut cursor = page.offset.min(self.full_text.len());
+        let mut words = Vec::new();
+        let mut word_start = None;
+        for (offset, c) in text.char_indices().chain(std::iter::once((text.len(), ' '))) {
+            match (c.is_whitespace(), word_start) {
+                (true, Some(start)) => {
+                    word_start = None;
+                    let word = &text[start..offset];
+                    let word = word.strip_suffix('-').filter(|stem| !stem.is_empty()).unwrap_or(word);
+                    if let Some(position) = self.full_text[cursor..].find(word) {
+                        words.push((start..start + word.len(), cursor + position));
+                        cursor += position + word.len();
+                    }
+                }
+                (false, None) => word_start = Some(offset),
+                _ => {}
+            }
+        }
+        words
+    }
+
+    fn source_range(&self, index: usize, range: Range<usize>) -> Option<Range<usize>> {
+        let words = self.word_offsets(index);
+        let first = words.iter().find(|(word, _)| word.end > range.start)?;
+        let last = words.iter().rev().find(|(word, _)| word.start < range.end)?;
+        let start = first.1 + range.start.saturating_sub(first.0.start);
+        let end = last.1 + range.end.min(last.0.end).saturating_sub(last.0.start);
+        (start < end).then_some(start..end)
+    }
+
+    fn cursor_line_range(&mut self) -> Option<(usize, usize)> {
+        if !self.prefs.cursor_line {
+            self.status = Some("Activa la línea de cursor (c) para elegir qué resaltar".to_string());
+            return None;
+        }
+        if self.pages.get(self.current_page).is_some_and(|page| page.synthetic) {
+            self.status = Some("Esta página solo resume páginas en blanco: no hay texto que resaltar".to_string());
+            return None;
+        }
+        let ranges = self.line_ranges(self.current_page);
+        let row = self.cursor_row.min(ranges.len().saturating_sub(1));
+        let range = ranges.get(row).copied().filter(|(start, end)| end > start);
+        if range.is_none() {
+            self.status = Some("La línea del cursor está vacía".to_string());
+        }
+        range
+    }
+
+    fn add_highlight(&mut self) {
+        let (start, end, what) = match self.selection_range() {
+            Some(range) => match self.source_range(self.current_page, range) {
+                Some(source) => (source.start, source.end, "Selección resaltada"),
+                None => {
+                    self.status = Some("La selección no tiene texto del documento que resaltar".to_string());
+                    return;
+                }
+            },
+            None => match self.cursor_line_range() {
+                Some((start, end)) => (start, end, "Línea resaltada"),
+                None => return,
+            },
+        };
+        let pdf_page = self.pages.get(self.current_page).map_or(0, |page| page.pdf_page) + 1;
+        let highlight = Highlight { start, end, pdf_page, color: HIGHLIGHT_COLORS[0].to_string() };
+        self.status = Some(match self.notes.add_highlight(highlight) {
+            Ok(()) => format!("{} (C cambia el color, U lo quita)", what),
+            Err(e) => format!("No se pudo guardar el resaltado: {}", e),
+        });
+    }
+
+    fn cycle_highlight(&mut self) {
+        let Some((start, end)) = self.cursor_line_range() else {
+            return;
+        };
+        match self.notes.cycle_highlights(start, end) {
+            Ok(0) => self.status = Some("No hay resaltado en esta línea".to_string()),
+            Ok(_) => {}
+            Err(e) => self.status = Some(format!("No se pudo guardar el resaltado: {}", e)),
+        }
+    }
+
+    fn remove_highlight(&mut self) {
+        let Some((start, end)) = self.cursor_line_range() else {
+            return;
+        };
+        match self.notes.remove_highlights(start, end) {
+            Ok(0) => self.status = Some("No hay resaltado en esta línea".to_string()),
+            Ok(_) => self.status = Some("Resaltado eliminado".to_string()),
+            Err(e) => self.status = Some(format!("No se pudo guardar el resaltado: {}", e)),
+        }
+    }
+
+    fn set_mark(&mut self, slot: u8) {
+        let offset = self.pages.get(self.current_page).map_or(0, |page| page.offset);
+        let previous = self.state.quickmarks.insert(slot, offset);
+        let saved = self.state.save();
+        self.status = Some(match (saved, previous) {
+            (Err(e), _) => format!("No se pudo guardar la marca: {}", e),
+            (Ok(()), Some(old)) if old != offset => {
+                format!("Marca {} movida (antes en pág. {})", slot, self.page_for_offset(old).0 + 1)
+            }
+            (Ok(()), _) => format!("Marca {} guardada", slot),
+        });
+    }
+
+    fn jump_to_mark(&mut self, slot: u8) {
+        match self.state.quickmarks.get(&slot) {
+            Some(&offset) => self.jump_to(self.page_for_offset(offset).0),
+            None => self.status = Some(format!("La marca {} no está definida (M{} para crearla)", slot, slot)),
+        }
+    }
+
+    fn add_note(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        let Some(page) = self.pages.get(self.current_page) else {
+            return Ok(());
+        };
+        let (offset, pdf_page) = (page.offset, page.pdf_page + 1);
+        let Some(text) = prompt::read_line("Nota:", "")? else {
+            return Ok(());
+        };
+        if text.trim().is_empty() {
+            return Ok(());
+        }
+        let note = Note { offset, pdf_page, text: text.trim().to_string() };
+        self.status = Some(match self.notes.add(note) {
+            Ok(()) => format!("Nota guardada en {}", self.notes.path().display()),
+            Err(e) => format!("No se pudo guardar la nota: {}", e),
+        });
+        Ok(())
+    }
+
+    fn run_external(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        let template = self.prefs.external_command.trim().to_string();
+        if template.is_empty() {
+            self.status = Some("No hay orden externa: define external_command en config.toml".to_string());
+            return Ok(());
+        }
+        let text = self.source_text(self.current_page).into_owned();
+        terminal::disable_raw_mode()?;
+        execute!(stdout(), DisableBracketedPaste, DisableMouseCapture, terminal::LeaveAlternateScreen)?;
+        let result = external::run(&template, &text, self.prefs.external_output);
+        execute!(stdout(), terminal::EnterAlternateScreen, EnableMouseCapture, EnableBracketedPaste)?;
+        terminal::enable_raw_mode()?;
+        match result {
+            Err(e) => self.status = Some(e),
+            Ok(output) if self.prefs.external_output || !output.status.success() => self.show_output(&template, &output)?,
+            Ok(_) => self.status = Some(format!("\"{}\" terminó", template)),
+        }
+        Ok(())
+    }
+
+    fn pipe_text(&mut self, text: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let Some(command) = prompt::read_line_with("|", "", &self.pipe_history, |_| None)? else {
+            return Ok(());
+        };
+        let command = command.trim().to_string();
+        if command.is_empty() {
+            return Ok(());
+        }
+        self.pipe_history.retain(|previous| *previous != command);
+        self.pipe_history.push(command.clone());
+        terminal::disable_raw_mode()?;
+        execute!(stdout(), DisableBracketedPaste, DisableMouseCapture, terminal::LeaveAlternateScreen)?;
+        let result = external::pipe(&command, text);
+        execute!(stdout(), terminal::EnterAlternateScreen, EnableMouseCapture, EnableBracketedPaste)?;
+        terminal::enable_raw_mode()?;
+        match result {
+            Err(e) => self.status = Some(e),
+            Ok(output) if output.stdout.trim().is_empty() && output.stderr.trim().is_empty() && output.status.success() => {
+                self.status = Some(format!("\"{}\" terminó sin salida", command));
+            }
+            Ok(output) => self.show_output(&command, &output)?,
+        }
+        Ok(())
+    }
+
+    fn screenshot_name(&self) -> String {
+        let stem = Path::new(&self.pdf_name).file_stem().unwrap_or_default().to_string_lossy().into_owned();
+        format!("{}-p{}-{}.txt", stem, self.current_page + 1, clock::timestamp())
+    }
+
+    fn save_screenshot(&mut self, path: &str) -> std::io::Result<()> {
+        self.materialize();
+        self.layout = self.frame_layout();
+        let frame = self.render();
+        std::fs::write(path, if self.prefs.screenshot_ansi { frame } else { strip_ansi(&frame) })
+    }
+
+    fn open_editor(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        let offset = self.pages.get(self.current_page).map_or(0, |page| page.offset).min(self.full_text.len());
+        let line = self.full_text[..offset].matches('\n').count() + 1;
+        terminal::disable_raw_mode()?;
+        execute!(stdout(), DisableBracketedPaste, DisableMouseCapture, terminal::LeaveAlternateScreen)?;
+        let result = external::edit(&self.full_text, line, self.prefs.keep_editor_file);
+        execute!(stdout(), terminal::EnterAlternateScreen, EnableMouseCapture, EnableBracketedPaste)?;
+        terminal::enable_raw_mode()?;
+        self.status = match result {
+            Err(e) => Some(e),
+            Ok(Some(path)) => Some(format!("Texto guardado en {}", path.display())),
+            Ok(None) => None,
+        };
+        Ok(())
+    }
+
+    fn show_output(&self, command: &str, output: &external::Output) -> Result<(), Box<dyn std::error::Error>> {
+        let lines: Vec<StyledContent<&str>> = output.stdout.lines().map(|line| line.stylize())
+            .chain(output.stderr.lines().map(|line| line.red()))
+            .collect();
+        let visible_rows = (self.terminal_height as usize).saturating_sub(7).max(1);
+        let mut first = 0;
+        loop {
+            execute!(stdout(), terminal::Clear(ClearType::All), cursor::MoveTo(0, 0))?;
+            println!("{}", format!("SALIDA - {}", command).bold().green());
+            println!();
+            for line in lines.iter().skip(first).take(visible_rows) {
+                println!("{}", line);
+            }
+            if lines.len() > first + visible_rows {
+                println!("{}", format!("… ({} líneas más, j/k o ↑/↓ para desplazar)", lines.len() - first - visible_rows).dark_grey());
+            }
+            if !output.status.success() {
+                let code = output.status.code().map_or("señal".to_string(), |code| code.to_string());
+                println!("{}", format!("La orden terminó con código {}", code).red().bold());
+            }
+            println!("\n Presiona cualquier otra tecla o haz clic para volver...");
+            match event::read()? {
+                Event::Key(key_event) if key_event.kind != KeyEventKind::Press => {}
+                Event::Key(key_event) => match key_event.code {
+                    KeyCode::Down | KeyCode::Char('j') => first = (first + 1).min(lines.len().saturating_sub(visible_rows)),
+                    KeyCode::Up | KeyCode::Char('k') => first = first.saturating_sub(1),
+                    KeyCode::PageDown | KeyCode::Char(' ') => first = (first + visible_rows).min(lines.len().saturating_sub(visible_rows)),
+                    KeyCode::PageUp => first = first.saturating_sub(visible_rows),
+                    _ => break,
+                },
+                Event::Mouse(MouseEvent { kind: MouseEventKind::Down(_), .. }) => break,
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn show_notes(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        if self.notes.notes.is_empty() {
+            self.status = Some("No hay notas (N para añadir una)".to_string());
+            return Ok(());
+        }
+        let mut selected: usize = 0;
+        let visible_rows = (self.terminal_height as usize).saturating_sub(6).max(1);
+        loop {
+            if self.notes.notes.is_empty() {
+                break;
+            }
+            selected = selected.min(self.notes.notes.len() - 1);
+            execute!(stdout(), terminal::Clear(ClearType::All), cursor::MoveTo(0, 0))?;
+            println!("{}", "NOTAS - PDF Viewer".bold().green());
+            println!();
+            let first = selected.saturating_sub(visible_rows - 1);
+            for (index, note) in self.notes.notes.iter().enumerate().skip(first).take(visible_rows) {
+                let entry = format!(" pág. {:>4}  {}", self.page_for_offset(note.offset).0 + 1, note.text);
+                if index == selected {
+                    println!("{}", entry.reverse());
+                } else {
+                    println!("{}", entry);
+                }
+            }
+            println!("\n {}", "↑/↓ Mover | Enter Ir | x Borrar | q/ESC Volver".italic().dark_grey());
+
+            if let Event::Key(key_event) = event::read()? {
+                if key_event.kind != KeyEventKind::Press {
+                    continue;
+                }
+                match key_event.code {
+                    KeyCode::Up | KeyCode::Char('k') => selected = selected.saturating_sub(1),
+                    KeyCode::Down | KeyCode::Char('j') => selected += 1,
+                    KeyCode::Enter => {
+                        self.jump_to(self.page_for_offset(self.notes.notes[selected].offset).0);
+                        break;
+                    }
+                    KeyCode::Char('x') | KeyCode::Delete => {
+                        if let Err(e) = self.notes.remove(selected) {
+                            self.status = Some(format!("No se pudo guardar las notas: {}", e));
+                        }
+                    }
+                    KeyCode::Char('q') | KeyCode::Esc => break,
+                    _ => {}
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn show_images(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        let pdf_page = self.pages.get(self.current_page).map_or(0, |page| page.pdf_page);
+        let refs = images::page_images(&self.doc, pdf_page);
+        if refs.is_empty() {
+            self.status = Some(format!("La página {} del PDF no tiene imágenes", pdf_page + 1));
+            return Ok(());
+        }
+        let backend = images::ImageBackend::detect(&self.prefs.image_backend);
+        let truecolor = images::truecolor_supported();
+        let max_cols = (self.terminal_width as usize).saturating_sub(2).max(1);
+        let max_rows = (self.terminal_height as usize).saturating_sub(4).max(1);
+        let (cell_width, cell_height) = match terminal::window_size() {
+            Ok(size) if size.width > 0 && size.height > 0 && size.columns > 0 && size.rows > 0 => {
+                ((size.width / size.columns).max(1) as usize, (size.height / size.rows).max(1) as usize)
+            }
+            _ => (10, 20),
+        };
+        let mut index = 0;
+        loop {
+            if backend == images::ImageBackend::Kitty {
+                print!("{}", kitty::delete());
+            }
+            execute!(stdout(), terminal::Clear(ClearType::All), cursor::MoveTo(0, 0))?;
+            let caption = format!("IMÁGENES - Página {} del PDF - {}/{} ({}×{})", 
+                pdf_page + 1, index + 1, refs.len(), refs[index].width, refs[index].height);
+            println!("{}", caption.bold().green());
+            let loaded = match backend {
+                images::ImageBackend::Sixel | images::ImageBackend::Kitty => {
+                    images::load_image(&self.doc, &refs[index], max_cols * cell_width, max_rows * cell_height / 6 * 6)
+                }
+                images::ImageBackend::Blocks => images::load_image(&self.doc, &refs[index], max_cols, max_rows * 2),
+            };
+            match loaded {
+                Ok(image) if backend == images::ImageBackend::Sixel => {
+                    execute!(stdout(), cursor::MoveTo(1, 1))?;
+                    print!("{}", sixel::encode(&image));
+                    execute!(stdout(), cursor::MoveTo(0, (max_rows + 1) as u16))?;
+                }
+                Ok(image) if backend == images::ImageBackend::Kitty => {
+                    execute!(stdout(), cursor::MoveTo(1, 1))?;
+                    print!("{}", kitty::transmit(&image));
+                    execute!(stdout(), cursor::MoveTo(0, (max_rows + 1) as u16))?;
+                }
+                Ok(image) => {
+                    for line in images::render_half_blocks(&image, truecolor) {
+                        println!(" {}", line);
+                    }
+                }
+                Err(e) => println!("\n {}", format!("No se pudo mostrar la imagen: {}", e).red()),
+            }
+            println!("{}", "←/→ Cambiar imagen | q/ESC Volver".italic().dark_grey());
+
+            if let Event::Key(key_event) = event::read()? {
+                if key_event.kind != KeyEventKind::Press {
+                    continue;
+                }
+                match key_event.code {
+                    KeyCode::Left | KeyCode::Char('h') => index = index.saturating_sub(1),
+                    KeyCode::Right | KeyCode::Char('l') => index = (index + 1).min(refs.len() - 1),
+                    KeyCode::Char('q') | KeyCode::Esc => break,
+                    _ => {}
+                }
+            }
+        }
+        if backend == images::ImageBackend::Kitty {
+            print!("{}", kitty::delete());
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "tts")]
+    fn toggle_speech(&mut self) {
+        if self.speaker.take().is_some() {
+            self.status = Some("Lectura en voz alta detenida".to_string());
+            return;
+        }
+        self.speak_current_page();
+    }
+
+    #[cfg(feature = "tts")]
+    fn speak_current_page(&mut self) {
+        let text = self.source_text(self.current_page).into_owned();
+        match tts::Speaker::speak(&text) {
+            Ok(speaker) => {
+                self.status = Some(format!("Leyendo la página {} en voz alta ({})", self.current_page + 1, speaker.backend));
+                self.speaker = Some(speaker);
+            }
+            Err(e) => self.status = Some(e),
+        }
+    }
+
+    #[cfg(feature = "tts")]
+    fn poll_speech(&mut self) -> bool {
+        let Some(speaker) = self.speaker.as_mut() else {
+            return false;
+        };
+        if !speaker.finished() {
+            return false;
+        }
+        self.speaker = None;
+        if self.prefs.tts_auto_advance && self.current_page + 1 < self.total_pages {
+            self.next_page();
+            self.speak_current_page();
+        } else {
+            self.status = Some("Lectura en voz alta terminada".to_string());
+        }
+        true
+    }
+
+    fn toggle_diff(&mut self) {
+        let Some(other_path) = self.compare_path.clone() else {
+            self.status = Some("Para comparar, abre dos PDFs: viewerpdf viejo.pdf nuevo.pdf".to_string());
+            return;
+        };
+        if self.other_pages.is_none() {
+            if let Err(e) = self.load_diff(&other_path) {
+                self.status = Some(format!("No se pudo cargar {}: {}", other_path, e));
+                return;
+            }
+        }
+        if let Some(other_pages) = self.other_pages.as_mut() {
+            std::mem::swap(&mut self.pages, other_pages);
+            self.index_pages();
+            std::mem::swap(&mut self.current_page, &mut self.other_page);
+            self.total_pages = self.pages.len().max(1);
+            self.current_page = self.current_page.min(self.total_pages - 1);
+            self.diff_mode = !self.diff_mode;
+            self.selected_link = None;
+            self.current_match = None;
+        }
+    }
+
+    fn load_diff(&mut self, other_path: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let other = Self::load_document(other_path, &self.extractor, &self.encoding, &self.columns)?;
+        let side_by_side = self.prefs.diff_layout == "side-by-side";
+        let diff = diff::diff_pages(&self.full_text, &other.text, self.tile_width(), self.page_height(), self.split.breaks, side_by_side);
+        self.other_pages = Some(diff.pages);
+        self.hunks = diff.hunks;
+        self.diff_lines = (diff.added, diff.removed);
+        Ok(())
+    }
+
+    fn jump_to_hunk(&mut self, forward: bool) {
+        let rows = self.prefs.cursor_line;
+        let here = (self.current_page, if rows { self.cursor_row } else { 0 });
+        let after = |hunk: &&(usize, usize)| if rows { **hunk > here } else { hunk.0 > here.0 };
+        let before = |hunk: &&(usize, usize)| if rows { **hunk < here } else { hunk.0 < here.0 };
+        let target = if forward { self.hunks.iter().find(after) } else { self.hunks.iter().rev().find(before) };
+        match target {
+            Some(&(page, row)) => {
+                self.current_page = page;
+                self.cursor_row = row;
+            }
+            None if self.hunks.is_empty() => self.status = Some("Los documentos son iguales".to_string()),
+            None => {
+                self.hit_boundary(if forward { "No hay más cambios" } else { "No hay cambios antes" });
+            }
+        }
+    }
+
+    fn help_key(&self) -> Option<KeyCode> {
+        action::parse_key(&self.prefs.help_key).filter(|code| *code != KeyCode::Char('?'))
+    }
+
+    fn help_lines(&self) -> Vec<String> {
+        let mut lines = vec![format!("{} {}", "AYUDA - PDF Viewer".bold().green(), format!("(teclas: {})", self.keymap.name()).dark_grey())];
+        for category in action::CATEGORIES {
+            lines.push(String::new());
+            lines.push(format!(" {}:", category));
+            for action in action::ALL.iter().filter(|action| action.describe().0 == *category) {
+                let mut keys = action.keys(self.keymap);
+                if *action == Action::Help && self.help_key().is_some() {
+                    keys.push(self.prefs.help_key.clone());
+                }
+                let keys = keys.join(" / ");
+                let keys = if keys.is_empty() {
+                    format!("{:<16}", "(sin asignar)").dark_grey().to_string()
+                } else {
+                    format!("{:<16}", keys)
+                };
+                lines.push(format!("  {} : {}", keys, action.describe().1));
+            }
+        }
+        lines.push(String::new());
+        lines.push("  Un número antes de la orden la repite (5l avanza 5 páginas)".to_string());
+        lines.push("  Modos activos en la barra de progreso: [C] cursor, [M] mapa, [D] comparación, [R] RAW, [/] búsqueda, [P] voz, [T] varias páginas, [S] vista dividida, [L] lectura continua, [U] regla de lectura, [Q] grabando macro".to_string());
+        lines.push("  Macros: Q + letra empieza a grabar, Q termina, @ + letra la repite (5@a cinco veces)".to_string());
+        lines.push(String::new());
+        lines.push(format!(" Barra de estado (statusline en config.toml, {} separa izquierda, centro y derecha):", statusline::SECTION));
+        for (name, description) in statusline::PLACEHOLDERS {
+            lines.push(format!("  {:<16} : {}", format!("{{{}}}", name), description));
+        }
+        lines.push(String::new());
+        lines.push(" Información del PDF:".to_string());
+        lines.push(format!("  Archivo: {}", self.pdf_name));
+        lines.push(format!("  Páginas: {}", self.total_pages));
+        lines.push(format!("  Caracteres: {}", self.char_prefix.last().copied().unwrap_or(0)));
+        lines.push(format!("  Texto extraído con: {}", self.extractor_used));
+        lines.push(format!("  Codificación: {}", self.encoding));
+        lines.push(format!("  Columnas: {}", self.columns));
+        lines.push("  Tiempos de carga:".to_string());
+        for line in self.timings.lines(self.doc.get_pages().len()) {
+            lines.push(format!("    {}", line));
+        }
+        lines.push(format!("  Enlaces: {}", self.links.len()));
+        let cache = self.wrap_cache.borrow();
+        lines.push(format!("  Caché de páginas: {}/{} entradas, {} aciertos, {} fallos", cache.len(), cache.capacity(), cache.hits, cache.misses));
+        lines
+    }
+
+    fn show_help(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let lines = self.help_lines();
+        let height = (self.terminal_height as usize).saturating_sub(2).max(1);
+        let mut top = 0;
+        loop {
+            execute!(stdout(), terminal::Clear(ClearType::All), cursor::MoveTo(0, 0))?;
+            for line in lines.iter().skip(top).take(height) {
+                println!("{}", line);
+            }
+            let last = (top + height).min(lines.len());
+            println!("\n {}", format!("j/k ↑/↓ Línea | Espacio/AvPág Página | g/G Inicio/Fin | otra tecla o clic Volver ({}-{} de {})", top + 1, last, lines.len()).italic().dark_grey());
+            let key = match event::read()? {
+                Event::Key(key_event) if key_event.kind == KeyEventKind::Press => key_event.code,
+                Event::Mouse(MouseEvent { kind: MouseEventKind::ScrollDown, .. }) => KeyCode::Down,
+                Event::Mouse(MouseEvent { kind: MouseEventKind::ScrollUp, .. }) => KeyCode::Up,
+                Event::Mouse(MouseEvent { kind: MouseEventKind::Down(_), .. }) => break,
+                _ => continue,
+            };
+            match scroll_overlay(top, key, lines.len(), height) {
+                Some(next) => top = next,
+                None => break,
+            }
+        }
+        Ok(())
+    }
+
+    fn search_prompt(&mut self, forward: bool) -> Result<(), Box<dyn std::error::Error>> {
+        let label = if forward { "/" } else { "\\" };
+        let Some(query) = prompt::read_line_with(label, "", &self.state.search_history, |_| None)? else {
+            return Ok(());
+        };
+        let query = query.trim().to_string();
+        if !query.is_empty() {
+            self.start_search(query, forward);
+        }
+        Ok(())
+    }
+
+    fn start_search(&mut self, query: String, forward: bool) {
+        self.typed = Some(query.clone());
+        let history = &mut self.state.search_history;
+        history.retain(|previous| *previous != query);
+        history.push(query.clone());
+        let excess = history.len().saturating_sub(self.prefs.search_history_size);
+        history.drain(..excess);
+        let _ = self.state.save();
+
+        self.set_search(query, forward);
+        self.current_match = None;
+        self.repeat_search(false);
+    }
+
+    fn set_search(&mut self, query: String, forward: bool) {
+        let matches = Rc::new(search::find_all(&self.full_text, &query, self.prefs.whole_word));
+        self.search = Some(Search { query, forward, matches });
+    }
+
+    fn repeat_search(&mut self, reverse: bool) {
+        let Some(search) = self.search.clone() else {
+            self.status = Some("No hay búsqueda activa (/ para buscar)".to_string());
+            self.step_failed = true;
+            return;
+        };
+        let forward = search.forward != reverse;
+        let (page_start, page_end) = self.page_range(self.current_page);
+        let current = self.current_match.filter(|offset| (page_start..page_end).contains(offset));
+        let matches = &search.matches;
+        let found = if forward {
+            matches.iter().find(|m| current.map_or(m.start >= page_start, |offset| m.start > offset))
+        } else {
+            matches.iter().rev().find(|m| current.map_or(m.start < page_end, |offset| m.start < offset))
+        };
+        let wrapped = found.is_none() && self.prefs.search_wrap;
+        let found = match found {
+            None if wrapped => if forward { matches.first() } else { matches.last() },
+            found => found,
+        };
+        if let Some(found) = found {
+            self.current_match = Some(found.start);
+            let (page, row) = self.page_for_offset(found.start);
+            if page != self.current_page {
+                self.jump_to(page);
+            }
+            if self.continuous {
+                self.scroll_to_line(self.line_prefix.get(page).copied().unwrap_or(0) + row);
+            }
+            self.reveal_match(found.start);
+            let index = matches.iter().position(|m| m.start == found.start).unwrap_or(0);
+            self.hooks.on_search(&search.query, Some(index), matches.len());
+            let mut status = format!("{}{} [{}/{}]", if search.forward { "/" } else { "\\" }, search.query, index + 1, matches.len());
+            if wrapped {
+                status.push_str(if forward { " · la búsqueda volvió al principio" } else { " · la búsqueda volvió al final" });
+            }
+            self.status = Some(status);
+            return;
+        }
+        self.step_failed = true;
+        self.hooks.on_search(&search.query, None, matches.len());
+        self.status = Some(if matches.is_empty() {
+            format!("No se encontró \"{}\"", search.query)
+        } else {
+            format!("No hay más coincidencias de \"{}\"", search.query)
+        });
+    }
+
+    fn command_prompt(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        if let Some(line) = prompt::read_line_with(":", "", &[], settings::complete)? {
+            self.typed = Some(line.trim().to_string());
+            self.run_command(line.trim())?;
+        }
+        Ok(())
+    }
+
+    fn run_command(&mut self, line: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let mut words = line.split_whitespace();
+        match words.next() {
+            None => {}
+            Some("set" | "se") => {
+                let arguments: Vec<&str> = words.collect();
+                if arguments.is_empty() {
+                    return self.show_settings();
+                }
+                for argument in arguments {
+                    match settings::parse(argument) {
+                        Ok((setting, value)) => self.apply_setting(setting, value),
+                        Err(e) => {
+                            self.status = Some(e);
+                            break;
+                        }
+                    }
+                }
+            }
+            Some("mksession" | "mks") => match words.next() {
+                Some(name) => self.make_session(name),
+                None => self.status = Some("Uso: :mksession NOMBRE".to_string()),
+            },
+            Some("mksidecar" | "mksi") => self.save_sidecar(),
+            Some("split" | "sp") => self.split_view(false),
+            Some("vsplit" | "vs") => self.split_view(true),
+            Some("only" | "on" | "close" | "clo") => self.close_pane(),
+            Some("offset") => match words.next().map(str::parse::<usize>) {
+                Some(Ok(offset)) if offset < self.full_text.len() => self.goto_offset(offset),
+                Some(Ok(_)) => self.status = Some(format!("El texto extraído tiene {} bytes", self.full_text.len())),
+                _ => self.status = Some("Uso: :offset N (posición en bytes del texto extraído)".to_string()),
+            },
+            Some("count" | "cou") => {
+                let pattern = line.split_once(char::is_whitespace).map_or("", |(_, rest)| rest.trim());
+                match (pattern, &self.search) {
+                    ("", None) => self.status = Some("Uso: :count PATRÓN (sin patrón cuenta la última búsqueda)".to_string()),
+                    ("", Some(search)) => self.status = Some(self.count_matches(&search.query)),
+                    (pattern, _) => self.status = Some(self.count_matches(pattern)),
+                }
+            }
+            Some("sessions") => {
+                let names = session::list();
+                self.status = Some(if names.is_empty() {
+                    "No hay sesiones guardadas (:mksession NOMBRE)".to_string()
+                } else {
+                    format!("Sesiones: {}", names.join(", "))
+                });
+            }
+            Some(other) => match other.strip_suffix('%').and_then(|percent| percent.parse().ok()) {
+                Some(percent) => self.goto_percent(percent),
+                None => self.status = Some(format!("Orden desconocida: {}", other)),
+            },
+        }
+        Ok(())
+    }
+
+    fn count_matches(&self, pattern: &str) -> String {
+        let matches = search::find_all(&self.full_text, pattern, self.prefs.whole_word);
+        let mut pages: Vec<usize> = matches.iter()
+            .map(|m| self.pages.partition_point(|page| page.offset <= m.start).saturating_sub(1))
+            .collect();
+        pages.dedup();
+        match (matches.len(), pages.len()) {
+            (0, _) => format!("\"{}\": sin coincidencias", pattern),
+            (1, _) => format!("\"{}\": 1 coincidencia en la página {}", pattern, pages[0] + 1),
+            (found, 1) => format!("\"{}\": {} coincidencias en 1 página", pattern, found),
+            (found, pages) => format!("\"{}\": {} coincidencias en {} páginas", pattern, found, pages),
+        }
+    }
+
+    fn make_session(&mut self, name: &str) {
+        let document = SessionDocument {
+            path: self.state.path.clone(),
+            compare: self.compare_path.as_ref().map(|path| std::fs::canonicalize(path).map_or(path.clone(), |path| path.to_string_lossy().into_owned())),
+            offset: self.pages.get(self.current_page).map_or(0, |page| page.offset),
+            search: self.search.as_ref().map(|search| search.query.clone()),
+            search_forward: self.search.as_ref().is_none_or(|search| search.forward),
+        };
+        let settings = self.current_settings().into_iter().collect();
+        self.status = Some(match (Session { document, settings }).save(name) {
+            Ok(path) => format!("Sesión guardada en {}", path.display()),
+            Err(e) => format!("No se pudo guardar la sesión: {}", e),
+        });
+    }
+
+    fn current_settings(&self) -> Vec<(String, String)> {
+        settings::SETTINGS.iter()
+            .map(|setting| {
+                let value = match self.setting_value(setting) {
+                    Value::Bool(enabled) => if enabled { "on" } else { "off" }.to_string(),
+                    Value::Text(text) => text,
+                };
+                (setting.name.to_string(), value)
+            })
+            .collect()
+    }
+
+    fn apply_sidecar(&mut self) {
+        let name = display_name(&sidecar::path(&self.state.path).to_string_lossy());
+        let entries = match sidecar::load(&self.state.path) {
+            Ok(entries) => entries,
+            Err(e) => {
+                self.status = Some(format!("Se ignora {}: {}", name, e));
+                return;
+            }
+        };
+        let mut errors = Vec::new();
+        for (setting, value) in std::mem::take(&mut self.sidecar_settings) {
+            self.apply_setting(setting, value);
+        }
+        for (key, value) in &entries {
+            match settings::parse(&format!("{}={}", key, value)) {
+                Ok((setting, value)) => {
+                    let global = self.setting_value(setting);
+                    self.apply_setting(setting, value);
+                    self.sidecar_settings.push((setting, global));
+                }
+                Err(e) => errors.push(e),
+            }
+        }
+        if !errors.is_empty() {
+            self.status = Some(format!("{}: {}", name, errors.join("; ")));
+        } else if !entries.is_empty() {
+            self.status = Some(format!("Ajustes de {} aplicados", name));
+        }
+    }
+
+    fn save_sidecar(&mut self) {
+        self.status = Some(match sidecar::save(&self.state.path, &self.current_settings()) {
+            Ok(path) => format!("Ajustes guardados en {}", path.display()),
+            Err(e) => format!("No se pudo guardar {}: {}", sidecar::path(&self.state.path).display(), e),
+        });
+    }
+
+    fn restore_session(&mut self, session: &Session, skipped: &[String]) {
+        let document = &session.document;
+        for (name, value) in &session.settings {
+            if let Ok((setting, value)) = settings::parse(&format!("{}={}", name, value)) {
+                self.apply_setting(setting, value);
+            }
+        }
+        self.current_page = self.page_for_offset(document.offset).0;
+        self.search = None;
+        if let Some(query) = document.search.clone() {
+            self.set_search(query, document.search_forward);
+        }
+        if !skipped.is_empty() {
+            self.status = Some(format!("Sesión restaurada sin: {}", skipped.join(", ")));
+        }
+    }
+
+    fn setting_value(&self, setting: &Setting) -> Value {
+        match setting.name {
+            "theme" => Value::Text(self.prefs.theme.clone()),
+            "cursorline" => Value::Bool(self.prefs.cursor_line),
+            "minimap" => Value::Bool(self.minimap),
+            "boundary" => Value::Text(self.prefs.page_boundary.clone()),
+            "fileboundary" => Value::Text(self.prefs.file_boundary.clone()),
+            "diff" => Value::Text(self.prefs.diff_layout.clone()),
+            "bell" => Value::Text(self.prefs.bell.clone()),
+            "quiet" => Value::Bool(self.prefs.quiet),
+            "title" => Value::Bool(self.prefs.set_title),
+            "shrink" => Value::Bool(self.prefs.shrink_frame),
+            "wrapscan" => Value::Bool(self.prefs.search_wrap),
+            "wholeword" => Value::Bool(self.prefs.whole_word),
+            "scroll" => Value::Bool(self.continuous),
+            "scrollframe" => Value::Bool(self.prefs.scroll_frame),
+            "clock" => Value::Bool(self.prefs.clock),
+            "timer" => Value::Bool(self.prefs.reading_timer),
+            "battery" => Value::Bool(self.prefs.battery),
+            "images" => Value::Text(self.prefs.image_backend.clone()),
+            "ttsadvance" => Value::Bool(self.prefs.tts_auto_advance),
+            "progress" => Value::Text(self.prefs.progress.clone()),
+            "cols" => Value::Text(self.cols.to_string()),
+            "skipblank" => Value::Bool(self.prefs.skip_blank),
+            "squeeze" => Value::Bool(self.prefs.squeeze_whitespace),
+            "blankpages" => Value::Text(self.prefs.blank_pages.clone()),
+            "tables" => Value::Bool(self.prefs.preserve_tables),
+            "bidi" => Value::Bool(self.prefs.detect_direction),
+            "syntax" => Value::Bool(self.prefs.highlight_code),
+            "footnotes" => Value::Bool(self.prefs.move_footnotes),
+            "hyphenate" => Value::Text(self.prefs.hyphenate.clone()),
+            "break" => Value::Text(self.prefs.line_break.clone()),
+            "number" => Value::Text(self.prefs.number.clone()),
+            _ => Value::Text(String::new()),
+        }
+    }
+
+    fn apply_setting(&mut self, setting: &Setting, value: Value) {
+        match (setting.name, value) {
+            ("theme", Value::Text(name)) => {
+                self.theme = Theme::named(&name);
+                self.prefs.theme = name;
+            }
+            ("cursorline", Value::Bool(enabled)) => self.prefs.cursor_line = enabled,
+            ("minimap", Value::Bool(enabled)) => self.minimap = enabled,
+            ("boundary", Value::Text(mode)) => self.prefs.page_boundary = mode,
+            ("fileboundary", Value::Text(mode)) => self.prefs.file_boundary = mode,
+            ("diff", Value::Text(layout)) => self.prefs.diff_layout = layout,
+            ("bell", Value::Text(mode)) => self.prefs.bell = mode,
+            ("quiet", Value::Bool(enabled)) => self.prefs.quiet = enabled,
+            ("title", Value::Bool(enabled)) => {
+                self.prefs.set_title = enabled;
+                if !enabled {
+                    restore_title();
+                    self.window_title.clear();
+                }
+            }
+            ("clock", Value::Bool(enabled)) => self.prefs.clock = enabled,
+            ("shrink", Value::Bool(enabled)) => self.prefs.shrink_frame = enabled,
+            ("wrapscan", Value::Bool(enabled)) => self.prefs.search_wrap = enabled,
+            ("wholeword", Value::Bool(enabled)) => {
+                self.prefs.whole_word = enabled;
+                if let Some(search) = self.search.take() {
+                    self.set_search(search.query, search.forward);
+                }
+            }
+            ("scroll", Value::Bool(enabled)) => self.set_continuous(enabled),
+            ("scrollframe", Value::Bool(enabled)) => self.prefs.scroll_frame = enabled,
+            ("timer", Value::Bool(enabled)) => self.prefs.reading_timer = enabled,
+            ("battery", Value::Bool(enabled)) => self.prefs.battery = enabled,
+            ("images", Value::Text(backend)) => self.prefs.image_backend = backend,
+            ("ttsadvance", Value::Bool(enabled)) => self.prefs.tts_auto_advance = enabled,
+            ("progress", Value::Text(mode)) => self.prefs.progress = mode,
+            ("cols", Value::Text(cols)) => self.cols = cols.parse().unwrap_or(1),
+            ("skipblank", Value::Bool(enabled)) => self.prefs.skip_blank = enabled,
+            ("squeeze", Value::Bool(enabled)) => self.prefs.squeeze_whitespace = enabled,
+            ("blankpages", Value::Text(mode)) => self.prefs.blank_pages = mode,
+            ("tables", Value::Bool(enabled)) => self.prefs.preserve_tables = enabled,
+            ("bidi", Value::Bool(enabled)) => self.prefs.detect_direction = enabled,
+            ("syntax", Value::Bool(enabled)) => self.prefs.highlight_code = enabled,
+            ("footnotes", Value::Bool(enabled)) => self.prefs.move_footnotes = enabled,
+            ("hyphenate", Value::Text(language)) => self.prefs.hyphenate = language,
+            ("break", Value::Text(policy)) => self.prefs.line_break = policy,
+            ("number", Value::Text(mode)) => self.prefs.number = mode,
+            _ => {}
+        }
+        if setting.layout {
+            self.reflow();
+        }
+    }
+
+    fn show_settings(&self) -> Result<(), Box<dyn std::error::Error>> {
+        execute!(stdout(), terminal::Clear(ClearType::All), cursor::MoveTo(0, 0))?;
+        println!("{}", "OPCIONES - PDF Viewer".bold().green());
+        println!();
+        for setting in settings::SETTINGS {
+            let value = format!("{:<8}", self.setting_value(setting).to_string());
+            println!("  {:<12} {} {}", setting.name, value.bold(), setting.description.dark_grey());
+        }
+        println!("\n :set opción, :set noopción o :set opción=valor (Tab completa)");
+        println!(" :mksidecar guarda estas opciones en {}", display_name(&sidecar::path(&self.state.path).to_string_lossy()));
+        println!(" Prioridad: archivo .pdfview junto al PDF > opciones de la línea de órdenes > config.toml > valores por defecto");
+        println!("\n Presiona cualquier tecla o haz clic para volver...");
+        loop {
+            match event::read()? {
+                Event::Key(_) | Event::Mouse(MouseEvent { kind: MouseEventKind::Down(_), .. }) => break,
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn perform(&mut self, action: Action, count: Option<usize>) -> Result<bool, Box<dyn std::error::Error>> {
+        if self.pages.is_empty() && !matches!(action, Action::Quit | Action::Help | Action::ShowImages) {
+            return Ok(false);
+        }
+        let times = count.unwrap_or(1).max(1);
+        match action {
+            Action::NextPage | Action::PrevPage if self.continuous => {
+                let screen = (self.page_height() as usize).saturating_sub(9).max(1) * times;
+                self.scroll_lines(if action == Action::NextPage { screen as isize } else { -(screen as isize) });
+            }
+            Action::HalfPageDown | Action::HalfPageUp if self.continuous => {
+                let half = ((self.page_height() as usize).saturating_sub(9) / 2).max(1) * times;
+                self.scroll_lines(if action == Action::HalfPageDown { half as isize } else { -(half as isize) });
+            }
+            Action::HalfPageDown => (0..times).for_each(|_| { self.next_page(); }),
+            Action::HalfPageUp => (0..times).for_each(|_| { self.prev_page(); }),
+            Action::ScrollDown => { self.scroll_lines(times as isize); }
+            Action::ScrollUp => { self.scroll_lines(-(times as isize)); }
+            Action::ToggleContinuous => self.set_continuous(!self.continuous),
+            Action::ZoomIn => self.zoom((WRAP_STEP as usize * times) as i32),
+            Action::ZoomOut => self.zoom(-((WRAP_STEP as usize * times) as i32)),
+            Action::ZoomReset => self.zoom_reset(),
+            Action::NextPage => (0..times).for_each(|_| { self.next_page(); }),
+            Action::PrevPage => (0..times).for_each(|_| { self.prev_page(); }),
+            Action::FirstPage | Action::LastPage if count.is_some() => self.jump_to(times - 1),
+            Action::FirstPage => self.jump_to(0),
+            Action::LastPage => self.jump_to(self.total_pages.saturating_sub(1)),
+            Action::FileInfo => self.show_file_info(),
+            Action::GotoPercent => match count {
+                Some(percent) => self.goto_percent(percent),
+                None => self.status = Some("Escribe el porcentaje antes de %: 50% va a la mitad".to_string()),
+            },
+            Action::NextChapter => (0..times).for_each(|_| self.jump_to_chapter(true)),
+            Action::PrevChapter => (0..times).for_each(|_| self.jump_to_chapter(false)),
+            Action::CursorDown => (0..times).for_each(|_| self.cursor_down()),
+            Action::CursorUp => (0..times).for_each(|_| self.cursor_up()),
+            Action::ScrollLeft => (0..times).for_each(|_| self.scroll_horizontally(false)),
+            Action::ScrollRight => (0..times).for_each(|_| self.scroll_horizontally(true)),
+            Action::JumpBack => (0..times).for_each(|_| self.jump_back()),
+            Action::ToggleRecent => self.toggle_recent(),
+            Action::NextFile => (0..times).for_each(|_| self.switch_file(true)),
+            Action::PrevFile => (0..times).for_each(|_| self.switch_file(false)),
+            Action::NextLink => (0..times).for_each(|_| self.cycle_link(true)),
+            Action::PrevLink => (0..times).for_each(|_| self.cycle_link(false)),
+            Action::ActivateLink => {
+                if let Some(target) = self.selected_target() {
+                    self.activate_link(target);
+                }
+            }
+            Action::CopyUrl => self.copy_selected_url(),
+            Action::ToggleCursorLine => (0..times).for_each(|_| self.toggle_cursor_line()),
+            Action::ToggleRuler => (0..times).for_each(|_| self.toggle_ruler()),
+            Action::CycleNumbers => (0..times).for_each(|_| self.cycle_numbers()),
+            Action::VisualMode => self.visual_mode()?,
+            #[cfg(feature = "tts")]
+            Action::ToggleSpeech => self.toggle_speech(),
+            Action::ToggleDiff => (0..times).for_each(|_| self.toggle_diff()),
+            Action::NextSection if self.diff_mode => (0..times).for_each(|_| self.jump_to_hunk(true)),
+            Action::PrevSection if self.diff_mode => (0..times).for_each(|_| self.jump_to_hunk(false)),
+            Action::NextSection => (0..times).for_each(|_| self.jump_to_heading(true)),
+            Action::PrevSection => (0..times).for_each(|_| self.jump_to_heading(false)),
+            Action::ToggleMinimap => (0..times).for_each(|_| self.toggle_minimap()),
+            Action::MinimapDown => {
+                self.minimap_row = Some((self.minimap_selection() + times).min(self.total_pages - 1));
+            }
+            Action::MinimapUp => self.minimap_row = Some(self.minimap_selection().saturating_sub(times)),
+            Action::MinimapJump => self.jump_to(self.minimap_selection()),
+            Action::ShowImages => self.show_images()?,
+            Action::ShowLinkList => self.show_link_list()?,
+            Action::ShowOutline => self.show_outline()?,
+            Action::ShowPalette => {
+                if let Some(chosen) = self.show_palette()? {
+                    return self.perform(chosen, count);
+                }
+            }
+            Action::ExternalCommand => self.run_external()?,
+            Action::PipePage => {
+                let text = self.source_text(self.current_page).into_owned();
+                self.pipe_text(&text)?;
+            }
+            Action::OpenEditor => self.open_editor()?,
+            Action::AddNote => self.add_note()?,
+            Action::ShowNotes => self.show_notes()?,
+            Action::SetMark(slot) => self.set_mark(slot),
+            Action::JumpToMark(slot) => self.jump_to_mark(slot),
+            Action::AddHighlight => self.add_highlight(),
+            Action::CycleHighlight => self.cycle_highlight(),
+            Action::RemoveHighlight => self.remove_highlight(),
+            Action::SearchForward => self.search_prompt(true)?,
+            Action::SearchBackward => self.search_prompt(false)?,
+            Action::NextMatch => (0..times).for_each(|_| self.repeat_search(false)),
+            Action::PrevMatch => (0..times).for_each(|_| self.repeat_search(true)),
+            Action::ToggleRaw => (0..times).for_each(|_| self.toggle_raw()),
+            Action::SwitchPane => (0..times).for_each(|_| self.switch_pane()),
+            Action::Command => self.command_prompt()?,
+            Action::Screenshot => {
+                let path = self.screenshot_name();
+                self.status = Some(match self.save_screenshot(&path) {
+                    Ok(()) => format!("Pantalla guardada en {}", path),
+                    Err(e) => format!("No se pudo guardar {}: {}", path, e),
+                });
+            }
+            Action::RepeatLast | Action::Refresh => {}
+            Action::Help => self.show_help()?,
+            Action::Quit => return Ok(true),
+        }
+        Ok(false)
+    }
+
+    fn record(&mut self, action: Action, count: Option<usize>) {
+        let typed = self.typed.take();
+        if let Some((_, steps)) = &mut self.recording {
+            steps.push((action, count, typed));
+        }
+    }
+
+    fn toggle_recording(&mut self, register: Option<char>) {
+        match (self.recording.take(), register) {
+            (Some((name, steps)), _) => {
+                self.status = Some(format!("Macro @{} grabada ({} pasos)", name, steps.len()));
+                self.macros.insert(name, steps);
+            }
+            (None, Some(name)) => {
+                self.recording = Some((name, Vec::new()));
+                self.status = Some(format!("Grabando @{} (Q para terminar)", name));
+            }
+            (None, None) => {}
+        }
+    }
+
+    fn play_macro(&mut self, register: char, times: usize) -> Result<bool, Box<dyn std::error::Error>> {
+        let Some(steps) = self.macros.get(&register).filter(|steps| !steps.is_empty()).cloned() else {
+            self.status = Some(format!("La macro @{} está vacía", register));
+            return Ok(false);
+        };
+        for _ in 0..times {
+            for (step, (action, count, typed)) in steps.iter().enumerate() {
+                self.step_failed = false;
+                match (action, typed) {
+                    (Action::SearchForward | Action::SearchBackward, Some(query)) => self.start_search(query.clone(), *action == Action::SearchForward),
+                    (Action::Command, Some(line)) => self.run_command(line)?,
+                    _ => {
+                        if self.perform(*action, *count)? {
+                            return Ok(true);
+                        }
+                    }
+                }
+                self.typed = None;
+                if self.step_failed {
+                    let reason = self.transient.as_ref().map(|(message, _, _)| message.clone()).or(self.status.clone()).unwrap_or_default();
+                    self.status = Some(format!("Macro @{} detenida en el paso {} ({}): {}", register, step + 1, action.name(), reason));
+                    return Ok(false);
+                }
+            }
+        }
+        Ok(false)
+    }
+
+    pub fn run(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        let screen = Screen::enter()?;
+        self.help_hint = true;
+        self.load_complete();
+        self.draw_page()?;
+        let mut count: Option<usize> = None;
+        let mut prefix: Option<char> = None;
+        let mut last_action: Option<(Action, Option<usize>)> = None;
+        loop {
+            if event::poll(std::time::Duration::from_millis(100))? {
+                let page_before = self.current_page;
+                let row_before = self.cursor_row;
+                let scroll_before = self.scroll;
+                let event = event::read()?;
+                if matches!(event, Event::Key(_) | Event::Mouse(_)) {
+                    self.last_input = Instant::now();
+                    self.transient = None;
+                    if std::mem::take(&mut self.dimmed) {
+                        self.draw_page()?;
+                        continue;
+                    }
+                }
+                match event {
+                    Event::Mouse(MouseEvent { kind: MouseEventKind::Down(MouseButton::Left), row, column, .. }) => {
+                        self.status = None;
+                        prefix = None;
+                        count = None;
+                        self.click(row, column)?;
+                        self.draw_page()?;
+                    }
+                    Event::Resize(width, height) => {
+                        self.terminal_width = width;
+                        self.terminal_height = height;
+                        self.reflow();
+                        self.draw_page()?;
+                    }
+                    Event::Key(key_event) if key_event.kind == KeyEventKind::Press => {
+                        self.status = None;
+                        if let Some(pending) = prefix.take() {
+                            match (pending, key_event.code) {
+                                ('Q', KeyCode::Char(name @ 'a'..='z')) => self.toggle_recording(Some(name)),
+                                ('@', KeyCode::Char(name @ 'a'..='z')) => {
+                                    if self.play_macro(name, count.unwrap_or(1))? {
+                                        break;
+                                    }
+                                }
+                                _ => {
+                                    if let Some(action) = Action::from_prefixed(pending, key_event) {
+                                        self.perform(action, None)?;
+                                        self.record(action, None);
+                                    }
+                                }
+                            }
+                            count = None;
+                            self.draw_page()?;
+                            continue;
+                        }
+                        match key_event.code {
+                            KeyCode::Char(pending @ ('M' | '\'')) => {
+                                prefix = Some(pending);
+                                self.status = Some(format!("{} (1-9)", pending));
+                                self.draw_page()?;
+                                continue;
+                            }
+                            KeyCode::Char('Q') if self.recording.is_some() => self.toggle_recording(None),
+                            KeyCode::Char(pending @ ('Q' | '@')) => {
+                                prefix = Some(pending);
+                                self.status = Some(format!("{} (a-z)", pending));
+                                self.draw_page()?;
+                                continue;
+                            }
+                            KeyCode::Char(digit @ '0'..='9') if digit != '0' || count.is_some() => {
+                                let value = count.unwrap_or(0).saturating_mul(10).saturating_add(digit as usize - '0' as usize);
+                                count = Some(value);
+                                self.status = Some(value.to_string());
+                                self.draw_page()?;
+                                continue;
+                            }
+                            _ => {
+                                let state = KeyState { cursor_line: self.prefs.cursor_line, continuous: self.continuous, minimap: self.minimap, keymap: self.keymap, help_key: self.help_key() };
+                                if let Some(Action::RepeatLast) = Action::from_key(key_event, &state) {
+                                    if let Some((action, last_count)) = last_action {
+                                        self.perform(action, count.or(last_count))?;
+                                        self.record(action, count.or(last_count));
+                                    }
+                                } else if let Some(action) = Action::from_key(key_event, &state) {
+                                    let action = match action {
+                                        Action::ShowPalette => self.show_palette()?,
+                                        action => Some(action),
+                                    };
+                                    let Some(action) = action else {
+                                        count = None;
+                                        self.draw_page()?;
+                                        continue;
+                                    };
+                                    if self.perform(action, count)? {
+                                        break;
+                                    }
+                                    self.record(action, count);
+                                    if action.repeatable() {
+                                        last_action = Some((action, count));
+                                    }
+                                }
+                            }
+                        }
+                        count = None;
+                        self.draw_page()?;
+                    }
+                    _ => {}
+                }
+                self.ring_bell(page_before)?;
+                self.sync_ruler(page_before, row_before);
+                if self.current_page != page_before {
+                    self.sync_match();
+                    self.page_changed();
+                }
+                if self.help_hint && (self.current_page != page_before || self.scroll != scroll_before) {
+                    self.help_hint = false;
+                    self.draw_page()?;
+                }
+            }
+            self.track_dwell();
+            self.track_reading();
+            if self.expire_transient() || self.dim_when_idle() || (self.refresh_widgets() && !self.dimmed) {
+                self.draw_page()?;
+            }
+            #[cfg(feature = "tts")]
+            if self.poll_speech() {
+                self.draw_page()?;
+            }
+        }
+        
+        self.save_position();
+        drop(screen);
+        
+        if self.keep_output && !self.pages.is_empty() {
+            println!("{}", format!("📄 {} - Página {}/{} 📄", self.pdf_name, self.current_page + 1, self.total_pages).bold().blue());
+            println!("{}", self.page_text(self.current_page));
+        }
+        
+        Ok(())
+    }
+}
+
+fn control_picture(c: char) -> char {
+    match c {
+        '\0'..='\x1f' => char::from_u32(0x2400 + c as u32).unwrap_or(c),
+        '\x7f' => '␡',
+        _ => c,
+    }
+}
+
+fn wrap_section(section: &str, section_start: usize, content_width: usize, options: SplitOptions) -> (Vec<String>, Option<usize>) {
+    let mut wrap_options = breaks::apply(textwrap::Options::new(content_width), options.breaks);
+    if let Some(language) = options.hyphenate {
+        wrap_options = wrap_options.word_splitter(hyphenate::splitter(language));
+    }
+    let wrap = |text: &str| {
+        let text = if options.squeeze { Cow::Owned(squeeze_whitespace(text)) } else { Cow::Borrowed(text) };
+        text.split('\n')
+            .map(|line| match list_indent(line).filter(|indent| *indent < content_width / 2) {
+                Some(indent) => fill(line, wrap_options.clone().subsequent_indent(&" ".repeat(indent))),
+                None => fill(line, &wrap_options),
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+    let moved = if options.footnotes { footnotes::move_to_bottom(section) } else { None };
+    let split = options.anchor.checked_sub(section_start)
+        .filter(|split| moved.is_none() && *split > 0 && *split < section.len() && section.is_char_boundary(*split));
+    let section = moved.as_deref().unwrap_or(section);
+    let source: Vec<&str> = section.split('\n').collect();
+    let mut tables = if options.tables { table_rows(&source, 3) } else { vec![false; source.len()] };
+    if options.code {
+        for (verbatim, language) in tables.iter_mut().zip(code::blocks(&source)) {
+            *verbatim |= language.is_some();
+        }
+    }
+
+    let mut lines: Vec<String> = Vec::new();
+    let mut break_at = None;
+    let mut start = 0;
+    let mut first = 0;
+    while first < source.len() {
+        let last = (first..source.len()).find(|&index| tables[index] != tables[first]).unwrap_or(source.len());
+        let end = start + source[first..last].iter().map(|line| line.len() + 1).sum::<usize>() - 1;
+        let split = split.filter(|split| (start..=end).contains(split));
+        if tables[first] {
+            if let Some(split) = split {
+                break_at = Some(lines.len() + section[start..split].matches('\n').count());
+            }
+            lines.extend(source[first..last].iter().map(|line| line.trim_end().to_string()));
+        } else {
+            let wrapped = |text: &str| {
+                let text = wrap(text);
+                if last == source.len() { text.lines().map(str::to_string).collect::<Vec<_>>() } else { text.split('\n').map(str::to_string).collect() }
+            };
+            if let Some(split) = split {
+                lines.extend(wrap(&section[start..split]).lines().map(str::to_string));
+                break_at = Some(lines.len());
+                lines.extend(wrapped(&section[split..end]));
+            } else {
+                lines.extend(wrapped(&section[start..end]));
+            }
+        }
+        start = end + 1;
+        first = last;
+    }
+    let leading = lines.iter().take_while(|line| line.trim().is_empty()).count();
+    let trailing = lines[leading..].iter().rev().take_while(|line| line.trim().is_empty()).count();
+    lines.truncate(lines.len() - trailing);
+    lines.drain(..leading);
+    (lines, break_at.map(|at| at.saturating_sub(leading)))
+}
+
+fn table_rows(lines: &[&str], min_rows: usize) -> Vec<bool> {
+    let cells: Vec<Vec<usize>> = lines.iter().map(|line| cell_columns(line)).collect();
+    let mut rows = vec![false; lines.len()];
+    let mut start = 0;
+    while start < lines.len() {
+        let end = (start..lines.len()).find(|&index| cells[index].len() < 2).unwrap_or(lines.len());
+        if end - start >= min_rows && is_table(&cells[start..end]) {
+            rows[start..end].fill(true);
+        }
+        start = end + 1;
+    }
+    rows
+}
+
+fn cell_columns(line: &str) -> Vec<usize> {
+    let mut columns = Vec::new();
+    let mut gap = 2;
+    for (column, c) in line.trim_end().chars().enumerate() {
+        if c == ' ' {
+            gap += 1;
+        } else if c == '\t' {
+            gap += 2;
+        } else {
+            if gap >= 2 {
+                columns.push(column);
+            }
+            gap = 0;
+        }
+    }
+    columns
+}
+
+fn is_table(block: &[Vec<usize>]) -> bool {
+    let Some(count) = (2..=block.iter().map(Vec::len).max().unwrap_or(0))
+        .max_by_key(|count| block.iter().filter(|cells| cells.len() == *count).count())
+    else {
+        return false;
+    };
+    let second: Vec<usize> = block.iter().filter(|cells| cells.len() == count).map(|cells| cells[1]).collect();
+    if second.len() * 3 < block.len() * 2 {
+        return false;
+    }
+    count >= 3 || second.iter().any(|column| second.iter().filter(|other| column.abs_diff(**other) <= 1).count() * 3 >= second.len() * 2)
+}
+
+fn squeeze_whitespace(text: &str) -> String {
+    let mut lines: Vec<String> = Vec::new();
+    for line in text.split('\n') {
+        let indent = if list_indent(line).is_some() { &line[..line.len() - line.trim_start().len()] } else { "" };
+        let squeezed = indent.to_string() + &line.split_whitespace().collect::<Vec<_>>().join(" ");
+        if squeezed.is_empty() && lines.last().is_some_and(|last| last.is_empty()) {
+            continue;
+        }
+        lines.push(squeezed);
+    }
+    lines.join("\n")
+}
+
+fn list_indent(line: &str) -> Option<usize> {
+    let item = line.trim_start();
+    let marker = if item.starts_with(['•', '◦', '▪', '‣', '-', '*', '–']) {
+        item.chars().next().map_or(0, char::len_utf8)
+    } else {
+        let digits = item.len() - item.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+        if !(1..=3).contains(&digits) || !item[digits..].starts_with(['.', ')']) {
+            return None;
+        }
+        digits + 1
+    };
+    let text = item[marker..].trim_start();
+    if text.is_empty() || !item[marker..].starts_with([' ', '\t']) {
+        return None;
+    }
+    Some(textwrap::core::display_width(&line[..line.len() - text.len()]))
+}
+
+fn strip_ansi(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            chars.by_ref().take_while(|c| !c.is_ascii_alphabetic()).for_each(drop);
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+fn trim_page(text: &str) -> &str {
+    let text = text.trim_end();
+    let content = text.len() - text.trim_start().len();
+    &text[text[..content].rfind('\n').map_or(0, |newline| newline + 1)..]
+}
+
+fn blank_marker(count: usize) -> String {
+    if count == 1 {
+        "[página en blanco]".to_string()
+    } else {
+        format!("[{} páginas en blanco]", count)
+    }
+}
+
+fn is_blank(section: &str, min_chars: usize) -> bool {
+    min_chars > 0 && section.chars().filter(|c| !c.is_whitespace()).take(min_chars).count() < min_chars
+}
+
+fn center_text(text: &str, width: usize, height: usize) -> String {
+    let lines: Vec<&str> = text.lines().collect();
+    let top = height.saturating_sub(lines.len()) / 2;
+    let centered = lines.iter().map(|line| {
+        let margin = width.saturating_sub(line.chars().count()) / 2;
+        format!("{}{}", " ".repeat(margin), line)
+    });
+    std::iter::repeat_n(String::new(), top).chain(centered).collect::<Vec<_>>().join("\n")
+}
+
+fn advance_past(section: &str, cursor: &mut usize, line: &str) -> usize {
+    let mut first = None;
+    for word in line.split_whitespace() {
+        let word = word.strip_suffix('-').filter(|stem| !stem.is_empty()).unwrap_or(word);
+        if let Some(position) = section[*cursor..].find(word) {
+            first.get_or_insert(*cursor + position);
+            *cursor += position + word.len();
+        }
+    }
+    first.unwrap_or(*cursor)
+}
+
+fn next_word(text: &str, offset: usize) -> usize {
+    let rest = &text[offset..];
+    let word_end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+    rest[word_end..].find(|c: char| !c.is_whitespace()).map_or(offset, |next| offset + word_end + next)
+}
+
+fn previous_word(text: &str, offset: usize) -> usize {
+    let before = text[..offset].trim_end();
+    before.rfind(char::is_whitespace).map_or(0, |space| space + before[space..].chars().next().map_or(1, char::len_utf8))
+}
+
+fn page_fraction(page: usize, total: usize) -> f32 {
+    let total = total.max(1);
+    page.saturating_add(1).min(total) as f32 / total as f32
+}
+
+fn progress_fill(fraction: f32) -> usize {
+    if fraction.is_nan() {
+        return 0;
+    }
+    ((fraction.clamp(0.0, 1.0) * PROGRESS_WIDTH as f32).round() as usize).min(PROGRESS_WIDTH)
+}
+
+fn scroll_overlay(top: usize, key: KeyCode, lines: usize, height: usize) -> Option<usize> {
+    let last = lines.saturating_sub(height);
+    let top = match key {
+        KeyCode::Down | KeyCode::Char('j') | KeyCode::Enter => top + 1,
+        KeyCode::Up | KeyCode::Char('k') => top.saturating_sub(1),
+        KeyCode::PageDown | KeyCode::Char(' ') => top + height,
+        KeyCode::PageUp | KeyCode::Char('b') => top.saturating_sub(height),
+        KeyCode::Home | KeyCode::Char('g') => 0,
+        KeyCode::End | KeyCode::Char('G') => last,
+        _ => return None,
+    };
+    Some(top.min(last))
+}
+
+fn zoomed_width(available: u16, wrap_width: u16) -> u16 {
+    if wrap_width == 0 {
+        available
+    } else {
+        (wrap_width + 6).min(available)
+    }
+}
+
+// Deja la terminal como estaba al salir de run(), también si una acción falla
+// con ? o hay un pánico a mitad de una orden externa.
+struct Screen;
+
+impl Screen {
+    fn enter() -> std::io::Result<Screen> {
+        terminal::enable_raw_mode()?;
+        let screen = Screen;
+        execute!(stdout(), terminal::EnterAlternateScreen, EnableMouseCapture, EnableBracketedPaste)?;
+        Ok(screen)
+    }
+}
+
+impl Drop for Screen {
+    fn drop(&mut self) {
+        let _ = terminal::disable_raw_mode();
+        let _ = execute!(stdout(), DisableBracketedPaste, DisableMouseCapture, terminal::LeaveAlternateScreen);
+    }
+}
+
+fn restore_title() {
+    if TITLE_SET.swap(false, Ordering::SeqCst) {
+        print!("\x1b]2;\x07\x1b[23;0t");
+        let _ = stdout().flush();
+    }
+}
+
+fn display_name(path: &str) -> String {
+    Path::new(path)
+        .file_name()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_string()
+}
+
+fn styled_line(line: &str, spans: &[(Range<usize>, ContentStyle)], base: ContentStyle) -> String {
+    let mut out = String::new();
+    let mut position = 0;
+    for (range, style) in spans {
+        if range.start < position || !line.is_char_boundary(range.start) || !line.is_char_boundary(range.end) {
+            continue;
+        }
+        let mut style = *style;
+        if style.background_color.is_none() {
+            style.background_color = base.background_color;
+        }
+        if range.start > position {
+            out.push_str(&base.apply(&line[position..range.start]).to_string());
+        }
+        out.push_str(&style.apply(&line[range.clone()]).to_string());
+        position = range.end;
+    }
+    out.push_str(&base.apply(&line[position..]).to_string());
+    out
+}
+
+fn page_separator(style: &str, index: usize, pdf_page: usize, width: usize) -> Option<String> {
+    match style {
+        "ff" => Some("\x0C".to_string()),
+        "page" => Some(format!("--- Página {} (PDF {}) ---", index + 1, pdf_page + 1)),
+        "none" => None,
+        _ => {
+            let label = format!(" {} · PDF {} ", index + 1, pdf_page + 1);
+            let side = width.saturating_sub(label.chars().count()) / 2;
+            Some(format!("{}{}{}", "─".repeat(side.max(3)), label, "─".repeat(side.max(3))))
+        }
+    }
+}
+
+fn print_pages(pdf_path: &str, width: Option<u16>, separator: &str, extractor: &str, encoding: &str, columns: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let pages = paginate(pdf_path, width, extractor, encoding, columns)?;
+    let rule_width = (width.unwrap_or_else(|| terminal::size().map_or(80, |(width, _)| width)) as usize).saturating_sub(6);
+    let mut out = stdout().lock();
+    let written = pages.iter().enumerate().try_for_each(|(index, page)| {
+        if let Some(line) = page_separator(separator, index, page.pdf_page, rule_width).filter(|_| index > 0 || separator != "ff") {
+            writeln!(out, "{}", line)?;
+        }
+        writeln!(out, "{}", page.text)
+    });
+    match written.and_then(|()| out.flush()) {
+        Err(e) if e.kind() != std::io::ErrorKind::BrokenPipe => Err(e.into()),
+        _ => Ok(()),
+    }
+}
+
+#[derive(Serialize)]
+struct PageDump<'a> {
+    index: usize,
+    pdf_page: usize,
+    text: &'a str,
+    char_count: usize,
+    line_count: usize,
+}
+
+fn paginate(pdf_path: &str, width: Option<u16>, extractor: &str, encoding: &str, columns: &str) -> Result<Vec<Page>, Box<dyn std::error::Error>> {
+    let full_text = PdfViewer::load_document(pdf_path, extractor, encoding, columns)?.text;
+    if full_text.trim().is_empty() {
+        return Err(Failure::new(ExitCode::NoText, "El PDF no tiene texto extraíble").into());
+    }
+    let (terminal_width, terminal_height) = terminal::size().unwrap_or((80, 24));
+    let prefs = Prefs::load();
+    let options = SplitOptions {
+        anchor: 0,
+        min_chars: if prefs.skip_blank { prefs.blank_threshold } else { 0 },
+        squeeze: prefs.squeeze_whitespace,
+        tables: prefs.preserve_tables,
+        direction: prefs.detect_direction,
+        code: prefs.highlight_code,
+        footnotes: prefs.move_footnotes,
+        hyphenate: hyphenate::language(&prefs.hyphenate),
+        breaks: breaks::policy(&prefs.line_break),
+        blank_marker: prefs.blank_pages == "marker",
+    };
+    Ok(PdfViewer::split_into_pages_with(&full_text, width.unwrap_or(terminal_width), terminal_height, options))
+}
+
+fn dump_pages(pdf_path: &str, width: Option<u16>, extractor: &str, encoding: &str, columns: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let pages = paginate(pdf_path, width, extractor, encoding, columns)?;
+    let dump: Vec<PageDump> = pages.iter()
+        .enumerate()
+        .map(|(index, page)| PageDump {
+            index,
+            pdf_page: page.pdf_page + 1,
+            text: &page.text,
+            char_count: page.text.chars().count(),
+            line_count: page.text.lines().count(),
+        })
+        .collect();
+    
+    let json = if stdout().is_terminal() {
+        serde_json::to_string_pretty(&dump)?
+    } else {
+        serde_json::to_string(&dump)?
+    };
+    println!("{}", json);
+    Ok(())
+}
+
+#[derive(Serialize)]
+struct DocumentInfo {
+    file: String,
+    title: Option<String>,
+    author: Option<String>,
+    pdf_pages: usize,
+    chars: usize,
+    words: usize,
+    image_only: bool,
+}
+
+fn print_info(pdf_path: &str, json: bool, extractor: &str, encoding: &str, columns: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let LoadedDocument { doc, text, .. } = PdfViewer::load_document(pdf_path, extractor, encoding, columns)?;
+    let pdf_pages = doc.get_pages().len();
+    let info = DocumentInfo {
+        file: display_name(pdf_path),
+        title: links::info_field(&doc, b"Title"),
+        author: links::info_field(&doc, b"Author"),
+        pdf_pages,
+        chars: text.chars().count(),
+        words: text.split_whitespace().count(),
+        image_only: text.trim().is_empty() && (0..pdf_pages).any(|page| !images::page_images(&doc, page).is_empty()),
+    };
+    if json {
+        println!("{}", serde_json::to_string_pretty(&info)?);
+        return Ok(());
+    }
+    let rows = [
+        ("Archivo", info.file),
+        ("Título", info.title.unwrap_or_else(|| "—".to_string())),
+        ("Autor", info.author.unwrap_or_else(|| "—".to_string())),
+        ("Páginas PDF", info.pdf_pages.to_string()),
+        ("Caracteres", info.chars.to_string()),
+        ("Palabras", info.words.to_string()),
+        ("Solo imágenes", if info.image_only { "sí" } else { "no" }.to_string()),
+    ];
+    for (key, value) in rows {
+        println!("{:<14} {}", format!("{}:", key), value);
+    }
+    Ok(())
+}
+
+fn export_highlights(pdf_path: &str, extractor: &str, encoding: &str, columns: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let full_text = PdfViewer::load_document(pdf_path, extractor, encoding, columns)?.text;
+    if full_text.trim().is_empty() {
+        return Err(Failure::new(ExitCode::NoText, "El PDF no tiene texto extraíble").into());
+    }
+    let notes = Notes::load(pdf_path);
+    if let Some(error) = notes.error() {
+        return Err(error.into());
+    }
+    for highlight in notes.highlights {
+        let passage = full_text.get(highlight.start..highlight.end.min(full_text.len())).unwrap_or("");
+        println!("pág. {} [{}]", highlight.pdf_page, highlight.color);
+        println!("{}\n", passage.split_whitespace().collect::<Vec<_>>().join(" "));
+    }
+    Ok(())
+}
+
+pub fn run_cli() -> Result<(), Box<dyn std::error::Error>> {
+    let cli = cli::parse();
+    if let Some(path) = &cli.config {
+        if !Path::new(path).exists() {
+            eprintln!("❌ No existe el archivo de configuración: {}", path);
+            ExitCode::NotFound.exit();
+        }
+        if let Err(e) = Prefs::use_file(Path::new(path)) {
+            eprintln!("❌ {}", e);
+            ExitCode::Usage.exit();
+        }
+    } else if cli.no_config {
+        Prefs::use_defaults();
+    }
+    let keymap_name = cli.keymap.clone().unwrap_or_else(|| Prefs::load().keymap);
+    let Some(keymap) = Keymap::named(&keymap_name) else {
+        eprintln!("❌ Perfil de teclas desconocido: {} (disponibles: {})", keymap_name, action::KEYMAPS.join(", "));
+        ExitCode::Usage.exit();
+    };
+    if cli.dump_keys {
+        action::dump_keys(keymap);
+        return Ok(());
+    }
+    let session = cli.session.as_deref().map(|name| Session::load(name).unwrap_or_else(|e| {
+        eprintln!("❌ {}", e);
+        ExitCode::NotFound.exit();
+    }));
+    let restored = session.as_ref().map(|session| &session.document);
+    if let Some(document) = restored.filter(|document| !Path::new(&document.path).exists()) {
+        eprintln!("❌ No existe el documento de la sesión: {}", document.path);
+        ExitCode::NotFound.exit();
+    }
+    let mut skipped = Vec::new();
+    let compare = match restored {
+        Some(document) => document.compare.clone().filter(|path| {
+            let exists = Path::new(path).exists();
+            if !exists {
+                skipped.push(format!("{} (no existe)", path));
+            }
+            exists
+        }),
+        None => cli.compare.clone(),
+    };
+
+    let Some(pdf_path) = restored.map(|document| document.path.as_str()).or(cli.file.as_deref()) else {
+        println!("{}", "PDF Viewer TUI".bold().blue());
+        println!("  ← → h l  : Cambiar páginas");
+        println!("  Home/End : Primera/Última página");
+        println!("  Tab Enter: Seguir enlaces");
+        println!("  q ESC    : Salir");
+        println!("  r        : Refrescar");
+        println!("  ?        : Ayuda");
+        ExitCode::Usage.exit();
+    };
+
+    if cli.diff && compare.is_none() {
+        eprintln!("❌ --diff necesita dos PDFs: viewerpdf --diff viejo.pdf nuevo.pdf");
+        ExitCode::Usage.exit();
+    }
+
+    if let Some(missing) = std::iter::once(pdf_path).chain(compare.as_deref()).find(|path| !Path::new(path).exists()) {
+        eprintln!("❌ No existe el archivo: {}", missing);
+        ExitCode::NotFound.exit();
+    }
+
+    if cli.export_highlights {
+        if let Err(e) = export_highlights(pdf_path, &cli.extractor, &cli.encoding, &cli.columns) {
+            eprintln!("❌ Error al cargar PDF: {}", e);
+            Failure::code_of(e.as_ref()).exit();
+        }
+        return Ok(());
+    }
+
+    if cli.info {
+        if let Err(e) = print_info(pdf_path, cli.json, &cli.extractor, &cli.encoding, &cli.columns) {
+            eprintln!("❌ Error al cargar PDF: {}", e);
+            Failure::code_of(e.as_ref()).exit();
+        }
+        return Ok(());
+    }
+
+    if cli.dump_pages {
+        if let Err(e) = dump_pages(pdf_path, cli.width, &cli.extractor, &cli.encoding, &cli.columns) {
+            eprintln!("❌ Error al cargar PDF: {}", e);
+            Failure::code_of(e.as_ref()).exit();
+        }
+        return Ok(());
+    }
+
+    if cli.pager || (!cli.tui && !stdout().is_terminal() && cli.screenshot.is_none()) {
+        if let Err(e) = print_pages(pdf_path, cli.width, &cli.page_sep, &cli.extractor, &cli.encoding, &cli.columns) {
+            eprintln!("❌ Error al cargar PDF: {}", e);
+            Failure::code_of(e.as_ref()).exit();
+        }
+        return Ok(());
+    }
+
+    match PdfViewer::new(pdf_path, &cli.extractor, &cli.encoding, &cli.columns) {
+        Ok(mut viewer) => {
+            viewer.compare_path = compare;
+            if let Some(session) = &session {
+                viewer.restore_session(session, &skipped);
+            }
+            viewer.keep_output = cli.keep_output;
+            viewer.keymap = keymap;
+            viewer.idle_dim = cli.idle_dim_secs.filter(|secs| *secs > 0).map(Duration::from_secs);
+            if cli.bell_on_turn {
+                viewer.prefs.bell = "turn".to_string();
+            }
+            if cli.verbose {
+                for line in viewer.timings.lines(viewer.doc.get_pages().len()) {
+                    eprintln!("{}", line);
+                }
+            }
+            if cli.cols > 1 {
+                viewer.cols = cli.cols;
+                viewer.reflow();
+            }
+            viewer.apply_sidecar();
+            if let Some(other) = viewer.compare_path.clone().filter(|_| cli.diff) {
+                if let Err(e) = viewer.load_diff(&other) {
+                    eprintln!("❌ Error al cargar {} para comparar: {}", other, e);
+                    Failure::code_of(e.as_ref()).exit();
+                }
+                viewer.toggle_diff();
+            }
+            viewer.open_at(cli.goto, cli.search.clone());
+            viewer.prefs.screenshot_ansi |= cli.screenshot_ansi;
+            if let Some(path) = &cli.screenshot {
+                if let Err(e) = viewer.save_screenshot(path) {
+                    eprintln!("❌ No se pudo guardar la captura en {}: {}", path, e);
+                    ExitCode::Write.exit();
+                }
+                return Ok(());
+            }
+            let hook = std::panic::take_hook();
+            std::panic::set_hook(Box::new(move |info| {
+                restore_title();
+                hook(info);
+            }));
+            let result = viewer.run();
+            restore_title();
+            result?;
+        }
+        Err(e) => {
+            eprintln!("❌ Error al cargar PDF: {}", e);
+            eprintln!("\n💡 Sugerencias:");
+            eprintln!("• Verifica que el archivo sea un PDF válido");
+            eprintln!("• Algunos PDFs con imágenes pueden no mostrar texto");
+            eprintln!("• Prueba con un PDF que contenga texto seleccionable");
+            Failure::code_of(e.as_ref()).exit();
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use testdir::TestDir;
+
+    #[test]
+    fn leading_bom_is_removed_before_pagination() {
+        let text = extract::normalize("\u{feff}Hola mundo\nsegunda l\u{fffd}nea", "utf8");
+        let pages = PdfViewer::split_into_pages_with(&text, 80, 24, SplitOptions::default());
+        assert_eq!(pages[0].offset, 0);
+        assert!(pages[0].text.starts_with("Hola mundo"));
+        assert!(pages[0].text.contains("segunda l␣nea"));
+    }
+
+    #[test]
+    fn reading_position_survives_resizing() {
+        let text = (1..=40)
+            .map(|n| format!("Frase número {} de un párrafo bastante largo que ocupa varias líneas al envolverse.", n))
+            .collect::<Vec<_>>()
+            .join(" ");
+        let pages = PdfViewer::split_into_pages_with(&text, 80, 20, SplitOptions::default());
+        let anchor = pages[2].offset;
+        let first_words: String = text[anchor..].split_whitespace().take(4).collect::<Vec<_>>().join(" ");
+        let mut offset = anchor;
+        for width in (40..=140).step_by(9) {
+            let pages = PdfViewer::split_into_pages_with(&text, width, 20, SplitOptions { anchor: offset, ..SplitOptions::default() });
+            let index = pages.partition_point(|page| page.offset <= offset) - 1;
+            let top: String = pages[index].text.split_whitespace().take(4).collect::<Vec<_>>().join(" ");
+            assert_eq!(top, first_words, "ancho {}", width);
+            offset = pages[index].offset;
+        }
+    }
+
+    #[test]
+    fn top_line_and_cursor_survive_a_width_change() {
+        let text = (1..=60)
+            .map(|n| format!("Renglón {} {}", n, "palabra ".repeat(n % 7 + 6).trim_end()))
+            .collect::<Vec<_>>()
+            .join("\n");
+        for raw in [false, true] {
+            let mut document = viewer(&text, 100, 20);
+            if raw {
+                document.toggle_raw();
+            }
+            (0..3).for_each(|_| { document.next_page(); });
+            document.prefs.cursor_line = true;
+            document.cursor_row = 2;
+            let top = document.page_range(document.current_page).0;
+            let cursor = document.line_ranges(document.current_page).get(2).map(|(start, _)| *start);
+            for width in [140, 70, 100] {
+                document.terminal_width = width;
+                document.reflow();
+                assert_eq!(document.page_range(document.current_page).0, top, "ancho {} (raw: {})", width, raw);
+                if !raw {
+                    assert_eq!(document.line_ranges(document.current_page).get(document.cursor_row).map(|(start, _)| *start), cursor);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn jump_list_survives_a_reflow() {
+        let text = (1..=60).map(|n| format!("Línea {} del documento.", n)).collect::<Vec<_>>().join("\n");
+        let mut document = viewer(&text, 60, 16);
+        (0..2).for_each(|_| { document.next_page(); });
+        let before = document.page_text(document.current_page).lines().next().unwrap_or("").to_string();
+        document.jump_to(document.total_pages - 1);
+        document.toggle_minimap();
+        document.jump_back();
+        assert_eq!(document.page_text(document.current_page).lines().next(), Some(before.as_str()));
+    }
+
+    #[test]
+    fn first_and_last_page_keys_record_a_jump() {
+        let text = (1..=60).map(|n| format!("Línea {} del documento.", n)).collect::<Vec<_>>().join("\n");
+        let mut document = viewer(&text, 60, 16);
+        (0..2).for_each(|_| { document.next_page(); });
+        document.perform(Action::LastPage, None).unwrap();
+        document.jump_back();
+        assert_eq!(document.current_page, 2);
+        document.perform(Action::FirstPage, None).unwrap();
+        document.jump_back();
+        assert_eq!(document.current_page, 2);
+    }
+
+    #[test]
+    fn chapter_keys_fall_back_to_pdf_pages() {
+        let long = (1..=30).map(|n| format!("Línea {}", n)).collect::<Vec<_>>().join("\n");
+        let mut document = viewer(&format!("Portada\x0C{}\x0CEpílogo", long), 60, 16);
+        document.jump_to_chapter(true);
+        assert_eq!((document.current_page, document.status.as_deref()), (1, Some("§ Página 2 del PDF")));
+        document.next_page();
+        document.jump_to_chapter(false);
+        assert_eq!(document.current_page, 1);
+        document.jump_to_chapter(true);
+        assert_eq!(document.pages[document.current_page].pdf_page, 2);
+        document.jump_back();
+        assert_eq!(document.current_page, 1);
+    }
+
+    #[test]
+    fn offsets_map_to_page_and_row_across_boundaries() {
+        let text = (1..=30).map(|n| format!("Línea {} del texto.", n)).collect::<Vec<_>>().join("\n");
+        let document = viewer(&text, 60, 16);
+        assert_eq!(document.page_for_offset(0), (0, 0));
+        for page in 1..document.pages.len() {
+            let start = document.pages[page].offset;
+            let rows = document.page_text(page - 1).split('\n').count();
+            assert_eq!(document.page_for_offset(start), (page, 0));
+            assert_eq!(document.page_for_offset(start - 1), (page - 1, rows - 1));
+            let second_line = text[start..].find("\n").unwrap() + start + 1;
+            assert_eq!(document.page_for_offset(second_line + 2), (page, 1));
+        }
+        let last = document.pages.len() - 1;
+        assert_eq!(document.page_for_offset(text.len() + 10).0, last);
+    }
+
+    #[test]
+    fn blank_extraction_yields_the_empty_state() {
+        assert!(PdfViewer::split_into_pages_with("", 80, 24, SplitOptions::default()).is_empty());
+        assert!(PdfViewer::split_into_pages_with("  \n\x0C\n\t\x0C", 80, 24, SplitOptions::default()).is_empty());
+
+        let notice = center_text("Vacío\nde verdad", 20, 6);
+        let lines: Vec<&str> = notice.split('\n').collect();
+        assert_eq!(lines, ["", "", "       Vacío", "     de verdad"]);
+    }
+
+    #[test]
+    fn pages_are_tagged_with_their_direction() {
+        let text = "The introduction is written in English prose.\x0C\
+                    שלום עולם, זהו פרק בעברית עם ציטוט (v1.2) קצר.\x0C\
+                    مرحبا بالعالم هذا فصل باللغة العربية\x0C\
+                    Back to English with a quote: «שלום».";
+        let tags = |options: SplitOptions| -> Vec<bool> {
+            PdfViewer::split_into_pages_with(text, 80, 24, options).iter().map(|page| page.rtl).collect()
+        };
+        assert_eq!(tags(SplitOptions { direction: true, ..SplitOptions::default() }), [false, true, true, false]);
+        assert_eq!(tags(SplitOptions::default()), [false; 4]);
+        assert_eq!(direction::visual("אבג (v1.2) דה"), "הד (v1.2) גבא");
+    }
+
+    fn describe_pages(pages: &[Page]) -> String {
+        pages.iter()
+            .enumerate()
+            .map(|(index, page)| format!("--- página {} (PDF p. {}, offset {}, {} caracteres)\n{}\n", index, page.pdf_page + 1, page.offset, page.chars, page.text))
+            .collect()
+    }
+
+    fn viewer(text: &str, width: u16, height: u16) -> PdfViewer {
+        Prefs::use_defaults();
+        PdfViewer::from_text("instantanea.pdf", Document::with_version("1.5"), text.to_string(), width, height, Prefs::default())
+    }
+
+    #[test]
+    fn pagination_snapshots() {
+        let prose = (1..=12)
+            .map(|n| format!("Frase {} de un texto de prueba que se envuelve en varias líneas.", n))
+            .collect::<Vec<_>>()
+            .join(" ");
+        let cases = [
+            ("paginas_vacias", String::new()),
+            ("paginas_saltos_de_pagina", format!("Primera página.\x0C\x0CTercera página, tras una vacía.\x0C{}", prose)),
+            ("paginas_palabra_larga", format!("Antes https://example.com/{} después", "segmento/".repeat(12))),
+            ("paginas_tabla", "Resultados del experimento, medidos en tres ejecuciones independientes.\n\nMétodo      Tiempo   Memoria   Aciertos\nBase          12.4       310      0.81\nPropuesto      9.8       295      0.86\nVariante A    10.1       402      0.84\n\nLa propuesta es más rápida sin perder precisión.".to_string()),
+            ("paginas_lista", "Pasos a seguir:\n• Instalar las dependencias del proyecto con el gestor de paquetes habitual.\n  - Comprobar que la versión del compilador es la correcta para este proyecto.\n• Compilar.\n10) Ejecutar las pruebas y revisar que todas terminan sin errores.".to_string()),
+            ("paginas_codigo", "Listing 3 muestra la función principal del programa, que lee la configuración y arranca.\n\n    fn main() {\n        let config = Config::load().expect(\"config.toml\");\n        run(&config);\n    }\n\nEl resto del capítulo explica cada paso en detalle.".to_string()),
+            ("paginas_cjk", "日本語のテキストは空白なしで折り返す必要があります。中文文本也没有空格，需要按宽度换行。".repeat(3)),
+        ];
+        for (name, text) in cases {
+            let options = SplitOptions { tables: true, code: true, ..SplitOptions::default() };
+            insta::assert_snapshot!(name, describe_pages(&PdfViewer::split_into_pages_with(&text, 40, 14, options)));
+        }
+    }
+
+    #[test]
+    fn default_extraction_is_unchanged_by_column_detection() {
+        let pdf = Path::new(env!("CARGO_MANIFEST_DIR")).join("doc.pdf");
+        let loaded = PdfViewer::load_document(pdf.to_str().unwrap(), "pdf-extract", "utf8", "1").unwrap();
+        insta::assert_snapshot!("extraccion_por_defecto", loaded.text);
+    }
+
+    #[test]
+    fn render_snapshots() {
+        let text = (1..=30)
+            .map(|n| format!("Línea {} del documento de prueba.", n))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let mut document = viewer(&text, 60, 16);
+        insta::assert_snapshot!("render_primera_pagina", strip_ansi(&document.render()));
+
+        document.next_page();
+        document.status = Some("Aviso de prueba".to_string());
+        insta::assert_snapshot!("render_segunda_pagina", strip_ansi(&document.render()));
+
+        document.cols = 2;
+        document.reflow();
+        insta::assert_snapshot!("render_dos_paginas", strip_ansi(&document.render()));
+
+        document.split_view(false);
+        (0..4).for_each(|_| { document.next_page(); });
+        insta::assert_snapshot!("render_dividido", strip_ansi(&document.render()));
+        document.switch_pane();
+        document.close_pane();
+        assert!(document.page_text(document.current_page).starts_with("Línea 9 "));
+        assert_eq!(document.jump_list.last().map(|offset| document.full_text[*offset..].starts_with("Línea 17 ")), Some(true));
+
+        insta::assert_snapshot!("render_vacio", strip_ansi(&viewer("", 60, 16).render()));
+
+        let table = "Tiempos por método:\n\nMétodo      Ejecución 1   Ejecución 2   Ejecución 3   Ejecución 4   Media\nBase               12.4          12.9          12.1          12.6    12.5\nPropuesto           9.8          10.2           9.6           9.9     9.9";
+        let mut document = viewer(table, 60, 16);
+        document.scroll_horizontally(true);
+        insta::assert_snapshot!("render_desplazado", strip_ansi(&document.render()));
+    }
+
+    #[test]
+    fn custom_status_line_replaces_the_progress_bar() {
+        let text = (1..=30).map(|n| format!("Línea {} del informe.", n)).collect::<Vec<_>>().join("\n");
+        let prefs = Prefs { statusline: "{name} · {page}/{pages}%={percent}% · {zom}".to_string(), ..Prefs::default() };
+        let mut document = PdfViewer::from_text("informe.pdf", Document::with_version("1.5"), text, 40, 16, prefs);
+        assert_eq!(document.status.as_deref(), Some("statusline: marcadores desconocidos {zom}"));
+        document.next_page();
+        let frame = strip_ansi(&document.render());
+        let footer = frame.lines().last().unwrap();
+        assert_eq!(footer.chars().count(), 40);
+        assert!(footer.starts_with("informe.pdf · 2/") && footer.ends_with("% · {zom}"));
+        assert!(!frame.contains("Progreso"));
+    }
+
+    #[test]
+    fn pressing_past_the_last_page_shows_a_transient_message() {
+        let mut document = viewer("Única página.", 40, 16);
+        assert!(!document.next_page());
+        assert!(strip_ansi(&document.render()).contains("Ya estás en la última página"));
+        assert!(!document.expire_transient());
+        document.transient.as_mut().unwrap().1 -= TRANSIENT;
+        assert!(document.expire_transient());
+        assert!(!strip_ansi(&document.render()).contains("última página"));
+
+        let mut document = viewer("Primera.\x0CSegunda.", 40, 16);
+        document.prefs.page_boundary = "wrap".to_string();
+        assert!(document.prev_page());
+        assert_eq!((document.current_page, document.transient.as_ref().map(|(message, _, _)| message.as_str())), (1, Some("↺ Vuelta al final")));
+        assert!(document.next_page());
+        assert_eq!(document.current_page, 0);
+    }
+
+    #[test]
+    fn continuous_mode_scrolls_by_line_across_pages() {
+        let text = (1..=30).map(|n| format!("Línea {} del documento de prueba.", n)).collect::<Vec<_>>().join("\n");
+        let mut document = viewer(&text, 60, 16);
+        document.next_page();
+        document.set_continuous(true);
+        assert_eq!(document.top_line(), 8);
+        document.scroll_lines(6);
+        assert_eq!((document.current_page, document.scroll_row()), (1, 6));
+        let frame = strip_ansi(&document.render());
+        let rows: Vec<&str> = frame.lines().filter(|line| line.starts_with('│')).collect();
+        assert!(rows[0].contains("Línea 15 ") && rows[7].contains("Línea 22 "));
+        assert!(frame.contains("Progreso (líneas)") && frame.contains("[L]"));
+
+        document.prefs.scroll_frame = false;
+        insta::assert_snapshot!("render_continuo", strip_ansi(&document.render()));
+
+        document.terminal_width = 40;
+        document.reflow();
+        assert!(document.page_text(document.current_page).split('\n').nth(document.scroll_row()).unwrap().starts_with("Línea 15 "));
+        document.scroll_lines(100);
+        assert!(!document.scroll_lines(1));
+        let top = document.scroll_row();
+        assert!(document.page_text(document.current_page).split('\n').nth(top).unwrap().starts_with("Línea 23 "));
+        document.set_continuous(false);
+        assert!(document.page_text(document.current_page).contains("Línea 23 "));
+    }
+
+    #[test]
+    fn less_d_and_u_scroll_half_a_screen() {
+        let text = (1..=60).map(|n| format!("Línea {} del documento de prueba.", n)).collect::<Vec<_>>().join("\n");
+        let mut document = viewer(&text, 60, 20);
+        let state = KeyState { cursor_line: false, continuous: true, minimap: false, keymap: Keymap::Less, help_key: None };
+        let key = |c| Action::from_key(event::KeyEvent::new(KeyCode::Char(c), event::KeyModifiers::NONE), &state);
+        assert_eq!((key('d'), key('u')), (Some(Action::HalfPageDown), Some(Action::HalfPageUp)));
+
+        document.perform(Action::HalfPageDown, None).unwrap();
+        assert_eq!(document.current_page, 1);
+        document.perform(Action::HalfPageUp, None).unwrap();
+        assert_eq!(document.current_page, 0);
+
+        document.set_continuous(true);
+        let half = (document.page_height() as usize - 9) / 2;
+        document.perform(Action::HalfPageDown, Some(3)).unwrap();
+        assert_eq!(document.top_line(), 3 * half);
+        document.perform(Action::HalfPageUp, None).unwrap();
+        assert_eq!(document.top_line(), 2 * half);
+    }
+
+    #[test]
+    fn progress_fill_stays_in_bounds_for_any_page_count() {
+        for total in [0, 1, 19, 20, 21, 1_000_003, usize::MAX] {
+            for page in [0, 1, total / 2, total.saturating_sub(2), total.saturating_sub(1), total, usize::MAX] {
+                let fraction = page_fraction(page, total);
+                assert!((0.0..=1.0).contains(&fraction), "{}/{}", page, total);
+                assert!(progress_fill(fraction) <= PROGRESS_WIDTH);
+            }
+            assert_eq!(progress_fill(page_fraction(total.saturating_sub(1), total)), PROGRESS_WIDTH);
+        }
+        assert_eq!(progress_fill(page_fraction(0, 21)), 1);
+        assert_eq!(progress_fill(page_fraction(9, 20)), 10);
+        assert_eq!(progress_fill(f32::NAN), 0);
+    }
+
+    #[test]
+    fn progress_bar_fills_monotonically_and_ends_full() {
+        for total in [3, 7, 13] {
+            let fills: Vec<usize> = (0..total).map(|page| progress_fill(page_fraction(page, total))).collect();
+            assert!(fills.windows(2).all(|pair| pair[0] <= pair[1]), "{:?}", fills);
+            assert_eq!(fills.last(), Some(&PROGRESS_WIDTH));
+            assert_eq!(format!("{:.1}", page_fraction(total - 1, total) * 100.0), "100.0");
+        }
+        let text = (1..=40).map(|n| format!("Línea {}.", n)).collect::<Vec<_>>().join("\n");
+        let mut document = viewer(&text, 40, 16);
+        document.perform(Action::LastPage, None).unwrap();
+        assert!(strip_ansi(&document.render()).contains(&format!("[{}] 100.0%", "█".repeat(PROGRESS_WIDTH))));
+    }
+
+    #[test]
+    fn repeated_search_wraps_and_counts_matches() {
+        let text = (1..=20).map(|n| if n % 7 == 0 { format!("Línea {} con la clave.", n) } else { format!("Línea {}.", n) }).collect::<Vec<_>>().join("\n");
+        let mut document = viewer(&text, 40, 16);
+        document.set_search("clave".to_string(), true);
+        document.repeat_search(false);
+        assert_eq!(document.status.as_deref(), Some("/clave [1/2]"));
+        document.repeat_search(false);
+        assert_eq!(document.status.as_deref(), Some("/clave [2/2]"));
+        document.repeat_search(false);
+        assert_eq!(document.status.as_deref(), Some("/clave [1/2] · la búsqueda volvió al principio"));
+        document.repeat_search(true);
+        assert_eq!(document.status.as_deref(), Some("/clave [2/2] · la búsqueda volvió al final"));
+
+        document.prefs.search_wrap = false;
+        document.repeat_search(false);
+        assert_eq!(document.status.as_deref(), Some("No hay más coincidencias de \"clave\""));
+        assert_eq!(document.statusline_value("matches").as_deref(), Some("2/2"));
+
+        document.set_search("clav".to_string(), true);
+        assert_eq!(document.statusline_value("matches").as_deref(), Some("2/2"));
+        let (setting, value) = settings::parse("wholeword=on").unwrap();
+        document.apply_setting(setting, value);
+        assert_eq!(document.statusline_value("matches").as_deref(), Some("0"));
+    }
+
+    #[test]
+    fn end_marker_appears_on_the_last_page_only() {
+        let text = (1..=20).map(|n| format!("Línea {}.", n)).collect::<Vec<_>>().join("\n");
+        let mut document = viewer(&text, 40, 16);
+        let ends = |document: &PdfViewer| strip_ansi(&document.render()).lines().last().unwrap().ends_with("(END)");
+        assert!(!ends(&document));
+        document.perform(Action::LastPage, None).unwrap();
+        assert!(ends(&document));
+        document.prev_page();
+        assert!(!ends(&document));
+
+        document.set_continuous(true);
+        document.scroll_lines(3);
+        assert!(!ends(&document));
+        document.scroll_lines(20);
+        assert!(ends(&document));
+    }
+
+    #[test]
+    fn narrower_wrap_width_is_centered() {
+        let text = "Una frase lo bastante larga como para que el ancho del texto decida dónde se corta cada línea.";
+        let mut document = viewer(text, 60, 16);
+        document.prefs.wrap_width = 30;
+        document.reflow();
+        assert_eq!((document.text_width(), document.margin()), (36, 12));
+        let frame = strip_ansi(&document.render());
+        let rows: Vec<&str> = frame.lines().filter(|line| line.trim_start().starts_with('│')).collect();
+        assert!(rows.iter().all(|row| row.starts_with(&" ".repeat(12)) && row.chars().count() == 46));
+        assert_eq!(document.page_text(0).split('\n').next(), Some("Una frase lo bastante larga"));
+        assert_eq!(zoomed_width(60, 80), 60);
+    }
+
+    #[test]
+    fn short_pages_shrink_the_frame_when_enabled() {
+        let text = format!("{}\x0CFin.", (1..=12).map(|n| format!("Línea {}.", n)).collect::<Vec<_>>().join("\n"));
+        let box_rows = |document: &PdfViewer| strip_ansi(&document.render()).lines().filter(|line| line.starts_with('│')).count();
+        let mut document = viewer(&text, 40, 16);
+        document.prefs.shrink_frame = true;
+        assert_eq!(box_rows(&document), 8);
+        document.next_page();
+        assert_eq!(box_rows(&document), 5);
+        document.next_page();
+        assert_eq!(box_rows(&document), 2);
+        assert_eq!(document.frame_layout().controls, document.frame_layout().header.end + 6);
+        document.prefs.shrink_frame = false;
+        assert_eq!(box_rows(&document), 8);
+    }
+
+    #[test]
+    fn ruler_dims_other_lines_and_remembers_its_row_per_page() {
+        let text = (1..=30).map(|n| format!("Línea {}", n)).collect::<Vec<_>>().join("\n");
+        let mut document = viewer(&text, 40, 16);
+        document.prefs.cursor_line = true;
+        let plain = document.render();
+        document.prefs.ruler = true;
+        let ruled = document.render();
+        assert_ne!(plain, ruled);
+        assert!(strip_ansi(&ruled).contains("C U]") && !strip_ansi(&plain).contains(" U]"));
+
+        document.cursor_row = 3;
+        document.next_page();
+        document.sync_ruler(0, 3);
+        document.cursor_row = 1;
+        document.prev_page();
+        document.sync_ruler(1, 1);
+        assert_eq!(document.cursor_row, 3);
+        document.next_page();
+        document.sync_ruler(0, 3);
+        assert_eq!(document.cursor_row, 1);
+
+        let mut document = viewer(&text, 40, 16);
+        document.toggle_ruler();
+        assert!(document.prefs.cursor_line);
+        document.toggle_ruler();
+        assert!(!document.prefs.cursor_line);
+        document.toggle_cursor_line();
+        document.toggle_ruler();
+        document.toggle_ruler();
+        assert!(document.prefs.cursor_line && !document.prefs.ruler);
+    }
+
+    #[test]
+    fn visual_selection_copies_the_document_text() {
+        let text = "Primera frase de prueba bastante larga para que se parta en varias líneas.\nSegunda línea.";
+        let mut document = viewer(text, 40, 16);
+        let page = document.page_text(0).into_owned();
+        assert!(page.starts_with("Primera frase de prueba bastante\nlarga"));
+        let start = page.find("bastante").unwrap();
+        let end = page.find("Segunda").unwrap() + "Segunda".len() - 1;
+        document.selection = Some((start, end));
+        assert_eq!(document.selected_text(&page), "bastante larga para que se parta en varias líneas.\nSegunda");
+
+        document.selection = Some((page.find("larga").unwrap() + 1, page.find("larga").unwrap() + 2));
+        assert_eq!(document.selected_text(&page), "ar");
+    }
+
+    #[test]
+    fn hyphens_stay_out_of_the_source_text() {
+        let text = "Las extraordinariamente complicadas interpretaciones contemporáneas.";
+        let mut document = viewer(text, 26, 16);
+        document.prefs.hyphenate = "es".to_string();
+        document.reflow();
+        assert!(document.page_text(0).lines().any(|line| line.ends_with('-')));
+        assert_eq!(document.source_text(0), text);
+    }
+
+    #[test]
+    fn goto_counts_pdf_pages_not_screen_pages() {
+        let long = (1..=30).map(|n| format!("Línea {}", n)).collect::<Vec<_>>().join("\n");
+        let mut document = viewer(&format!("{}\x0CSegunda página\x0CTercera página", long), 40, 16);
+        assert!(document.total_pages > 3);
+        document.open_at(Some(3), None);
+        assert_eq!(document.page_text(document.current_page), "Tercera página");
+        document.open_at(Some(9), None);
+        assert_eq!(document.status.as_deref(), Some("La página 9 no existe; el PDF tiene 3"));
+    }
+
+    #[test]
+    fn highlights_cover_the_visual_selection() {
+        let text = "Primera frase de prueba bastante larga para que se parta en varias líneas.";
+        let mut document = viewer(text, 40, 16);
+        let dir = TestDir::new("seleccion");
+        let pdf = dir.path("seleccion.pdf");
+        document.notes = Notes::load(&pdf.to_string_lossy());
+        let page = document.page_text(0).into_owned();
+        document.selection = Some((page.find("bastante").unwrap(), page.find("para").unwrap() + 3));
+        document.add_highlight();
+        let highlight = &document.notes.highlights[0];
+        assert_eq!(&text[highlight.start..highlight.end], "bastante larga para");
+    }
+
+    #[test]
+    fn sidecar_settings_stay_out_of_the_global_config() {
+        let mut document = viewer("Texto", 40, 16);
+        let dir = TestDir::new("capa");
+        document.state.path = dir.path("capa.pdf").to_string_lossy().into_owned();
+        std::fs::write(sidecar::path(&document.state.path), "number = \"relative\"\n").unwrap();
+        document.apply_sidecar();
+        std::fs::remove_file(sidecar::path(&document.state.path)).unwrap();
+        assert_eq!(document.prefs.number, "relative");
+
+        document.cycle_numbers();
+        assert_eq!(document.prefs.number, "off");
+        assert!(document.status.as_deref().is_some_and(|status| status.contains("solo vale para esta sesión")));
+        document.status = None;
+        document.toggle_cursor_line();
+        assert_eq!(document.status, None);
+
+        let mut next = viewer("Otro documento", 40, 16);
+        document.hand_over(&mut next);
+        assert_eq!(next.prefs.number, Prefs::default().number);
+        assert!(next.prefs.cursor_line);
+        next.cycle_numbers();
+        assert_eq!(next.status, None);
+    }
+
+    #[test]
+    fn relative_numbers_count_from_the_cursor_line() {
+        let text = (1..=30).map(|n| format!("Línea {}", n)).collect::<Vec<_>>().join("\n");
+        let mut document = viewer(&text, 40, 16);
+        let gutters = |document: &PdfViewer| strip_ansi(&document.render())
+            .lines()
+            .filter(|line| line.starts_with('│'))
+            .take(4)
+            .map(|line| line.chars().skip(2).take(3).collect::<String>())
+            .collect::<Vec<_>>();
+        document.prefs.number = "absolute".to_string();
+        document.reflow();
+        assert_eq!(gutters(&document), [" 1 ", " 2 ", " 3 ", " 4 "]);
+        document.prefs.number = "relative".to_string();
+        document.prefs.cursor_line = true;
+        document.cursor_row = 2;
+        assert_eq!(gutters(&document), [" 2 ", " 1 ", "3  ", " 1 "]);
+        document.cursor_down();
+        assert_eq!(gutters(&document), [" 3 ", " 2 ", " 1 ", "4  "]);
+        assert!(strip_ansi(&document.render()).contains("│  3 Línea 1"));
+    }
+
+    #[test]
+    fn count_reports_matches_and_pages_without_moving() {
+        let text = (1..=30).map(|n| if n % 10 == 0 { format!("Línea {} con la clave y otra clave.", n) } else { format!("Línea {}.", n) }).collect::<Vec<_>>().join("\n");
+        let mut document = viewer(&text, 50, 16);
+        document.run_command("count clave").unwrap();
+        assert_eq!(document.status.as_deref(), Some("\"clave\": 6 coincidencias en 3 páginas"));
+        assert_eq!(document.current_page, 0);
+        document.run_command("count con la").unwrap();
+        assert_eq!(document.status.as_deref(), Some("\"con la\": 3 coincidencias en 3 páginas"));
+        document.run_command("count nada").unwrap();
+        assert_eq!(document.status.as_deref(), Some("\"nada\": sin coincidencias"));
+        document.run_command("count").unwrap();
+        assert!(document.status.as_deref().is_some_and(|status| status.starts_with("Uso: :count")));
+    }
+
+    #[test]
+    fn page_separators_name_the_viewer_and_pdf_page() {
+        assert_eq!(page_separator("rule", 2, 1, 20).as_deref(), Some("──── 3 · PDF 2 ────"));
+        assert_eq!(page_separator("page", 0, 0, 20).as_deref(), Some("--- Página 1 (PDF 1) ---"));
+        assert_eq!(page_separator("ff", 4, 3, 20).as_deref(), Some("\x0C"));
+        assert_eq!(page_separator("none", 4, 3, 20), None);
+    }
+
+    #[test]
+    fn macros_replay_actions_and_stop_on_failure() {
+        let text = (1..=60).map(|n| format!("Línea {}.", n)).collect::<Vec<_>>().join("\n");
+        let mut document = viewer(&text, 40, 16);
+        document.toggle_recording(Some('a'));
+        for action in [Action::NextPage, Action::NextPage] {
+            document.perform(action, None).unwrap();
+            document.record(action, None);
+        }
+        document.toggle_recording(None);
+        assert_eq!(document.status.as_deref(), Some("Macro @a grabada (2 pasos)"));
+        document.perform(Action::FirstPage, None).unwrap();
+        document.play_macro('a', 1).unwrap();
+        assert_eq!(document.current_page, 2);
+
+        document.play_macro('a', 10).unwrap();
+        assert_eq!(document.current_page, document.total_pages - 1);
+        assert!(document.status.as_deref().is_some_and(|status| status.starts_with("Macro @a detenida en el paso")));
+        document.play_macro('b', 1).unwrap();
+        assert_eq!(document.status.as_deref(), Some("La macro @b está vacía"));
+
+        document.perform(Action::FirstPage, None).unwrap();
+        document.toggle_recording(Some('s'));
+        document.start_search("Línea 30.".to_string(), true);
+        document.record(Action::SearchForward, None);
+        document.toggle_recording(None);
+        let found = document.current_page;
+        document.perform(Action::FirstPage, None).unwrap();
+        document.search = None;
+        document.toggle_recording(Some('c'));
+        document.play_macro('s', 1).unwrap();
+        document.toggle_recording(None);
+        assert_eq!(document.current_page, found);
+        assert_eq!(document.status.as_deref(), Some("Macro @c grabada (0 pasos)"));
+
+        let mut next = viewer("Otro documento", 40, 16);
+        document.hand_over(&mut next);
+        assert_eq!(next.macros.keys().collect::<String>(), "acs");
+    }
+
+    #[test]
+    fn palette_filters_actions_and_runs_the_chosen_one() {
+        let text = (1..=60).map(|n| format!("Línea {}.", n)).collect::<Vec<_>>().join("\n");
+        let mut document = viewer(&text, 40, 16);
+        assert_eq!(Action::ShowPalette.keys(document.keymap), ["Ctrl+P"]);
+
+        let entries = document.palette_entries();
+        assert!(entries.iter().all(|(action, _, _)| *action != Action::ShowPalette));
+        let zoom: Vec<Action> = PdfViewer::palette_matches(&entries, "zoom").iter().map(|(index, _, _)| entries[*index].0).collect();
+        assert!([Action::ZoomIn, Action::ZoomOut, Action::ZoomReset].iter().all(|action| zoom[..3].contains(action)));
+        assert!(PdfViewer::palette_matches(&entries, "qqqzzz").is_empty());
+        assert_eq!(PdfViewer::palette_matches(&entries, "").len(), entries.len());
+
+        let matches = PdfViewer::palette_matches(&entries, "next_page");
+        let (chosen, keys, _) = &entries[matches[0].0];
+        assert_eq!((*chosen, keys.contains('l')), (Action::NextPage, true));
+        document.perform(*chosen, None).unwrap();
+        assert_eq!(document.current_page, 1);
+    }
+
+    #[test]
+    fn blank_sections_are_skipped_or_collapsed_into_one_marker() {
+        let text = "Uno\x0C\x0C \n \x0C\n\x0CDos\x0C\x0CTres";
+        let pages = |blank_marker| PdfViewer::split_into_pages_with(text, 40, 20, SplitOptions { blank_marker, ..SplitOptions::default() })
+            .into_iter()
+            .map(|page| (page.text, page.pdf_page + 1))
+            .collect::<Vec<_>>();
+        let owned = |pages: &[(&str, usize)]| pages.iter().map(|(text, page)| (text.to_string(), *page)).collect::<Vec<_>>();
+        assert_eq!(pages(false), owned(&[("Uno", 1), ("Dos", 5), ("Tres", 7)]));
+        assert_eq!(pages(true), owned(&[("Uno", 1), ("[3 páginas en blanco]", 2), ("Dos", 5), ("[página en blanco]", 6), ("Tres", 7)]));
+
+        let mut document = viewer(text, 40, 20);
+        document.prefs.blank_pages = "marker".to_string();
+        document.reflow();
+        document.next_page();
+        assert_eq!((document.page_text(1).as_ref(), document.pages[1].pdf_page), ("[3 páginas en blanco]", 1));
+        document.prefs.cursor_line = true;
+        assert!(document.line_ranges(1).is_empty());
+        document.add_highlight();
+        assert!(document.status.as_deref().is_some_and(|status| status.starts_with("Esta página solo resume")));
+        assert!(document.notes.highlights.is_empty());
+        document.next_page();
+        assert_eq!(document.page_text(document.current_page), "Dos");
+    }
+
+    #[test]
+    fn blank_lines_at_either_end_of_a_page_are_dropped_alike() {
+        for squeeze in [false, true] {
+            let options = SplitOptions { squeeze, blank_marker: true, ..SplitOptions::default() };
+            let heights = |text: &str| PdfViewer::split_into_pages_with(text, 40, 20, options)
+                .iter()
+                .map(|page| (page.text.clone(), page.lines.clone().map(|lines| lines.len())))
+                .collect::<Vec<_>>();
+            let expected = [("Uno\nDos".to_string(), Some(2)), ("Tres".to_string(), Some(1))];
+            assert_eq!(heights("\n\n \nUno\nDos\x0CTres"), expected);
+            assert_eq!(heights("Uno\nDos\n \n\n\x0CTres"), expected);
+            assert_eq!(heights("\n\nUno\nDos\n\n\x0C\n\nTres\n\n"), expected);
+        }
+    }
+
+    #[test]
+    fn hooks_observe_page_changes_and_searches() {
+        struct Recorder(Rc<RefCell<Vec<String>>>);
+        impl Hooks for Recorder {
+            fn on_page_change(&mut self, page: usize, pdf_page: usize, total: usize) {
+                self.0.borrow_mut().push(format!("página {} (PDF {}) de {}", page, pdf_page, total));
+            }
+            fn on_search(&mut self, query: &str, current: Option<usize>, total: usize) {
+                self.0.borrow_mut().push(format!("{} {:?}/{}", query, current, total));
+            }
+            fn on_load_complete(&mut self, pages: usize, chars: usize) {
+                self.0.borrow_mut().push(format!("cargado: {} páginas, {} caracteres", pages, chars));
+            }
+        }
+        let events = Rc::new(RefCell::new(Vec::new()));
+        let mut document = viewer("Primera con clave.\x0CSegunda con clave.", 40, 16);
+        document.set_hooks(Box::new(Recorder(events.clone())));
+        document.load_complete();
+        document.set_search("clave".to_string(), true);
+        document.repeat_search(false);
+        document.repeat_search(false);
+        document.page_changed();
+        document.set_search("nada".to_string(), true);
+        document.repeat_search(false);
+        assert_eq!(*events.borrow(), ["cargado: 2 páginas, 36 caracteres", "clave Some(0)/2", "clave Some(1)/2", "página 1 (PDF 1) de 2", "nada None/0"]);
+
+        let mut next = viewer("Otro documento", 40, 16);
+        document.hand_over(&mut next);
+        next.load_complete();
+        assert_eq!(events.borrow().last().map(String::as_str), Some("cargado: 1 páginas, 14 caracteres"));
+    }
+
+    #[test]
+    fn help_scrolls_within_its_lines() {
+        let document = viewer("Texto.", 60, 16);
+        let lines = document.help_lines().len();
+        assert!(lines > 14);
+        assert_eq!(scroll_overlay(0, KeyCode::Char('j'), lines, 14), Some(1));
+        assert_eq!(scroll_overlay(0, KeyCode::Up, lines, 14), Some(0));
+        assert_eq!(scroll_overlay(0, KeyCode::PageDown, lines, 14), Some(14));
+        assert_eq!(scroll_overlay(0, KeyCode::Char('G'), lines, 14), Some(lines - 14));
+        assert_eq!(scroll_overlay(lines - 14, KeyCode::Down, lines, 14), Some(lines - 14));
+        assert_eq!(scroll_overlay(3, KeyCode::Char('q'), lines, 14), None);
+        assert_eq!(scroll_overlay(0, KeyCode::Down, 5, 14), Some(0));
+        assert_eq!((action::parse_key("h"), action::parse_key("F1"), action::parse_key("F13")), (Some(KeyCode::Char('h')), Some(KeyCode::F(1)), None));
+    }
+
+    #[test]
+    fn active_match_is_reversed_and_follows_manual_navigation() {
+        let text = (1..=30).map(|n| format!("Línea {} error, y otro error.", n)).collect::<Vec<_>>().join("\n");
+        let mut document = viewer(&text, 50, 16);
+        let reversed = |document: &PdfViewer| document.render().matches("\x1b[7m").count();
+        document.set_search("error".to_string(), true);
+        assert_eq!(reversed(&document), 0);
+        document.repeat_search(false);
+        document.repeat_search(false);
+        assert_eq!(reversed(&document), 1);
+        let second = document.current_match.unwrap();
+        assert_eq!(document.active_match(0, &document.page_text(0), &search::find_all(&document.page_text(0), "error", false)).map(|m| m.start), Some(second));
+
+        document.next_page();
+        document.sync_match();
+        assert_eq!((document.current_match, reversed(&document)), (None, 0));
+        document.repeat_search(false);
+        assert_eq!(document.current_page, 1);
+        assert!(document.current_match.is_some_and(|offset| offset >= document.page_range(1).0));
+    }
+}
@@ -0,0 +1,238 @@
+use std::collections::HashMap;
+use std::ops::Range;
+
+use lopdf::{Dictionary, Document, Object, ObjectId};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum LinkTarget {
+    Page(usize),
+    Uri(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct Link {
+    pub pdf_page: usize,
+    pub rect: [f64; 4],
+    pub target: LinkTarget,
+    pub anchor: String,
+}
+
+impl Link {
+    pub fn contains(&self, x: f64, y: f64) -> bool {
+        let [x1, y1, x2, y2] = self.rect;
+        x >= x1 - 1.0 && x <= x2 + 1.0 && y >= y1 - 2.0 && y <= y2 + 1.0
+    }
+}
+
+pub fn collect_links(doc: &Document) -> Vec<Link> {
+    let pages = doc.get_pages();
+    let page_numbers: HashMap<ObjectId, usize> = pages
+        .iter()
+        .map(|(number, id)| (*id, *number as usize - 1))
+        .collect();
+
+    let mut links = Vec::new();
+    for (number, page_id) in &pages {
+        let Ok(page) = doc.get_dictionary(*page_id) else { continue };
+        let Some(annots) = page.get(b"Annots").ok().and_then(|a| resolve_array(doc, a)) else { continue };
+
+        for annot in annots {
+            let Some(annot) = resolve_dict(doc, annot) else { continue };
+            if annot.get(b"Subtype").and_then(Object::as_name).ok() != Some(b"Link".as_slice()) {
+                continue;
+            }
+            let Some(rect) = annot.get(b"Rect").ok().and_then(|r| parse_rect(doc, r)) else { continue };
+            let Some(target) = link_target(doc, annot, &page_numbers) else { continue };
+
+            links.push(Link {
+                pdf_page: *number as usize - 1,
+                rect,
+                target,
+                anchor: String::new(),
+            });
+        }
+    }
+    links
+}
+
+#[derive(Debug, Clone)]
+pub struct OutlineEntry {
+    pub level: usize,
+    pub title: String,
+    pub pdf_page: usize,
+    pub detected_at: Option<usize>,
+}
+
+pub fn collect_outline(doc: &Document) -> Vec<OutlineEntry> {
+    let page_numbers: HashMap<ObjectId, usize> = doc.get_pages()
+        .iter()
+        .map(|(number, id)| (*id, *number as usize - 1))
+        .collect();
+    let mut entries = Vec::new();
+    let first = doc.catalog().ok()
+        .and_then(|catalog| resolve_dict(doc, catalog.get(b"Outlines").ok()?))
+        .and_then(|outlines| outlines.get(b"First").ok());
+    if let Some(first) = first {
+        outline_items(doc, first, 0, &page_numbers, &mut entries);
+    }
+    entries
+}
+
+pub fn info_field(doc: &Document, key: &[u8]) -> Option<String> {
+    let info = resolve_dict(doc, doc.trailer.get(b"Info").ok()?)?;
+    let value = doc.dereference(info.get(key).ok()?).ok()?.1.as_str().ok()?;
+    Some(text_string(value)).filter(|value| !value.is_empty())
+}
+
+fn outline_items(doc: &Document, first: &Object, level: usize, page_numbers: &HashMap<ObjectId, usize>, entries: &mut Vec<OutlineEntry>) {
+    if level > 16 {
+        return;
+    }
+    let mut next = Some(first);
+    while let Some(item) = next.and_then(|item| resolve_dict(doc, item)) {
+        if entries.len() > 10_000 {
+            return;
+        }
+        let title = item.get(b"Title").ok()
+            .and_then(|title| doc.dereference(title).ok()?.1.as_str().ok())
+            .map(text_string)
+            .unwrap_or_default();
+        let dest = item.get(b"Dest").ok().or_else(|| {
+            let action = resolve_dict(doc, item.get(b"A").ok()?)?;
+            action.get(b"D").ok()
+        });
+        let page = dest
+            .and_then(|dest| destination_page(doc, dest, 0))
+            .and_then(|id| page_numbers.get(&id).copied());
+        if let Some(pdf_page) = page {
+            entries.push(OutlineEntry { level, title, pdf_page, detected_at: None });
+        }
+        if let Ok(child) = item.get(b"First") {
+            outline_items(doc, child, level + 1, page_numbers, entries);
+        }
+        next = item.get(b"Next").ok();
+    }
+}
+
+fn text_string(bytes: &[u8]) -> String {
+    match bytes {
+        [0xfe, 0xff, rest @ ..] => {
+            let units: Vec<u16> = rest.chunks_exact(2).map(|pair| u16::from_be_bytes([pair[0], pair[1]])).collect();
+            String::from_utf16_lossy(&units)
+        }
+        _ => bytes.iter().map(|&b| b as char).collect(),
+    }
+    .trim()
+    .to_string()
+}
+
+fn link_target(doc: &Document, annot: &Dictionary, page_numbers: &HashMap<ObjectId, usize>) -> Option<LinkTarget> {
+    let dest = match annot.get(b"Dest") {
+        Ok(dest) => dest,
+        Err(_) => {
+            let action = resolve_dict(doc, annot.get(b"A").ok()?)?;
+            match action.get(b"S").and_then(Object::as_name).ok()? {
+                b"GoTo" => action.get(b"D").ok()?,
+                b"URI" => {
+                    let uri = doc.dereference(action.get(b"URI").ok()?).ok()?.1.as_str().ok()?;
+                    return Some(LinkTarget::Uri(String::from_utf8_lossy(uri).into_owned()));
+                }
+                _ => return None,
+            }
+        }
+    };
+    let page_id = destination_page(doc, dest, 0)?;
+    page_numbers.get(&page_id).map(|page| LinkTarget::Page(*page))
+}
+
+fn destination_page(doc: &Document, dest: &Object, depth: usize) -> Option<ObjectId> {
+    if depth > 8 {
+        return None;
+    }
+    match doc.dereference(dest).ok()?.1 {
+        Object::Array(items) => items.first()?.as_reference().ok(),
+        Object::Dictionary(dict) => destination_page(doc, dict.get(b"D").ok()?, depth + 1),
+        Object::Name(name) | Object::String(name, _) => {
+            let dest = named_destination(doc, name)?;
+            destination_page(doc, dest, depth + 1)
+        }
+        _ => None,
+    }
+}
+
+fn named_destination<'a>(doc: &'a Document, name: &[u8]) -> Option<&'a Object> {
+    let catalog = doc.catalog().ok()?;
+    if let Some(dests) = catalog.get(b"Dests").ok().and_then(|d| resolve_dict(doc, d)) {
+        if let Ok(dest) = dests.get(name) {
+            return Some(dest);
+        }
+    }
+    let names = resolve_dict(doc, catalog.get(b"Names").ok()?)?;
+    let tree = resolve_dict(doc, names.get(b"Dests").ok()?)?;
+    search_name_tree(doc, tree, name, 0)
+}
+
+fn search_name_tree<'a>(doc: &'a Document, node: &'a Dictionary, name: &[u8], depth: usize) -> Option<&'a Object> {
+    if depth > 32 {
+        return None;
+    }
+    if let Some(entries) = node.get(b"Names").ok().and_then(|n| resolve_array(doc, n)) {
+        for pair in entries.chunks(2) {
+            if let [key, value] = pair {
+                if doc.dereference(key).ok()?.1.as_str().ok() == Some(name) {
+                    return Some(value);
+                }
+            }
+        }
+    }
+    let kids = node.get(b"Kids").ok().and_then(|k| resolve_array(doc, k))?;
+    kids.iter()
+        .filter_map(|kid| resolve_dict(doc, kid))
+        .find_map(|kid| search_name_tree(doc, kid, name, depth + 1))
+}
+
+fn parse_rect(doc: &Document, rect: &Object) -> Option<[f64; 4]> {
+    let values = resolve_array(doc, rect)?;
+    if values.len() != 4 {
+        return None;
+    }
+    let mut out = [0.0; 4];
+    for (slot, value) in out.iter_mut().zip(values) {
+        *slot = doc.dereference(value).ok()?.1.as_float().ok()? as f64;
+    }
+    Some([out[0].min(out[2]), out[1].min(out[3]), out[0].max(out[2]), out[1].max(out[3])])
+}
+
+fn resolve_dict<'a>(doc: &'a Document, object: &'a Object) -> Option<&'a Dictionary> {
+    doc.dereference(object).ok()?.1.as_dict().ok()
+}
+
+fn resolve_array<'a>(doc: &'a Document, object: &'a Object) -> Option<&'a Vec<Object>> {
+    doc.dereference(object).ok()?.1.as_array().ok()
+}
+
+pub fn find_loose(haystack: &str, needle: &str) -> Option<Range<usize>> {
+    let words: Vec<&str> = needle.split_whitespace().collect();
+    let first = *words.first()?;
+
+    let mut from = 0;
+    while let Some(pos) = haystack[from..].find(first) {
+        let start = from + pos;
+        let mut end = start + first.len();
+        let matched = words[1..].iter().all(|word| {
+            let rest = &haystack[end..];
+            let skipped = rest.len() - rest.trim_start().len();
+            if rest[skipped..].starts_with(word) {
+                end += skipped + word.len();
+                true
+            } else {
+                false
+            }
+        });
+        if matched {
+            return Some(start..end);
+        }
+        from = start + first.len();
+    }
+    None
+}
@@ -0,0 +1,152 @@
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Note {
+    pub offset: usize,
+    pub pdf_page: usize,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Highlight {
+    pub start: usize,
+    pub end: usize,
+    pub pdf_page: usize,
+    pub color: String,
+}
+
+pub const HIGHLIGHT_COLORS: [&str; 4] = ["yellow", "green", "blue", "pink"];
+
+#[derive(Debug, Serialize, Deserialize)]
+struct NotesFile {
+    version: u32,
+    notes: Vec<Note>,
+    #[serde(default)]
+    highlights: Vec<Highlight>,
+}
+
+pub struct Notes {
+    path: PathBuf,
+    pub notes: Vec<Note>,
+    pub highlights: Vec<Highlight>,
+    error: Option<String>,
+}
+
+impl Notes {
+    pub fn load(pdf_path: &str) -> Notes {
+        let path = PathBuf::from(format!("{}.pdfview-notes.json", pdf_path));
+        let file = match std::fs::read_to_string(&path) {
+            Ok(contents) => serde_json::from_str::<NotesFile>(&contents).map(Some).map_err(|e| e.to_string()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.to_string()),
+        };
+        match file {
+            Ok(file) => {
+                let (notes, highlights) = file.map(|file| (file.notes, file.highlights)).unwrap_or_default();
+                Notes { path, notes, highlights, error: None }
+            }
+            Err(error) => Notes { path, notes: Vec::new(), highlights: Vec::new(), error: Some(error) },
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn error(&self) -> Option<String> {
+        self.error.as_ref()
+            .map(|error| format!("No se pudo leer {} ({}); corrígelo o muévelo para guardar notas", self.path.display(), error))
+    }
+
+    fn check(&self) -> std::io::Result<()> {
+        match self.error() {
+            Some(error) => Err(std::io::Error::other(error)),
+            None => Ok(()),
+        }
+    }
+
+    pub fn add(&mut self, note: Note) -> std::io::Result<()> {
+        self.check()?;
+        let index = self.notes.partition_point(|n| n.offset <= note.offset);
+        self.notes.insert(index, note);
+        self.save()
+    }
+
+    pub fn remove(&mut self, index: usize) -> std::io::Result<()> {
+        self.check()?;
+        if index < self.notes.len() {
+            self.notes.remove(index);
+        }
+        self.save()
+    }
+
+    pub fn add_highlight(&mut self, highlight: Highlight) -> std::io::Result<()> {
+        self.check()?;
+        let index = self.highlights.partition_point(|h| h.start <= highlight.start);
+        self.highlights.insert(index, highlight);
+        self.save()
+    }
+
+    pub fn cycle_highlights(&mut self, start: usize, end: usize) -> std::io::Result<usize> {
+        self.check()?;
+        let mut changed = 0;
+        for highlight in self.highlights.iter_mut().filter(|h| h.start < end && h.end > start) {
+            let index = HIGHLIGHT_COLORS.iter().position(|c| *c == highlight.color).map_or(0, |i| i + 1);
+            highlight.color = HIGHLIGHT_COLORS[index % HIGHLIGHT_COLORS.len()].to_string();
+            changed += 1;
+        }
+        self.save()?;
+        Ok(changed)
+    }
+
+    pub fn remove_highlights(&mut self, start: usize, end: usize) -> std::io::Result<usize> {
+        self.check()?;
+        let before = self.highlights.len();
+        self.highlights.retain(|h| h.start >= end || h.end <= start);
+        self.save()?;
+        Ok(before - self.highlights.len())
+    }
+
+    pub fn count_in(&self, start: usize, end: usize) -> usize {
+        self.notes.iter().filter(|note| note.offset >= start && note.offset < end).count()
+    }
+
+    fn save(&self) -> std::io::Result<()> {
+        self.check()?;
+        if self.notes.is_empty() && self.highlights.is_empty() {
+            return match std::fs::remove_file(&self.path) {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+                _ => Ok(()),
+            };
+        }
+        let file = NotesFile { version: 1, notes: self.notes.clone(), highlights: self.highlights.clone() };
+        let json = serde_json::to_string_pretty(&file).map_err(std::io::Error::other)?;
+        std::fs::write(&self.path, json + "\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testdir::TestDir;
+
+    #[test]
+    fn a_broken_sidecar_is_reported_and_never_overwritten() {
+        let dir = TestDir::new("notas");
+        let pdf = dir.path("notas.pdf");
+        let pdf = pdf.to_str().unwrap();
+        let sidecar = format!("{}.pdfview-notes.json", pdf);
+        std::fs::write(&sidecar, "{ notas a medio escribir").unwrap();
+
+        let mut notes = Notes::load(pdf);
+        assert!(notes.error().is_some());
+        assert!(notes.add(Note { offset: 0, pdf_page: 1, text: "hola".to_string() }).is_err());
+        assert!(notes.notes.is_empty());
+        assert_eq!(std::fs::read_to_string(&sidecar).unwrap(), "{ notas a medio escribir");
+
+        std::fs::remove_file(&sidecar).unwrap();
+        assert!(Notes::load(pdf).error().is_none());
+    }
+}
@@ -0,0 +1,195 @@
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use serde::{Deserialize, Serialize};
+use toml_edit::DocumentMut;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Prefs {
+    pub theme: String,
+    pub cursor_line: bool,
+    pub ruler: bool,
+    pub tts_auto_advance: bool,
+    pub image_backend: String,
+    pub page_boundary: String,
+    pub file_boundary: String,
+    pub search_history_size: usize,
+    pub search_wrap: bool,
+    pub whole_word: bool,
+    pub progress: String,
+    pub statusline: String,
+    pub wrap_cache_size: usize,
+    pub skip_blank: bool,
+    pub blank_threshold: usize,
+    pub blank_pages: String,
+    pub squeeze_whitespace: bool,
+    pub preserve_tables: bool,
+    pub highlight_code: bool,
+    pub detect_direction: bool,
+    pub move_footnotes: bool,
+    pub hyphenate: String,
+    pub line_break: String,
+    pub number: String,
+    pub bell: String,
+    pub diff_layout: String,
+    pub keymap: String,
+    pub help_key: String,
+    pub quiet: bool,
+    pub set_title: bool,
+    pub shrink_frame: bool,
+    pub scroll_frame: bool,
+    pub wrap_width: u16,
+    pub clock: bool,
+    pub battery: bool,
+    pub reading_timer: bool,
+    pub timer_idle_secs: u64,
+    pub external_command: String,
+    pub external_output: bool,
+    pub keep_editor_file: bool,
+    pub screenshot_ansi: bool,
+}
+
+impl Default for Prefs {
+    fn default() -> Self {
+        Prefs {
+            theme: "dark".to_string(),
+            cursor_line: false,
+            ruler: false,
+            tts_auto_advance: true,
+            image_backend: "auto".to_string(),
+            page_boundary: "stop".to_string(),
+            file_boundary: "stop".to_string(),
+            search_history_size: 50,
+            search_wrap: true,
+            whole_word: false,
+            progress: "pages".to_string(),
+            statusline: String::new(),
+            wrap_cache_size: 32,
+            skip_blank: false,
+            blank_threshold: 5,
+            blank_pages: "skip".to_string(),
+            squeeze_whitespace: true,
+            preserve_tables: true,
+            highlight_code: true,
+            detect_direction: true,
+            move_footnotes: false,
+            hyphenate: "off".to_string(),
+            line_break: "anywhere".to_string(),
+            number: "off".to_string(),
+            bell: "off".to_string(),
+            diff_layout: "side-by-side".to_string(),
+            keymap: "vim-ish".to_string(),
+            help_key: "?".to_string(),
+            quiet: false,
+            set_title: true,
+            shrink_frame: false,
+            scroll_frame: true,
+            wrap_width: 0,
+            clock: false,
+            battery: false,
+            reading_timer: false,
+            timer_idle_secs: 120,
+            external_command: String::new(),
+            external_output: true,
+            keep_editor_file: false,
+            screenshot_ansi: false,
+        }
+    }
+}
+
+static SOURCE: OnceLock<Option<PathBuf>> = OnceLock::new();
+
+impl Prefs {
+    pub fn load() -> Prefs {
+        Self::path()
+            .and_then(|path| std::fs::read_to_string(path).ok())
+            .and_then(|contents| toml::from_str(&contents).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn use_file(path: &Path) -> Result<(), String> {
+        Self::read(path)?;
+        let _ = SOURCE.set(Some(path.to_path_buf()));
+        Ok(())
+    }
+
+    pub fn use_defaults() {
+        let _ = SOURCE.set(None);
+    }
+
+    fn read(path: &Path) -> Result<Prefs, String> {
+        let contents = std::fs::read_to_string(path).map_err(|e| format!("No se pudo leer {}: {}", path.display(), e))?;
+        toml::from_str(&contents).map_err(|e| format!("{} no es válido: {}", path.display(), e.message()))
+    }
+
+    pub fn persist(key: &str, value: impl Into<toml_edit::Value>) -> Result<(), String> {
+        match Self::path() {
+            Some(path) => Self::persist_to(&path, key, value.into()),
+            None => Ok(()),
+        }
+    }
+
+    fn persist_to(path: &Path, key: &str, value: toml_edit::Value) -> Result<(), String> {
+        let mut document = match std::fs::read_to_string(path) {
+            Ok(contents) => contents.parse::<DocumentMut>()
+                .map_err(|e| format!("{} no es válido, no se guardó {}: {}", path.display(), key, e.message()))?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => DocumentMut::new(),
+            Err(e) => return Err(format!("No se pudo leer {}: {}", path.display(), e)),
+        };
+        document[key] = toml_edit::value(value);
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).map_err(|e| format!("No se pudo crear {}: {}", dir.display(), e))?;
+        }
+        std::fs::write(path, document.to_string()).map_err(|e| format!("No se pudo guardar {}: {}", path.display(), e))
+    }
+
+    fn path() -> Option<PathBuf> {
+        match SOURCE.get() {
+            Some(source) => source.clone(),
+            None => config_dir().map(|dir| dir.join("pdfview").join("config.toml")),
+        }
+    }
+}
+
+fn config_dir() -> Option<PathBuf> {
+    if let Some(dir) = std::env::var_os("XDG_CONFIG_HOME").filter(|d| !d.is_empty()) {
+        return Some(PathBuf::from(dir));
+    }
+    if cfg!(windows) {
+        return std::env::var_os("APPDATA").map(PathBuf::from);
+    }
+    std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testdir::TestDir;
+
+    #[test]
+    fn alternate_config_must_exist_and_parse() {
+        let dir = TestDir::new("config");
+        let path = dir.path("config.toml");
+        assert!(Prefs::read(&path).unwrap_err().starts_with("No se pudo leer"));
+        std::fs::write(&path, "theme = \"light\"\ncursor_line = true\n").unwrap();
+        let prefs = Prefs::read(&path).unwrap();
+        assert_eq!((prefs.theme.as_str(), prefs.cursor_line, prefs.keymap.as_str()), ("light", true, "vim-ish"));
+        std::fs::write(&path, "cursor_line = \"sí\"").unwrap();
+        assert!(Prefs::read(&path).unwrap_err().contains("no es válido"));
+    }
+
+    #[test]
+    fn persisting_a_key_keeps_the_rest_of_the_file() {
+        let dir = TestDir::new("persist");
+        let path = dir.path("persist.toml");
+        std::fs::write(&path, "# mi configuración\ntheme = \"light\"  # claro\nwrap_width = 60\n").unwrap();
+        Prefs::persist_to(&path, "wrap_width", 72.into()).unwrap();
+        Prefs::persist_to(&path, "cursor_line", true.into()).unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "# mi configuración\ntheme = \"light\"  # claro\nwrap_width = 72\ncursor_line = true\n");
+
+        std::fs::write(&path, "theme = \"light\n").unwrap();
+        assert!(Prefs::persist_to(&path, "wrap_width", 72.into()).unwrap_err().contains("no es válido"));
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "theme = \"light\n");
+    }
+}
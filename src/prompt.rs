@@ -0,0 +1,112 @@
+use std::io::{stdout, Write};
+
+use crossterm::{
+    cursor,
+    event::{self, Event, KeyCode, KeyEventKind, KeyModifiers},
+    execute,
+    style::Stylize,
+    terminal::{self, ClearType},
+};
+
+const MAX_INPUT: usize = 256;
+
+pub fn read_line(label: &str, initial: &str) -> std::io::Result<Option<String>> {
+    read_line_with(label, initial, &[], |_| None)
+}
+
+pub fn read_line_with(
+    label: &str,
+    initial: &str,
+    history: &[String],
+    complete: impl Fn(&str) -> Option<String>,
+) -> std::io::Result<Option<String>> {
+    let (_, height) = terminal::size()?;
+    let mut input = initial.to_string();
+    let mut draft = String::new();
+    let mut recalled: Option<usize> = None;
+    let mut truncated = false;
+    loop {
+        execute!(stdout(), cursor::MoveTo(0, height.saturating_sub(1)), terminal::Clear(ClearType::CurrentLine))?;
+        print!("{} {}", label.bold().yellow(), input);
+        if std::mem::take(&mut truncated) {
+            print!("  {}", format!("(recortado a {} caracteres)", MAX_INPUT).dark_grey());
+            execute!(stdout(), cursor::MoveTo((label.chars().count() + 1 + input.chars().count()) as u16, height.saturating_sub(1)))?;
+        }
+        stdout().flush()?;
+
+        let event = event::read()?;
+        if let Event::Paste(text) = &event {
+            truncated = insert_pasted(&mut input, text);
+        }
+        if let Event::Key(key_event) = event {
+            if key_event.kind != KeyEventKind::Press {
+                continue;
+            }
+            match key_event.code {
+                KeyCode::Enter => return Ok(Some(input)),
+                KeyCode::Esc => return Ok(None),
+                KeyCode::Char('c') if key_event.modifiers.contains(KeyModifiers::CONTROL) => return Ok(None),
+                KeyCode::Char('u') if key_event.modifiers.contains(KeyModifiers::CONTROL) => input.clear(),
+                KeyCode::Char('v' | 'y') if key_event.modifiers.contains(KeyModifiers::CONTROL) => {
+                    if let Some(text) = crate::clipboard::paste() {
+                        truncated = insert_pasted(&mut input, &text);
+                    }
+                }
+                KeyCode::Up if !history.is_empty() => {
+                    let index = match recalled {
+                        Some(index) => index.saturating_sub(1),
+                        None => {
+                            draft = std::mem::take(&mut input);
+                            history.len() - 1
+                        }
+                    };
+                    recalled = Some(index);
+                    input = history[index].clone();
+                }
+                KeyCode::Down => match recalled {
+                    Some(index) if index + 1 < history.len() => {
+                        recalled = Some(index + 1);
+                        input = history[index + 1].clone();
+                    }
+                    Some(_) => {
+                        recalled = None;
+                        input = std::mem::take(&mut draft);
+                    }
+                    None => {}
+                },
+                KeyCode::Tab => {
+                    if let Some(completed) = complete(&input) {
+                        input = completed;
+                    }
+                }
+                KeyCode::Backspace => {
+                    input.pop();
+                }
+                KeyCode::Char(c) if input.chars().count() < MAX_INPUT => input.push(c),
+                _ => {}
+            }
+        }
+    }
+}
+
+fn insert_pasted(input: &mut String, text: &str) -> bool {
+    let text = text.split(['\r', '\n']).map(str::trim).filter(|line| !line.is_empty()).collect::<Vec<_>>().join(" ");
+    let room = MAX_INPUT.saturating_sub(input.chars().count());
+    input.extend(text.chars().take(room));
+    text.chars().count() > room
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pasted_lines_join_and_long_pastes_are_cut() {
+        let mut input = "/".to_string();
+        assert!(!insert_pasted(&mut input, "get_page_text\r\n  del módulo\n"));
+        assert_eq!(input, "/get_page_text del módulo");
+        let mut input = String::new();
+        assert!(insert_pasted(&mut input, &"x".repeat(MAX_INPUT + 10)));
+        assert_eq!(input.chars().count(), MAX_INPUT);
+    }
+}
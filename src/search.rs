@@ -0,0 +1,101 @@
+use std::ops::Range;
+use std::rc::Rc;
+
+#[derive(Debug, Clone)]
+pub struct Search {
+    pub query: String,
+    pub forward: bool,
+    pub matches: Rc<Vec<Range<usize>>>,
+}
+
+pub const WHOLE_WORD: &str = "\\w";
+
+pub fn find_all(haystack: &str, query: &str, whole_word: bool) -> Vec<Range<usize>> {
+    let (query, whole_word) = match query.strip_suffix(WHOLE_WORD) {
+        Some(query) => (query, true),
+        None => (query, whole_word),
+    };
+    let fold = !query.chars().any(char::is_uppercase);
+    let needle: Vec<Option<char>> = fold_chars(query.trim(), fold)
+        .map(|(c, _)| (!c.is_whitespace()).then_some(c))
+        .fold(Vec::new(), |mut tokens, token| {
+            if token.is_some() || tokens.last().is_some_and(Option::is_some) {
+                tokens.push(token);
+            }
+            tokens
+        });
+    if needle.is_empty() {
+        return Vec::new();
+    }
+    let chars: Vec<(char, usize)> = fold_chars(haystack, fold).collect();
+
+    let mut matches = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        match match_at(&chars, i, &needle) {
+            Some(end) if !is_placeholder_line(haystack, chars[i].1) && (!whole_word || on_word_boundaries(&chars, i, end)) => {
+                let end_byte = chars.get(end).map_or(haystack.len(), |(_, byte)| *byte);
+                matches.push(chars[i].1..end_byte);
+                i = end;
+            }
+            _ => i += 1,
+        }
+    }
+    matches
+}
+
+fn fold_chars(text: &str, fold: bool) -> impl Iterator<Item = (char, usize)> + '_ {
+    text.char_indices().map(move |(byte, c)| {
+        let c = if fold { c.to_lowercase().next().unwrap_or(c) } else { c };
+        (c, byte)
+    })
+}
+
+fn match_at(chars: &[(char, usize)], start: usize, needle: &[Option<char>]) -> Option<usize> {
+    let mut j = start;
+    for token in needle {
+        match token {
+            Some(c) => {
+                if chars.get(j)?.0 != *c {
+                    return None;
+                }
+                j += 1;
+            }
+            None => {
+                let skipped = chars[j..].iter().take_while(|(c, _)| c.is_whitespace()).count();
+                if skipped == 0 {
+                    return None;
+                }
+                j += skipped;
+            }
+        }
+    }
+    Some(j)
+}
+
+fn on_word_boundaries(chars: &[(char, usize)], start: usize, end: usize) -> bool {
+    let word = |index: usize| chars.get(index).is_some_and(|(c, _)| c.is_alphanumeric() || *c == '_');
+    let starts = !word(start) || start == 0 || !word(start - 1);
+    let ends = !word(end - 1) || !word(end);
+    starts && ends
+}
+
+fn is_placeholder_line(text: &str, byte: usize) -> bool {
+    let line_start = text[..byte].rfind('\n').map_or(0, |i| i + 1);
+    text[line_start..].starts_with("[imagen: ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn whole_word_skips_matches_inside_longer_words() {
+        let text = "El art de la parte, start y article; ART moderno. Arte, artístico, art_deco, café-art.";
+        assert_eq!(find_all(text, "art", false).len(), 9);
+        let whole: Vec<&str> = find_all(text, "art", true).into_iter().map(|range| &text[range]).collect();
+        assert_eq!(whole, ["art", "ART", "art"]);
+        assert_eq!(find_all(text, "art\\w", false), find_all(text, "art", true));
+        assert_eq!(find_all("cafés y café", "café", true).first().map(|range| range.start), Some(9));
+    }
+}
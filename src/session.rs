@@ -0,0 +1,63 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Session {
+    pub document: SessionDocument,
+    pub settings: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SessionDocument {
+    pub path: String,
+    pub compare: Option<String>,
+    pub offset: usize,
+    pub search: Option<String>,
+    pub search_forward: bool,
+}
+
+impl Session {
+    pub fn save(&self, name: &str) -> Result<PathBuf, Box<dyn std::error::Error>> {
+        let file = session_file(name)?;
+        if let Some(dir) = file.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(&file, serde_json::to_string_pretty(self)? + "\n")?;
+        Ok(file)
+    }
+
+    pub fn load(name: &str) -> Result<Session, String> {
+        let file = session_file(name)?;
+        let contents = std::fs::read_to_string(&file).map_err(|_| format!("No existe la sesión {} ({})", name, file.display()))?;
+        serde_json::from_str(&contents).map_err(|e| format!("La sesión {} no es válida: {}", name, e))
+    }
+}
+
+pub fn list() -> Vec<String> {
+    let mut names: Vec<String> = sessions_dir()
+        .and_then(|dir| std::fs::read_dir(dir).ok())
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| entry.file_name().to_string_lossy().strip_suffix(".json").map(str::to_string))
+        .collect();
+    names.sort();
+    names
+}
+
+fn sessions_dir() -> Option<PathBuf> {
+    crate::state::data_dir().map(|dir| dir.join("pdfview").join("sessions"))
+}
+
+fn session_file(name: &str) -> Result<PathBuf, String> {
+    if name.is_empty() || name.contains(['/', '\\']) || name.starts_with('.') {
+        return Err(format!("Nombre de sesión no válido: \"{}\"", name));
+    }
+    sessions_dir()
+        .map(|dir| dir.join(format!("{}.json", name)))
+        .ok_or_else(|| "No se encontró el directorio de datos".to_string())
+}
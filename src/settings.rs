@@ -0,0 +1,125 @@
+pub enum Kind {
+    Bool,
+    Choice(&'static [&'static str]),
+}
+
+pub struct Setting {
+    pub name: &'static str,
+    pub kind: Kind,
+    pub layout: bool,
+    pub description: &'static str,
+}
+
+pub const SETTINGS: &[Setting] = &[
+    Setting { name: "theme", kind: Kind::Choice(&["dark", "light"]), layout: false, description: "Tema de colores" },
+    Setting { name: "cursorline", kind: Kind::Bool, layout: false, description: "Línea de cursor" },
+    Setting { name: "minimap", kind: Kind::Bool, layout: true, description: "Mapa del documento" },
+    Setting { name: "cols", kind: Kind::Choice(&["1", "2", "3", "4"]), layout: true, description: "Páginas por pantalla, una al lado de otra" },
+    Setting { name: "skipblank", kind: Kind::Bool, layout: true, description: "Saltar páginas casi vacías (blank_threshold en config.toml)" },
+    Setting { name: "blankpages", kind: Kind::Choice(&["skip", "marker"]), layout: true, description: "Páginas en blanco del PDF: omitirlas (skip) o mostrar una página que las resume (marker)" },
+    Setting { name: "squeeze", kind: Kind::Bool, layout: true, description: "Juntar espacios repetidos y líneas en blanco seguidas" },
+    Setting { name: "tables", kind: Kind::Bool, layout: true, description: "Mantener las tablas sin reajustar, con sus columnas alineadas" },
+    Setting { name: "bidi", kind: Kind::Bool, layout: true, description: "Detectar páginas de derecha a izquierda (hebreo, árabe); desactívalo en documentos enormes" },
+    Setting { name: "syntax", kind: Kind::Bool, layout: true, description: "Colorear los bloques de código y mantenerlos sin reajustar" },
+    Setting { name: "footnotes", kind: Kind::Bool, layout: true, description: "Llevar las notas al pie al final de su página, con [n] en el texto" },
+    Setting { name: "hyphenate", kind: Kind::Choice(crate::hyphenate::NAMES), layout: true, description: "Partir palabras largas con guion al final de línea (en, es)" },
+    Setting { name: "number", kind: Kind::Choice(NUMBERS), layout: true, description: "Números de línea: absolutos o relativos a la línea de cursor (relative, como en Vim)" },
+    Setting { name: "break", kind: Kind::Choice(crate::breaks::NAMES), layout: true, description: "Cómo cortar URLs y palabras más largas que la línea" },
+    Setting { name: "diff", kind: Kind::Choice(crate::diff::LAYOUTS), layout: true, description: "Comparación en dos columnas (side-by-side) o en una (unified)" },
+    Setting { name: "bell", kind: Kind::Choice(&["off", "boundary", "turn"]), layout: false, description: "Campana al llegar al principio o al final (boundary) o en cada cambio de página (turn)" },
+    Setting { name: "title", kind: Kind::Bool, layout: false, description: "Poner el documento y la página en el título de la ventana" },
+    Setting { name: "quiet", kind: Kind::Bool, layout: false, description: "Silenciar todas las campanas" },
+    Setting { name: "scroll", kind: Kind::Bool, layout: false, description: "Lectura continua, como less (tecla s)" },
+    Setting { name: "scrollframe", kind: Kind::Bool, layout: false, description: "Marco alrededor del texto en la lectura continua" },
+    Setting { name: "shrink", kind: Kind::Bool, layout: false, description: "Ajustar el marco al texto en páginas cortas" },
+    Setting { name: "clock", kind: Kind::Bool, layout: false, description: "Hora a la derecha de la línea de controles" },
+    Setting { name: "timer", kind: Kind::Bool, layout: false, description: "Tiempo de lectura de la sesión junto a la hora (se pausa tras timer_idle_secs sin teclas)" },
+    Setting { name: "battery", kind: Kind::Bool, layout: false, description: "Nivel de batería junto a la hora (compilado con --features battery)" },
+    Setting { name: "wrapscan", kind: Kind::Bool, layout: false, description: "n y , siguen desde el otro extremo al acabar las coincidencias" },
+    Setting { name: "wholeword", kind: Kind::Bool, layout: false, description: "Buscar solo palabras completas (o termina la búsqueda con \\w)" },
+    Setting { name: "boundary", kind: Kind::Choice(&["stop", "wrap"]), layout: false, description: "Qué hacer al llegar al final: parar o dar la vuelta (solo al pasar página; g, G y las búsquedas no la dan)" },
+    Setting { name: "fileboundary", kind: Kind::Choice(&["stop", "wrap"]), layout: false, description: "Qué hacer con Ctrl+N/Ctrl+B al llegar al último o primer PDF de la carpeta" },
+    Setting { name: "images", kind: Kind::Choice(&["auto", "sixel", "kitty", "blocks"]), layout: false, description: "Cómo dibujar las imágenes" },
+    Setting { name: "progress", kind: Kind::Choice(&["pages", "chars"]), layout: false, description: "Progreso por páginas o por caracteres" },
+    Setting { name: "ttsadvance", kind: Kind::Bool, layout: false, description: "Pasar de página al terminar de leer en voz alta" },
+];
+
+pub const NUMBERS: &[&str] = &["off", "absolute", "relative"];
+
+pub enum Value {
+    Bool(bool),
+    Text(String),
+}
+
+impl std::fmt::Display for Value {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Value::Bool(true) => write!(f, "sí"),
+            Value::Bool(false) => write!(f, "no"),
+            Value::Text(text) => write!(f, "{}", text),
+        }
+    }
+}
+
+pub fn find(name: &str) -> Option<&'static Setting> {
+    SETTINGS.iter().find(|setting| setting.name == name)
+}
+
+pub fn parse(argument: &str) -> Result<(&'static Setting, Value), String> {
+    if let Some((name, value)) = argument.split_once('=') {
+        let setting = find(name).ok_or_else(|| format!("Opción desconocida: {}", name))?;
+        let value = match &setting.kind {
+            Kind::Bool => match value {
+                "on" | "true" | "yes" | "1" => Value::Bool(true),
+                "off" | "false" | "no" | "0" => Value::Bool(false),
+                _ => return Err(format!("{} espera on/off, no \"{}\"", name, value)),
+            },
+            Kind::Choice(choices) if choices.contains(&value) => Value::Text(value.to_string()),
+            Kind::Choice(choices) => {
+                return Err(format!("Valor no válido para {}: {} (opciones: {})", name, value, choices.join(", ")));
+            }
+        };
+        return Ok((setting, value));
+    }
+    let (name, enabled) = match argument.strip_prefix("no") {
+        Some(rest) if find(rest).is_some() => (rest, false),
+        _ => (argument, true),
+    };
+    let setting = find(name).ok_or_else(|| format!("Opción desconocida: {}", name))?;
+    match setting.kind {
+        Kind::Bool => Ok((setting, Value::Bool(enabled))),
+        Kind::Choice(_) => Err(format!("{} necesita un valor: :set {}=…", name, name)),
+    }
+}
+
+pub fn complete(input: &str) -> Option<String> {
+    let (head, word) = match input.rfind(' ') {
+        Some(space) => input.split_at(space + 1),
+        None => ("", input),
+    };
+    let candidates: Vec<String> = if head.is_empty() {
+        vec!["set".to_string()]
+    } else if let Some((name, value)) = word.split_once('=') {
+        match find(name).map(|setting| &setting.kind) {
+            Some(Kind::Choice(choices)) => choices.iter().map(|c| format!("{}={}", name, c)).collect(),
+            Some(Kind::Bool) => ["on", "off"].iter().map(|c| format!("{}={}", name, c)).collect(),
+            None => Vec::new(),
+        }
+        .into_iter()
+        .filter(|candidate| candidate.starts_with(&format!("{}={}", name, value)))
+        .collect()
+    } else {
+        SETTINGS.iter()
+            .flat_map(|setting| match setting.kind {
+                Kind::Bool => vec![setting.name.to_string(), format!("no{}", setting.name)],
+                Kind::Choice(_) => vec![format!("{}=", setting.name)],
+            })
+            .collect()
+    };
+    let matching: Vec<&String> = candidates.iter().filter(|c| c.starts_with(word)).collect();
+    let first = matching.first()?;
+    let common = matching.iter().fold(first.len(), |len, candidate| {
+        first.bytes().zip(candidate.bytes()).take(len).take_while(|(a, b)| a == b).count()
+    });
+    (common > word.len()).then(|| format!("{}{}", head, &first[..common]))
+}
@@ -0,0 +1,61 @@
+use std::path::PathBuf;
+
+pub fn path(pdf_path: &str) -> PathBuf {
+    PathBuf::from(format!("{}.pdfview", pdf_path))
+}
+
+pub fn load(pdf_path: &str) -> Result<Vec<(String, String)>, String> {
+    let file = path(pdf_path);
+    let Ok(contents) = std::fs::read_to_string(&file) else {
+        return Ok(Vec::new());
+    };
+    let table: toml::Table = toml::from_str(&contents).map_err(|e| e.message().to_string())?;
+    table.into_iter()
+        .map(|(name, value)| {
+            let value = match value {
+                toml::Value::Boolean(enabled) => if enabled { "on" } else { "off" }.to_string(),
+                toml::Value::Integer(number) => number.to_string(),
+                toml::Value::String(text) => text,
+                other => return Err(format!("{} tiene un valor no válido: {}", name, other)),
+            };
+            Ok((name, value))
+        })
+        .collect()
+}
+
+pub fn save(pdf_path: &str, settings: &[(String, String)]) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let table: toml::Table = settings.iter()
+        .map(|(name, value)| {
+            let value = match value.as_str() {
+                "on" => toml::Value::Boolean(true),
+                "off" => toml::Value::Boolean(false),
+                text => toml::Value::String(text.to_string()),
+            };
+            (name.clone(), value)
+        })
+        .collect();
+    let file = path(pdf_path);
+    std::fs::write(&file, toml::to_string_pretty(&table)?)?;
+    Ok(file)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testdir::TestDir;
+
+    #[test]
+    fn sidecar_round_trips_through_toml() {
+        let dir = TestDir::new("sidecar");
+        let pdf = dir.path("sidecar.pdf").to_string_lossy().into_owned();
+        let settings = vec![("cols".to_string(), "2".to_string()), ("tables".to_string(), "off".to_string())];
+        let file = save(&pdf, &settings).unwrap();
+        assert!(std::fs::read_to_string(&file).unwrap().contains("tables = false"));
+        assert_eq!(load(&pdf).unwrap(), settings);
+
+        std::fs::write(&file, "cols = [2").unwrap();
+        assert!(load(&pdf).is_err());
+        std::fs::remove_file(&file).unwrap();
+        assert_eq!(load(&pdf).unwrap(), []);
+    }
+}
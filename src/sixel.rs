@@ -0,0 +1,73 @@
+use std::fmt::Write;
+
+use crate::images::PageImage;
+
+const LEVELS: [u8; 6] = [0, 51, 102, 153, 204, 255];
+
+fn palette_index([r, g, b]: [u8; 3]) -> usize {
+    let level = |v: u8| ((v as usize * 5 + 127) / 255).min(5);
+    level(r) * 36 + level(g) * 6 + level(b)
+}
+
+pub fn encode(image: &PageImage) -> String {
+    let indices: Vec<usize> = image.pixels.iter().map(|p| palette_index(*p)).collect();
+    let mut out = String::new();
+    let _ = write!(out, "\x1bPq\"1;1;{};{}", image.width, image.height);
+
+    let mut used = [false; 216];
+    for &index in &indices {
+        used[index] = true;
+    }
+    for (index, _) in used.iter().enumerate().filter(|(_, used)| **used) {
+        let percent = |level: usize| LEVELS[level] as usize * 100 / 255;
+        let _ = write!(out, "#{};2;{};{};{}", index, percent(index / 36), percent(index / 6 % 6), percent(index % 6));
+    }
+
+    for band in (0..image.height).step_by(6) {
+        let rows = (image.height - band).min(6);
+        let mut colors: Vec<usize> = (band..band + rows)
+            .flat_map(|y| indices[y * image.width..(y + 1) * image.width].iter().copied())
+            .collect();
+        colors.sort_unstable();
+        colors.dedup();
+
+        for (n, color) in colors.iter().enumerate() {
+            if n > 0 {
+                out.push('$');
+            }
+            let _ = write!(out, "#{}", color);
+            let mut run_char = None;
+            let mut run_len = 0;
+            for x in 0..image.width {
+                let mut bits = 0u8;
+                for dy in 0..rows {
+                    if indices[(band + dy) * image.width + x] == *color {
+                        bits |= 1 << dy;
+                    }
+                }
+                let sixel = (63 + bits) as char;
+                if run_char == Some(sixel) {
+                    run_len += 1;
+                } else {
+                    flush_run(&mut out, run_char, run_len);
+                    run_char = Some(sixel);
+                    run_len = 1;
+                }
+            }
+            flush_run(&mut out, run_char, run_len);
+        }
+        out.push('-');
+    }
+    out.push_str("\x1b\\");
+    out
+}
+
+fn flush_run(out: &mut String, sixel: Option<char>, len: usize) {
+    match (sixel, len) {
+        (Some(c), 1..=3) => (0..len).for_each(|_| out.push(c)),
+        (Some(c), _) => {
+            let _ = write!(out, "!{}{}", len, c);
+        }
+        (None, _) => {}
+    }
+}
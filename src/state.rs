@@ -0,0 +1,65 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DocState {
+    pub path: String,
+    pub quickmarks: BTreeMap<u8, usize>,
+    pub search_history: Vec<String>,
+    pub position: Option<usize>,
+    pub ruler_rows: BTreeMap<usize, usize>,
+}
+
+impl DocState {
+    pub fn load(pdf_path: &str) -> DocState {
+        let path = canonical(pdf_path);
+        let mut state: DocState = state_file(&path)
+            .and_then(|file| std::fs::read_to_string(file).ok())
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default();
+        state.path = path;
+        state
+    }
+
+    pub fn save(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let file = state_file(&self.path).ok_or("No se encontró el directorio de datos")?;
+        if let Some(dir) = file.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(file, serde_json::to_string_pretty(self)? + "\n")?;
+        Ok(())
+    }
+}
+
+fn canonical(pdf_path: &str) -> String {
+    std::fs::canonicalize(pdf_path)
+        .unwrap_or_else(|_| Path::new(pdf_path).to_path_buf())
+        .to_string_lossy()
+        .into_owned()
+}
+
+fn state_file(canonical_path: &str) -> Option<PathBuf> {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in canonical_path.bytes() {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    data_dir().map(|dir| dir.join("pdfview").join("state").join(format!("{:016x}.json", hash)))
+}
+
+pub fn data_dir() -> Option<PathBuf> {
+    // Los tests no leen ni escriben el estado real del usuario.
+    if cfg!(test) {
+        return None;
+    }
+    if let Some(dir) = std::env::var_os("XDG_DATA_HOME").filter(|d| !d.is_empty()) {
+        return Some(PathBuf::from(dir));
+    }
+    if cfg!(windows) {
+        return std::env::var_os("LOCALAPPDATA").map(PathBuf::from);
+    }
+    std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".local").join("share"))
+}
@@ -0,0 +1,94 @@
+pub const PLACEHOLDERS: &[(&str, &str)] = &[
+    ("name", "Nombre del archivo"),
+    ("title", "Título del documento (o el nombre si no tiene)"),
+    ("page", "Página del visor"),
+    ("pages", "Total de páginas del visor"),
+    ("pdfpage", "Página del PDF"),
+    ("percent", "Porcentaje leído"),
+    ("matches", "Coincidencia actual y total de la búsqueda"),
+    ("clock", "Hora local"),
+    ("zoom", "Ancho del texto respecto a la terminal"),
+];
+
+pub const SECTION: &str = "%=";
+
+pub fn placeholders(format: &str) -> impl Iterator<Item = &str> {
+    format.split('{').skip(1).filter_map(|rest| rest.split_once('}').map(|(name, _)| name))
+}
+
+pub fn unknown(format: &str) -> Vec<String> {
+    placeholders(format)
+        .filter(|name| !PLACEHOLDERS.iter().any(|(known, _)| known == name))
+        .map(|name| format!("{{{}}}", name))
+        .collect()
+}
+
+pub fn uses(format: &str, placeholder: &str) -> bool {
+    placeholders(format).any(|name| name == placeholder)
+}
+
+pub fn expand(format: &str, value: impl Fn(&str) -> Option<String>) -> String {
+    let mut out = String::new();
+    let mut rest = format;
+    while let Some(open) = rest.find('{') {
+        out.push_str(&rest[..open]);
+        let after = &rest[open + 1..];
+        match after.split_once('}').and_then(|(name, tail)| Some((value(name)?, tail))) {
+            Some((expanded, tail)) => {
+                out.push_str(&expanded);
+                rest = tail;
+            }
+            None => {
+                out.push('{');
+                rest = after;
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+pub fn layout(line: &str, width: usize) -> String {
+    let sections: Vec<&str> = line.split(SECTION).collect();
+    let len = |text: &str| text.chars().count();
+    match sections.as_slice() {
+        [left, right] => {
+            let padding = width.saturating_sub(len(left) + len(right)).max(1);
+            format!("{}{}{}", left, " ".repeat(padding), right)
+        }
+        [left, center, right, ..] => {
+            let center_at = width.saturating_sub(len(center)) / 2;
+            let before = center_at.saturating_sub(len(left)).max(1);
+            let after = width.saturating_sub(len(left) + before + len(center) + len(right)).max(1);
+            format!("{}{}{}{}{}", left, " ".repeat(before), center, " ".repeat(after), right)
+        }
+        _ => line.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn placeholders_expand_and_unknown_ones_are_reported() {
+        let format = "{name} · {page}/{pages} · {percent}% · {zom}";
+        assert_eq!(unknown(format), ["{zom}"]);
+        let value = |name: &str| match name {
+            "name" => Some("tesis.pdf".to_string()),
+            "page" => Some("3".to_string()),
+            "pages" => Some("40".to_string()),
+            "percent" => Some("8".to_string()),
+            _ => None,
+        };
+        assert_eq!(expand(format, value), "tesis.pdf · 3/40 · 8% · {zom}");
+        assert!(uses("%={clock}", "clock") && !uses("{clock", "clock"));
+    }
+
+    #[test]
+    fn sections_align_left_center_and_right() {
+        assert_eq!(layout("a%=12:00", 10), "a    12:00");
+        assert_eq!(layout("ab%=mid%=z", 11), "ab  mid   z");
+        assert_eq!(layout("sin secciones", 40), "sin secciones");
+    }
+}
@@ -0,0 +1,77 @@
+use crossterm::style::Color;
+
+#[derive(Debug, Clone, Copy)]
+pub struct Theme {
+    pub link: Color,
+    pub selected_link_fg: Color,
+    pub selected_link_bg: Color,
+    pub cursor_line: Color,
+    pub ruler: Color,
+    pub ruler_dim: Color,
+    pub diff_added: Color,
+    pub diff_removed: Color,
+    pub highlights: [Color; 4],
+    pub search_match_fg: Color,
+    pub search_match_bg: Color,
+    pub table: Color,
+    pub selection: Color,
+    pub code: [Color; 4],
+}
+
+impl Theme {
+    pub fn highlight(&self, tag: &str) -> Color {
+        let index = crate::notes::HIGHLIGHT_COLORS.iter().position(|c| *c == tag).unwrap_or(0);
+        self.highlights[index]
+    }
+
+    pub fn code(&self, token: crate::code::Token) -> Color {
+        self.code[token as usize]
+    }
+
+    pub fn named(name: &str) -> Theme {
+        match name {
+            "light" => Theme {
+                link: Color::DarkBlue,
+                selected_link_fg: Color::White,
+                selected_link_bg: Color::DarkBlue,
+                cursor_line: Color::Rgb { r: 255, g: 244, b: 194 },
+                ruler: Color::Rgb { r: 255, g: 230, b: 150 },
+                ruler_dim: Color::Rgb { r: 150, g: 150, b: 150 },
+                diff_added: Color::DarkGreen,
+                diff_removed: Color::DarkRed,
+                highlights: [
+                    Color::Rgb { r: 255, g: 236, b: 139 },
+                    Color::Rgb { r: 190, g: 240, b: 180 },
+                    Color::Rgb { r: 180, g: 215, b: 255 },
+                    Color::Rgb { r: 255, g: 200, b: 225 },
+                ],
+                search_match_fg: Color::Black,
+                search_match_bg: Color::Rgb { r: 255, g: 200, b: 60 },
+                table: Color::Rgb { r: 60, g: 85, b: 120 },
+                selection: Color::Rgb { r: 173, g: 214, b: 255 },
+                code: [Color::DarkMagenta, Color::DarkGreen, Color::DarkGrey, Color::DarkCyan],
+            },
+            _ => Theme {
+                link: Color::Cyan,
+                selected_link_fg: Color::Black,
+                selected_link_bg: Color::Cyan,
+                cursor_line: Color::Rgb { r: 58, g: 58, b: 88 },
+                ruler: Color::Rgb { r: 85, g: 75, b: 30 },
+                ruler_dim: Color::Rgb { r: 110, g: 110, b: 110 },
+                diff_added: Color::Green,
+                diff_removed: Color::Red,
+                highlights: [
+                    Color::Rgb { r: 110, g: 95, b: 20 },
+                    Color::Rgb { r: 35, g: 90, b: 45 },
+                    Color::Rgb { r: 35, g: 65, b: 115 },
+                    Color::Rgb { r: 110, g: 45, b: 85 },
+                ],
+                search_match_fg: Color::Black,
+                search_match_bg: Color::Yellow,
+                table: Color::Rgb { r: 150, g: 175, b: 200 },
+                selection: Color::Rgb { r: 68, g: 90, b: 140 },
+                code: [Color::Magenta, Color::Green, Color::DarkGrey, Color::Cyan],
+            },
+        }
+    }
+}
@@ -0,0 +1,31 @@
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Default, Clone)]
+pub struct Timings {
+    pub steps: Vec<(&'static str, Duration)>,
+}
+
+impl Timings {
+    pub fn measure<T>(&mut self, step: &'static str, work: impl FnOnce() -> T) -> T {
+        let start = Instant::now();
+        let result = work();
+        self.steps.push((step, start.elapsed()));
+        result
+    }
+
+    pub fn get(&self, step: &str) -> Option<Duration> {
+        self.steps.iter().find(|(name, _)| *name == step).map(|(_, duration)| *duration)
+    }
+
+    pub fn lines(&self, pdf_pages: usize) -> Vec<String> {
+        let mut lines: Vec<String> = self.steps.iter()
+            .map(|(step, duration)| format!("{:<14} {:>8.1} ms", step, duration.as_secs_f64() * 1000.0))
+            .collect();
+        let total: Duration = self.steps.iter().map(|(_, duration)| *duration).sum();
+        lines.push(format!("{:<14} {:>8.1} ms", "total", total.as_secs_f64() * 1000.0));
+        if let Some(extraction) = self.get("extracción").filter(|d| !d.is_zero()) {
+            lines.push(format!("{:<14} {:>8.1} páginas/s", "", pdf_pages as f64 / extraction.as_secs_f64()));
+        }
+        lines
+    }
+}
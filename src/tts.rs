@@ -0,0 +1,57 @@
+use std::io::{ErrorKind, Write};
+use std::process::{Child, Command, Stdio};
+
+const BACKENDS: &[(&str, &[&str])] = if cfg!(target_os = "macos") {
+    &[("say", &["-f", "-"])]
+} else if cfg!(windows) {
+    &[(
+        "powershell",
+        &[
+            "-NoProfile",
+            "-Command",
+            "Add-Type -AssemblyName System.Speech; (New-Object System.Speech.Synthesis.SpeechSynthesizer).Speak([Console]::In.ReadToEnd())",
+        ],
+    )]
+} else {
+    &[("espeak-ng", &["--stdin"]), ("espeak", &["--stdin"]), ("spd-say", &["-e", "-w"])]
+};
+
+pub struct Speaker {
+    child: Child,
+    pub backend: &'static str,
+}
+
+impl Speaker {
+    pub fn speak(text: &str) -> Result<Speaker, String> {
+        for (program, args) in BACKENDS {
+            let spawned = Command::new(program)
+                .args(*args)
+                .stdin(Stdio::piped())
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .spawn();
+            let mut child = match spawned {
+                Ok(child) => child,
+                Err(e) if e.kind() == ErrorKind::NotFound => continue,
+                Err(e) => return Err(format!("No se pudo iniciar {}: {}", program, e)),
+            };
+            if let Some(mut stdin) = child.stdin.take() {
+                let _ = stdin.write_all(text.as_bytes());
+            }
+            return Ok(Speaker { child, backend: program });
+        }
+        let names: Vec<&str> = BACKENDS.iter().map(|(program, _)| *program).collect();
+        Err(format!("No se encontró un motor de voz ({})", names.join(", ")))
+    }
+
+    pub fn finished(&mut self) -> bool {
+        !matches!(self.child.try_wait(), Ok(None))
+    }
+}
+
+impl Drop for Speaker {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
@@ -0,0 +1,169 @@
+use std::ops::Range;
+use std::process::{Command, Stdio};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct UrlMatch {
+    pub ranges: Vec<Range<usize>>,
+    pub url: String,
+}
+
+const SCHEMES: [&str; 3] = ["https://", "http://", "www."];
+const BREAK_CHARS: &[char] = &['/', '?', '&', '=', '#', '%', '_', '.', '~'];
+const TRAILING: &[char] = &['.', ',', ';', ':', '!', '?', '\'', '"'];
+
+fn is_url_char(c: char) -> bool {
+    !c.is_whitespace() && !matches!(c, '<' | '>' | '"' | '`' | '{' | '}' | '|' | '\\' | '^')
+}
+
+pub fn find_urls(text: &str, width: usize) -> Vec<UrlMatch> {
+    let mut matches = Vec::new();
+    let mut from = 0;
+
+    while let Some((start, _)) = SCHEMES.iter()
+        .filter_map(|scheme| text[from..].find(scheme).map(|pos| (from + pos, scheme)))
+        .min_by_key(|(pos, _)| *pos)
+    {
+        let preceded_by_word = text[..start].chars().next_back().is_some_and(|c| c.is_alphanumeric());
+        if preceded_by_word {
+            from = start + 1;
+            continue;
+        }
+
+        let mut ranges = Vec::new();
+        let mut url = String::new();
+        let mut fragment_start = start;
+        loop {
+            let fragment_end = text[fragment_start..]
+                .find(|c: char| !is_url_char(c))
+                .map_or(text.len(), |pos| fragment_start + pos);
+            let fragment = &text[fragment_start..fragment_end];
+            ranges.push(fragment_start..fragment_end);
+
+            match continuation(text, fragment_start, fragment_end, width) {
+                Some(next_start) => {
+                    url.push_str(if continues_word(fragment, &text[next_start..]) {
+                        &fragment[..fragment.len() - 1]
+                    } else {
+                        fragment
+                    });
+                    fragment_start = next_start;
+                }
+                None => {
+                    url.push_str(fragment);
+                    break;
+                }
+            }
+        }
+
+        trim_trailing(&mut url, &mut ranges);
+        from = ranges.last().map_or(start + 1, |range| range.end.max(start + 1));
+        if SCHEMES.iter().any(|scheme| url.len() > scheme.len() && url.starts_with(scheme)) {
+            matches.push(UrlMatch { ranges, url });
+        }
+    }
+    matches
+}
+
+fn continuation(text: &str, fragment_start: usize, fragment_end: usize, width: usize) -> Option<usize> {
+    if !text[fragment_end..].starts_with('\n') {
+        return None;
+    }
+    let next_start = fragment_end + 1;
+    let next_token = text[next_start..].split(|c: char| c.is_whitespace()).next().unwrap_or("");
+    if next_token.is_empty() || !next_token.chars().all(is_url_char) {
+        return None;
+    }
+
+    let line_start = text[..fragment_start].rfind('\n').map_or(0, |pos| pos + 1);
+    let line_full = text[line_start..fragment_end].chars().count() >= width;
+    let fragment = &text[fragment_start..fragment_end];
+    let first = next_token.chars().next()?;
+    let looks_continued = fragment.ends_with('-')
+        || (fragment.ends_with(BREAK_CHARS)
+            && (first.is_ascii_lowercase() || first.is_ascii_digit() || BREAK_CHARS.contains(&first)));
+
+    (line_full || looks_continued).then_some(next_start)
+}
+
+fn continues_word(fragment: &str, next: &str) -> bool {
+    let Some(before_hyphen) = fragment.strip_suffix('-') else {
+        return false;
+    };
+    before_hyphen.chars().next_back().is_some_and(char::is_alphabetic)
+        && next.chars().next().is_some_and(char::is_lowercase)
+}
+
+fn trim_trailing(url: &mut String, ranges: &mut Vec<Range<usize>>) {
+    loop {
+        let last = url.chars().next_back();
+        let unbalanced_paren = last == Some(')') && url.matches('(').count() < url.matches(')').count();
+        if !(last.is_some_and(|c| TRAILING.contains(&c)) || unbalanced_paren) {
+            break;
+        }
+        url.pop();
+        if let Some(range) = ranges.last_mut() {
+            range.end -= 1;
+            if range.start == range.end {
+                ranges.pop();
+            }
+        }
+    }
+}
+
+pub fn open_url(url: &str) -> std::io::Result<()> {
+    let url = if url.contains("://") { url.to_string() } else { format!("https://{}", url) };
+    let mut command = if cfg!(target_os = "macos") {
+        Command::new("open")
+    } else if cfg!(windows) {
+        let mut command = Command::new("rundll32");
+        command.arg("url.dll,FileProtocolHandler");
+        command
+    } else {
+        Command::new("xdg-open")
+    };
+    command.arg(url)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn urls(text: &str, width: usize) -> Vec<String> {
+        find_urls(text, width).into_iter().map(|found| found.url).collect()
+    }
+
+    #[test]
+    fn joins_urls_wrapped_at_the_line_width() {
+        let text = "ver https://example.com/a/\nlargo/ruta y más";
+        assert_eq!(urls(text, 26), ["https://example.com/a/largo/ruta"]);
+        let found = &find_urls(text, 26)[0];
+        assert_eq!(found.ranges.len(), 2);
+        assert_eq!(&text[found.ranges[1].clone()], "largo/ruta");
+    }
+
+    #[test]
+    fn trims_trailing_punctuation_and_unbalanced_parens() {
+        assert_eq!(urls("(véase www.example.org/x).", 80), ["www.example.org/x"]);
+        assert_eq!(urls("en https://es.wikipedia.org/wiki/Rust_(lenguaje), hoy", 80),
+            ["https://es.wikipedia.org/wiki/Rust_(lenguaje)"]);
+        assert_eq!(urls("playwww.example.org no es un enlace", 80), Vec::<String>::new());
+
+        let text = "ver https://example.com/ruta\n). Y sigue";
+        let found = &find_urls(text, 28)[0];
+        assert_eq!(found.url, "https://example.com/ruta");
+        assert_eq!(found.ranges.len(), 1);
+        assert_eq!(&text[found.ranges[0].clone()], found.url);
+    }
+
+    #[test]
+    fn drops_a_hyphen_only_when_the_word_continues() {
+        assert_eq!(urls("https://example.com/docu-\nmentos", 80), ["https://example.com/documentos"]);
+        assert_eq!(urls("https://example.com/v1-\n/final", 80), ["https://example.com/v1-/final"]);
+        assert_eq!(urls("https://example.com/rev-\n2020/", 25), ["https://example.com/rev-2020/"]);
+    }
+}